@@ -3,39 +3,131 @@
 //! The Backend holds server state and implements the `LanguageServer` trait from tower-lsp.
 //! It maintains document content in memory and delegates validation/formatting to sea-core.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use sea_core::parse_to_graph;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
-use tower_lsp::jsonrpc::Result;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
 use lru::LruCache;
 
+use crate::code_actions::{self, APPLY_NAMESPACE_COMMAND};
 use crate::completion;
-use crate::diagnostics::parse_error_to_diagnostic;
-use crate::formatting::{extract_format_options, format_document, LspFormatConfig};
-use crate::hover::markdown_renderer;
-use crate::hover::symbol_resolver::{build_hover_model, HoverBuildInput};
-use crate::hover::{DetailLevel, HoverPlusParams, HoverPlusResponse};
+use crate::diagnostics::{
+    ambiguous_reference_diagnostics, collect_parse_diagnostics, flow_unit_diagnostics,
+    info_diagnostic, missing_field_diagnostics, mixed_indentation_diagnostics,
+    orphaned_resource_diagnostics, undefined_instance_diagnostics, unresolved_import_diagnostics,
+    unresolved_imports, unused_role_diagnostics,
+};
+use crate::flow_graph::{
+    self, DotParams, FlowEdge, FlowsParams, Neighborhood, NeighborhoodParams, WhatProvides,
+    WhatProvidesParams,
+};
+use crate::folding;
+use crate::formatting::{
+    extract_format_options, format_check, format_document, FormatCheckParams, FormatCheckResult,
+    LspFormatConfig,
+};
+use crate::graph_diff::{self, GraphDiff, GraphDiffParams};
+use crate::graph_stats::{self, GraphStats, GraphStatsParams};
+use crate::hover::markdown_renderer::{self, MarkdownRenderResult};
+use crate::hover::plaintext_renderer;
+use crate::hover::symbol_resolver::{
+    build_hover_model, build_moniker, build_quick_info, build_resolution, mark_stale,
+    HoverBuildInput,
+};
+use crate::hover::{
+    DetailLevel, HoverBudget, HoverModel, HoverPlusParams, HoverPlusResponse, MonikerParams,
+    QuickInfo, QuickInfoParams, ResolveParams, SymbolMoniker, SymbolResolution,
+    DEFAULT_MAX_JSON_BYTES, DEFAULT_MAX_MARKDOWN_BYTES,
+};
+use crate::inline_values;
 use crate::line_index::LineIndex;
-use crate::navigation;
+use crate::navigation::{
+    self, BreadcrumbItem, BreadcrumbParams, GraphReferences, GraphReferencesParams,
+    RenameValidateParams, RenameValidation,
+};
+use crate::outline::{self, Outline, OutlineParams};
 use crate::semantic_index::SemanticIndex;
+use crate::workspace::{
+    self, IndexedFile, WorkspaceScan, EXTRACT_TO_FILE_COMMAND, NEW_FILE_COMMAND,
+    RENAME_NAMESPACE_COMMAND,
+};
 
 /// Server-side configuration for DomainForge.
 ///
 /// This matches the configuration schema defined in the VS Code extension's
 /// package.json contributes.configuration section.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DomainForgeConfig {
     /// Formatting configuration
     #[serde(default)]
     pub formatting: FormattingConfig,
+    /// Hover payload configuration
+    #[serde(default)]
+    pub hover: HoverConfig,
+    /// Completion configuration
+    #[serde(default)]
+    pub completion: CompletionConfig,
+    /// Validation configuration
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// Extensions of files treated as SEA sources (default: `["sea"]`). File renames
+    /// are reported for the extensions set at `initialize`.
+    #[serde(default = "default_file_extensions")]
+    pub file_extensions: Vec<String>,
+    /// Documents larger than this are not parsed or indexed (default: 5 MiB)
+    #[serde(default = "default_max_document_bytes")]
+    pub max_document_bytes: usize,
+    /// Hover, completion and formatting requests handled at once; the rest queue
+    /// (default: 8)
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: NonZeroUsize,
+    /// Language features the server offers; all on by default
+    #[serde(default)]
+    pub features: FeaturesConfig,
+}
+
+/// Default for `maxDocumentBytes`.
+pub const DEFAULT_MAX_DOCUMENT_BYTES: usize = 5 * 1024 * 1024;
+
+fn default_max_document_bytes() -> usize {
+    DEFAULT_MAX_DOCUMENT_BYTES
+}
+
+fn default_max_concurrent_requests() -> NonZeroUsize {
+    NonZeroUsize::new(8).expect("non-zero request concurrency")
+}
+
+fn default_file_extensions() -> Vec<String> {
+    vec![workspace::DEFAULT_FILE_EXTENSION.to_string()]
+}
+
+impl Default for DomainForgeConfig {
+    fn default() -> Self {
+        Self {
+            formatting: FormattingConfig::default(),
+            hover: HoverConfig::default(),
+            completion: CompletionConfig::default(),
+            validation: ValidationConfig::default(),
+            file_extensions: default_file_extensions(),
+            max_document_bytes: default_max_document_bytes(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            features: FeaturesConfig::default(),
+        }
+    }
 }
 
 /// Formatting-specific configuration.
@@ -84,6 +176,295 @@ impl From<&FormattingConfig> for LspFormatConfig {
     }
 }
 
+/// Hover-specific configuration.
+///
+/// Embedders targeting constrained chat contexts can shrink these budgets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoverConfig {
+    /// Byte budget for rendered hover markdown (default: 32 KiB)
+    #[serde(default = "default_max_markdown_bytes")]
+    pub max_markdown_bytes: usize,
+    /// Byte budget for the serialized hoverPlus model (default: 128 KiB)
+    #[serde(default = "default_max_json_bytes")]
+    pub max_json_bytes: usize,
+    /// Entries kept in the hover model cache (default: 512)
+    #[serde(default = "default_model_cache_size")]
+    pub model_cache_size: NonZeroUsize,
+    /// Entries kept in the rendered hover markdown cache (default: 256)
+    #[serde(default = "default_markdown_cache_size")]
+    pub markdown_cache_size: NonZeroUsize,
+    /// Resolve hovers, marked stale, and completions against the last graph that
+    /// parsed while the current text doesn't (default: true)
+    #[serde(default = "default_true")]
+    pub use_stale_graph: bool,
+    /// Detail level for plain hovers and for hoverPlus requests that don't name
+    /// one; unset falls back to the `hover.detailLevel` initialization option
+    #[serde(default)]
+    pub detail_level: Option<DetailLevel>,
+    /// Least certain resolution a hover is returned for (default: `any`)
+    #[serde(default)]
+    pub min_confidence: MinConfidence,
+}
+
+fn default_max_markdown_bytes() -> usize {
+    DEFAULT_MAX_MARKDOWN_BYTES
+}
+
+fn default_max_json_bytes() -> usize {
+    DEFAULT_MAX_JSON_BYTES
+}
+
+fn default_model_cache_size() -> NonZeroUsize {
+    NonZeroUsize::new(512).expect("non-zero hover model cache size")
+}
+
+fn default_markdown_cache_size() -> NonZeroUsize {
+    NonZeroUsize::new(256).expect("non-zero hover markdown cache size")
+}
+
+impl Default for HoverConfig {
+    fn default() -> Self {
+        Self {
+            max_markdown_bytes: default_max_markdown_bytes(),
+            max_json_bytes: default_max_json_bytes(),
+            model_cache_size: default_model_cache_size(),
+            markdown_cache_size: default_markdown_cache_size(),
+            use_stale_graph: default_true(),
+            detail_level: None,
+            min_confidence: MinConfidence::default(),
+        }
+    }
+}
+
+/// Completion-specific configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionConfig {
+    /// Maximum items per completion response (default: 200)
+    #[serde(default = "default_max_completion_items")]
+    pub max_items: usize,
+}
+
+fn default_max_completion_items() -> usize {
+    completion::DEFAULT_MAX_ITEMS
+}
+
+impl Default for CompletionConfig {
+    fn default() -> Self {
+        Self {
+            max_items: default_max_completion_items(),
+        }
+    }
+}
+
+/// Validation-specific configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationConfig {
+    /// Document events that publish diagnostics (default: `both`)
+    #[serde(default)]
+    pub trigger: ValidationTrigger,
+    /// Report resources that no flow uses as `W700` (default: false)
+    #[serde(default)]
+    pub orphaned_resources: bool,
+    /// Report roles nothing refers to as `W710` (default: false)
+    #[serde(default)]
+    pub unused_roles: bool,
+    /// Report instances missing a field every sibling instance sets as `W500`
+    /// (default: false). sea-core has no field schema, so this is an inference.
+    #[serde(default)]
+    pub inferred_required_fields: bool,
+}
+
+/// Switches for whole language features, for embedders that want only some of them.
+///
+/// Read once from the `features` initialization option: disabled features are left
+/// out of the server capabilities and their handlers return nothing. Later
+/// configuration changes keep the switches set at `initialize`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturesConfig {
+    /// `textDocument/hover`, `textDocument/hoverPlus`, monikers, inline values,
+    /// `sea/quickInfo` and `sea/resolve`
+    #[serde(default = "default_true")]
+    pub hover: bool,
+    #[serde(default = "default_true")]
+    pub completion: bool,
+    /// Published and pulled diagnostics, and `sea/diagnosticsAll`
+    #[serde(default = "default_true")]
+    pub diagnostics: bool,
+    #[serde(default = "default_true")]
+    pub code_actions: bool,
+    /// Document formatting and `sea/formatCheck`
+    #[serde(default = "default_true")]
+    pub formatting: bool,
+    /// Definition, references, workspace symbols, folding, linked editing and type
+    /// hierarchy, including supertypes and subtypes, plus `sea/breadcrumb`,
+    /// `sea/references` and `sea/workspaceReferences`
+    #[serde(default = "default_true")]
+    pub navigation: bool,
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            hover: true,
+            completion: true,
+            diagnostics: true,
+            code_actions: true,
+            formatting: true,
+            navigation: true,
+        }
+    }
+}
+
+/// Document events that publish diagnostics.
+///
+/// Documents are parsed on every change regardless, so the other language features
+/// always see the current text; only publishing is deferred.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationTrigger {
+    /// Publish when a document is opened or edited.
+    OnChange,
+    /// Publish only when a document is saved.
+    OnSave,
+    /// Publish on open, edit and save.
+    #[default]
+    Both,
+}
+
+impl ValidationTrigger {
+    /// Whether opening or editing a document publishes diagnostics.
+    pub fn on_change(self) -> bool {
+        self != Self::OnSave
+    }
+
+    /// Whether saving a document publishes diagnostics.
+    pub fn on_save(self) -> bool {
+        self != Self::OnChange
+    }
+}
+
+/// Least certain symbol resolution that still produces a hover.
+///
+/// Agents that act on hovers can refuse guesses: below the threshold the hover is
+/// empty rather than describing an ambiguous or unresolved symbol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MinConfidence {
+    /// Only symbols resolved to exactly one declaration of the current graph.
+    Exact,
+    /// Also symbols with several candidate declarations, or resolved against the
+    /// last graph that parsed.
+    Ambiguous,
+    /// Every hover, including unresolved fallbacks.
+    #[default]
+    Any,
+}
+
+impl MinConfidence {
+    /// Whether a model's `resolution_confidence` meets this threshold.
+    pub fn allows(self, confidence: &str) -> bool {
+        match self {
+            Self::Exact => confidence == "exact",
+            Self::Ambiguous => matches!(confidence, "exact" | "ambiguous" | "stale"),
+            Self::Any => true,
+        }
+    }
+}
+
+impl From<&HoverConfig> for HoverBudget {
+    fn from(config: &HoverConfig) -> Self {
+        HoverBudget {
+            max_markdown_bytes: config.max_markdown_bytes,
+            max_json_bytes: config.max_json_bytes,
+        }
+    }
+}
+
+/// How long `did_change` waits for further edits before validating.
+const VALIDATION_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How long diagnostics wait to be published, so bursts of validations of one
+/// document send only the last result.
+const PUBLISH_COALESCE_WINDOW: Duration = Duration::from_millis(50);
+
+/// Latest unpublished diagnostics per document.
+///
+/// The first submission for a document starts a timer; later submissions within the
+/// window replace the pending diagnostics, and the timer publishes whatever is
+/// pending when it fires.
+#[derive(Debug, Default, Clone)]
+struct PublishCoalescer {
+    pending: Arc<Mutex<HashMap<Url, Vec<Diagnostic>>>>,
+}
+
+impl PublishCoalescer {
+    async fn submit(&self, client: &Client, uri: Url, diagnostics: Vec<Diagnostic>) {
+        let scheduled = self
+            .pending
+            .lock()
+            .await
+            .insert(uri.clone(), diagnostics)
+            .is_some();
+        if scheduled {
+            return;
+        }
+        let pending = Arc::clone(&self.pending);
+        let client = client.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(PUBLISH_COALESCE_WINDOW).await;
+            let diagnostics = pending.lock().await.remove(&uri);
+            if let Some(diagnostics) = diagnostics {
+                client.publish_diagnostics(uri, diagnostics, None).await;
+            }
+        });
+    }
+
+    /// Drop the pending publish for `uri`, if any.
+    async fn cancel(&self, uri: &Url) {
+        self.pending.lock().await.remove(uri);
+    }
+
+    async fn cancel_all(&self) {
+        self.pending.lock().await.clear();
+    }
+}
+
+/// Per-document generation counters that debounce validation on change.
+///
+/// Each change takes a ticket; after the debounce delay only the holder of the
+/// latest ticket validates, so earlier pending validations are effectively cancelled.
+#[derive(Debug, Default, Clone)]
+struct ValidationDebounce {
+    generations: Arc<Mutex<HashMap<Url, u64>>>,
+}
+
+impl ValidationDebounce {
+    /// Take a new ticket for `uri`, superseding any pending one.
+    async fn schedule(&self, uri: &Url) -> u64 {
+        let mut generations = self.generations.lock().await;
+        let generation = generations.entry(uri.clone()).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    async fn is_latest(&self, uri: &Url, ticket: u64) -> bool {
+        self.generations.lock().await.get(uri) == Some(&ticket)
+    }
+
+    async fn forget(&self, uri: &Url) {
+        self.generations.lock().await.remove(uri);
+    }
+
+    /// Invalidate every pending ticket.
+    async fn forget_all(&self) {
+        self.generations.lock().await.clear();
+    }
+}
+
 /// State for a single document.
 ///
 /// This struct holds both the source text and the parsed semantic graph,
@@ -100,34 +481,290 @@ struct DocumentState {
     graph: Option<sea_core::Graph>,
     /// Semantic index of definitions/references for navigation and hover
     semantic_index: Option<SemanticIndex>,
+    /// Whether the text exceeded `maxDocumentBytes` and was left unparsed
+    oversized: bool,
+    /// The graph of the last version that parsed, kept while the current text fails to parse
+    last_good_graph: Option<sea_core::Graph>,
 }
 
 impl DocumentState {
     /// Create a new DocumentState from text and version.
     ///
     /// Attempts to parse the text into a Graph. If parsing fails,
-    /// the graph field will be None.
-    fn new(text: String, version: i32) -> Self {
-        let graph = parse_to_graph(&text).ok();
-        let semantic_index = Some(SemanticIndex::build(&text));
+    /// the graph field will be None. Text larger than `max_bytes` is
+    /// stored without being parsed or indexed.
+    fn new(text: String, version: i32, max_bytes: usize) -> Self {
+        let oversized = text.len() > max_bytes;
+        let (graph, semantic_index) = if oversized {
+            (None, None)
+        } else {
+            (
+                parse_to_graph(&text).ok(),
+                Some(SemanticIndex::build(&text)),
+            )
+        };
         Self {
             line_index: LineIndex::new(&text),
             text,
             version,
             graph,
             semantic_index,
+            oversized,
+            last_good_graph: None,
         }
     }
 
-    /// Update the document with new text and version.
-    ///
-    /// Re-parses the text and updates the cached graph.
-    fn update(&mut self, text: String, version: i32) {
-        self.text = text;
-        self.version = version;
-        self.graph = parse_to_graph(&self.text).ok();
-        self.semantic_index = Some(SemanticIndex::build(&self.text));
-        self.line_index = LineIndex::new(&self.text);
+    /// Keep `previous`'s graph as the last good one when this version failed to
+    /// parse, so hovers still resolve mid-edit.
+    fn retain_last_good_graph(&mut self, previous: DocumentState) {
+        if self.graph.is_none() && !self.oversized {
+            self.last_good_graph = previous.graph.or(previous.last_good_graph);
+        }
+    }
+
+    /// Graph to resolve hovers and completions against and whether it is stale: the
+    /// current parse, or with `use_stale_graph` the last good graph while the text
+    /// doesn't parse.
+    fn hover_graph(&self, use_stale_graph: bool) -> (Option<&sea_core::Graph>, bool) {
+        match (&self.graph, &self.last_good_graph) {
+            (Some(graph), _) => (Some(graph), false),
+            (None, Some(graph)) if use_stale_graph => (Some(graph), true),
+            _ => (None, false),
+        }
+    }
+
+    /// Parse `text` into a new DocumentState on the blocking thread pool, so large
+    /// documents don't stall the async runtime. Returns `None` if parsing panicked.
+    async fn parse(text: String, version: i32, max_bytes: usize) -> Option<Self> {
+        match tokio::task::spawn_blocking(move || Self::new(text, version, max_bytes)).await {
+            Ok(state) => Some(state),
+            Err(e) => {
+                log::error!("Failed to parse document version {}: {}", version, e);
+                None
+            }
+        }
+    }
+
+    /// Diagnostics for the current text: semantic checks when the text parses,
+    /// otherwise the syntax errors.
+    fn diagnostics(&self, uri: &Url, config: &DomainForgeConfig) -> Vec<Diagnostic> {
+        if self.oversized {
+            return vec![info_diagnostic(
+                Range::default(),
+                format!(
+                    "Document is {} bytes, over the maxDocumentBytes limit; \
+                     language features are disabled for this file",
+                    self.text.len()
+                ),
+                "I001".to_string(),
+            )];
+        }
+        let Some(graph) = self.graph.as_ref() else {
+            // We don't store the parse error in DocumentState, so re-parse to collect it
+            return collect_parse_diagnostics(&self.text);
+        };
+        let Some(index) = self.semantic_index.as_ref() else {
+            return vec![];
+        };
+        let mut diagnostics = flow_unit_diagnostics(graph, index, &self.line_index);
+        diagnostics.extend(ambiguous_reference_diagnostics(
+            uri,
+            graph,
+            index,
+            &self.line_index,
+        ));
+        diagnostics.extend(undefined_instance_diagnostics(
+            graph,
+            index,
+            &self.line_index,
+        ));
+        diagnostics.extend(unresolved_import_diagnostics(
+            uri,
+            index,
+            &self.line_index,
+            &config.file_extensions,
+        ));
+        diagnostics.extend(mixed_indentation_diagnostics(
+            &self.text,
+            config.formatting.use_tabs,
+        ));
+        if config.validation.orphaned_resources {
+            diagnostics.extend(orphaned_resource_diagnostics(
+                graph,
+                index,
+                &self.line_index,
+            ));
+        }
+        if config.validation.unused_roles {
+            diagnostics.extend(unused_role_diagnostics(graph, index, &self.line_index));
+        }
+        if config.validation.inferred_required_fields {
+            diagnostics.extend(missing_field_diagnostics(graph, index, &self.line_index));
+        }
+        diagnostics
+    }
+
+    /// Pull-diagnostics result id. Diagnostics depend on the text, the opt-in checks,
+    /// the configured indentation and which imported files exist, so hashing those
+    /// tells whether a previously reported result is current.
+    fn result_id(&self, uri: &Url, config: &DomainForgeConfig) -> String {
+        diagnostics_result_id(uri, &self.text, self.semantic_index.as_ref(), config)
+    }
+
+    /// Pull-diagnostics report for `uri`, `Unchanged` when `previous_result_id` is current.
+    fn workspace_report(
+        &self,
+        uri: Url,
+        version: Option<i64>,
+        previous_result_id: Option<&String>,
+        config: &DomainForgeConfig,
+    ) -> WorkspaceDocumentDiagnosticReport {
+        let result_id = self.result_id(&uri, config);
+        if previous_result_id == Some(&result_id) {
+            return unchanged_report(uri, version, result_id);
+        }
+        let items = self.diagnostics(&uri, config);
+        WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+            uri,
+            version,
+            full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                result_id: Some(result_id),
+                items,
+            },
+        })
+    }
+}
+
+/// Store `state`, parsed for a change to `uri`, unless the document was closed or
+/// a newer version was stored while it parsed. Returns whether it was stored.
+fn store_changed_document(
+    documents: &mut HashMap<Url, DocumentState>,
+    uri: &Url,
+    state: DocumentState,
+) -> bool {
+    match documents.get_mut(uri) {
+        Some(current) if current.version <= state.version => {
+            let previous = std::mem::replace(current, state);
+            current.retain_last_good_graph(previous);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// [`DocumentState::result_id`] for `text` and its semantic `index`.
+fn diagnostics_result_id(
+    uri: &Url,
+    text: &str,
+    index: Option<&SemanticIndex>,
+    config: &DomainForgeConfig,
+) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(text.as_bytes());
+    hasher.update(&[
+        u8::from(config.validation.orphaned_resources),
+        u8::from(config.validation.unused_roles),
+        u8::from(config.validation.inferred_required_fields),
+        u8::from(config.formatting.use_tabs),
+    ]);
+    if let Some(index) = index {
+        for import in unresolved_imports(uri, index, &config.file_extensions) {
+            hasher.update(&import.range.start.to_le_bytes());
+        }
+    }
+    hasher.finalize().to_hex()[..16].to_string()
+}
+
+fn unchanged_report(
+    uri: Url,
+    version: Option<i64>,
+    result_id: String,
+) -> WorkspaceDocumentDiagnosticReport {
+    WorkspaceDocumentDiagnosticReport::Unchanged(WorkspaceUnchangedDocumentDiagnosticReport {
+        uri,
+        version,
+        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport { result_id },
+    })
+}
+
+/// Pull-diagnostics report for a workspace file that isn't open. Its cached index
+/// tells whether `previous_result_id` is current, so only changed files are
+/// reparsed. Blocking: reads the file and checks its imports on disk.
+fn indexed_file_report(
+    uri: Url,
+    file: &IndexedFile,
+    previous_result_id: Option<&String>,
+    config: &DomainForgeConfig,
+) -> Option<WorkspaceDocumentDiagnosticReport> {
+    let text = std::fs::read_to_string(uri.to_file_path().ok()?).ok()?;
+    let result_id = diagnostics_result_id(&uri, &text, Some(&file.index), config);
+    if previous_result_id == Some(&result_id) {
+        return Some(unchanged_report(uri, None, result_id));
+    }
+    let state = DocumentState::new(text, 0, config.max_document_bytes);
+    Some(state.workspace_report(uri, None, None, config))
+}
+
+/// Graph and semantic index of one side of `sea/graphDiff`, under the same size
+/// limit as open documents. Blocking.
+fn parse_version(
+    label: &str,
+    text: &str,
+    max_bytes: usize,
+) -> std::result::Result<(sea_core::Graph, SemanticIndex), String> {
+    if text.len() > max_bytes {
+        return Err(format!(
+            "{} text is {} bytes, over the maxDocumentBytes limit",
+            label,
+            text.len()
+        ));
+    }
+    let graph =
+        parse_to_graph(text).map_err(|e| format!("{} text does not parse: {}", label, e))?;
+    Ok((graph, SemanticIndex::build(text)))
+}
+
+/// `uri at line:character`, the verbose trace detail for positional requests.
+fn at(uri: &Url, position: Position) -> String {
+    format!("{} at {}:{}", uri, position.line, position.character)
+}
+
+/// Document a workspace diagnostic report is about.
+fn report_uri(report: &WorkspaceDocumentDiagnosticReport) -> &Url {
+    match report {
+        WorkspaceDocumentDiagnosticReport::Full(report) => &report.uri,
+        WorkspaceDocumentDiagnosticReport::Unchanged(report) => &report.uri,
+    }
+}
+
+/// Compute the diagnostics for `state` and hand them to the coalescer, unless
+/// diagnostics are switched off.
+async fn publish_validation(
+    client: &Client,
+    coalescer: &PublishCoalescer,
+    config: &DomainForgeConfig,
+    uri: Url,
+    state: &DocumentState,
+) {
+    if !config.features.diagnostics {
+        return;
+    }
+    let diagnostics = state.diagnostics(&uri, config);
+    log::debug!("Validated {}: {} diagnostic(s)", uri, diagnostics.len());
+
+    coalescer.submit(client, uri, diagnostics).await;
+}
+
+/// Overlay pulled `settings` onto `config` key by key, so sections the client leaves
+/// out keep their current values.
+fn merge_settings(config: &mut Value, settings: Value) {
+    match (config, settings) {
+        (Value::Object(config), Value::Object(settings)) => {
+            for (key, value) in settings {
+                merge_settings(config.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (config, settings) => *config = settings,
     }
 }
 
@@ -137,58 +774,121 @@ impl DocumentState {
 /// - `client`: The LSP client handle for sending notifications
 /// - `documents`: In-memory storage of open document contents and parsed graphs
 /// - `config`: Server configuration synced from the client
+/// - `workspace_files`: Index of `.sea` files found on disk at startup
 pub struct Backend {
     /// The LSP client handle for sending diagnostics and other notifications
     client: Client,
     /// In-memory storage of open document state (text + parsed graph), keyed by document URI
-    documents: RwLock<HashMap<Url, DocumentState>>,
-    /// Server configuration, updated via workspace/didChangeConfiguration
-    config: RwLock<DomainForgeConfig>,
+    documents: Arc<RwLock<HashMap<Url, DocumentState>>>,
+    /// Server configuration, pulled via workspace/configuration on `initialized` and
+    /// updated via workspace/didChangeConfiguration
+    config: Arc<RwLock<DomainForgeConfig>>,
+    /// Workspace folders announced by the client in `initialize`
+    workspace_roots: RwLock<Vec<PathBuf>>,
+    /// Whether the client advertised `window.workDoneProgress`
+    work_done_progress: AtomicBool,
+    /// Whether the client accepts dynamic registration of the type hierarchy
+    register_type_hierarchy: AtomicBool,
+    /// Whether the client answers `workspace/configuration` requests
+    pull_configuration: AtomicBool,
+    /// Whether the client pulls diagnostics (`textDocument.diagnostic`), in which
+    /// case none are pushed
+    pull_diagnostics: AtomicBool,
+    /// Semantic index of every `.sea` file in the workspace, keyed by file URI
+    workspace_files: RwLock<HashMap<Url, IndexedFile>>,
+    /// Pending debounced validations, keyed by document URI
+    validation_debounce: ValidationDebounce,
+    /// Diagnostics waiting out the publish window, keyed by document URI
+    publish_coalescer: PublishCoalescer,
+    /// Trace level set by the client via `initialize` or `$/setTrace`
+    trace: RwLock<TraceValue>,
+    /// Hover format picked from the client's `hover.contentFormat` preference
+    hover_format: RwLock<MarkupKind>,
+    /// Hover detail level from the `hover.detailLevel` initialization option
+    init_detail_level: RwLock<Option<DetailLevel>>,
+    /// Slots for expensive requests, sized by `maxConcurrentRequests`; replaced
+    /// rather than resized when the setting changes
+    request_permits: RwLock<Arc<Semaphore>>,
 
     hover_model_cache: Mutex<LruCache<HoverCacheKey, crate::hover::HoverModel>>,
-    hover_markdown_cache: Mutex<LruCache<HoverCacheKey, String>>,
+    hover_markdown_cache: Mutex<LruCache<HoverCacheKey, MarkdownRenderResult>>,
 }
 
 impl Backend {
     /// Create a new Backend instance with the given client handle.
     pub fn new(client: Client) -> Self {
+        let config = DomainForgeConfig::default();
+        let max_concurrent_requests = config.max_concurrent_requests;
         Self {
             client,
-            documents: RwLock::new(HashMap::new()),
-            config: RwLock::new(DomainForgeConfig::default()),
-            hover_model_cache: Mutex::new(LruCache::new(
-                NonZeroUsize::new(512).expect("non-zero hover model cache size"),
-            )),
-            hover_markdown_cache: Mutex::new(LruCache::new(
-                NonZeroUsize::new(256).expect("non-zero hover markdown cache size"),
-            )),
+            documents: Arc::new(RwLock::new(HashMap::new())),
+            hover_model_cache: Mutex::new(LruCache::new(config.hover.model_cache_size)),
+            hover_markdown_cache: Mutex::new(LruCache::new(config.hover.markdown_cache_size)),
+            config: Arc::new(RwLock::new(config)),
+            workspace_roots: RwLock::new(Vec::new()),
+            work_done_progress: AtomicBool::new(false),
+            register_type_hierarchy: AtomicBool::new(false),
+            pull_configuration: AtomicBool::new(false),
+            pull_diagnostics: AtomicBool::new(false),
+            workspace_files: RwLock::new(HashMap::new()),
+            validation_debounce: ValidationDebounce::default(),
+            publish_coalescer: PublishCoalescer::default(),
+            trace: RwLock::new(TraceValue::Off),
+            hover_format: RwLock::new(MarkupKind::Markdown),
+            init_detail_level: RwLock::new(None),
+            request_permits: RwLock::new(Arc::new(Semaphore::new(max_concurrent_requests.get()))),
         }
     }
 
+    /// Wait for one of the `maxConcurrentRequests` slots for hover, completion and
+    /// formatting. The slot frees when the permit is dropped, on every return path.
+    async fn request_permit(&self) -> OwnedSemaphorePermit {
+        let permits = self.request_permits.read().await.clone();
+        permits
+            .acquire_owned()
+            .await
+            .expect("request semaphore is never closed")
+    }
+
     /// Validate a document and publish diagnostics.
     ///
     /// Uses the cached graph from DocumentState if available. If parsing failed,
     /// the error was already captured during DocumentState creation.
     async fn validate_document(&self, uri: Url, state: &DocumentState) {
-        let diagnostics = if state.graph.is_some() {
-            // Parse succeeded - no diagnostics
-            log::debug!("Document validated successfully: {}", uri);
-            vec![]
-        } else {
-            // Parse failed - re-parse to get the error for diagnostics
-            // (We don't store the error in DocumentState to keep it simple)
-            match parse_to_graph(&state.text) {
-                Ok(_) => vec![], // Shouldn't happen, but handle gracefully
-                Err(parse_error) => {
-                    log::debug!("Parse error in {}: {:?}", uri, parse_error);
-                    vec![parse_error_to_diagnostic(&parse_error)]
-                }
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            return;
+        }
+        let config = self.config.read().await.clone();
+        publish_validation(&self.client, &self.publish_coalescer, &config, uri, state).await;
+    }
+
+    /// Validate the current text of `uri` once the debounce delay passes, unless a
+    /// newer change took over. Runs on a task of its own so the change notification
+    /// returns straight away.
+    fn validate_after_debounce(&self, uri: Url, ticket: u64) {
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            return;
+        }
+        let client = self.client.clone();
+        let documents = Arc::clone(&self.documents);
+        let config = Arc::clone(&self.config);
+        let debounce = self.validation_debounce.clone();
+        let coalescer = self.publish_coalescer.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(VALIDATION_DEBOUNCE).await;
+            if !debounce.is_latest(&uri, ticket).await {
+                return;
             }
-        };
 
-        self.client
-            .publish_diagnostics(uri, diagnostics, None)
-            .await;
+            let state = {
+                let documents = documents.read().await;
+                documents.get(&uri).cloned()
+            };
+            if let Some(state) = state {
+                let config = config.read().await.clone();
+                publish_validation(&client, &coalescer, &config, uri, &state).await;
+            }
+        });
     }
 
     /// Get the current formatting configuration.
@@ -197,6 +897,12 @@ impl Backend {
         LspFormatConfig::from(&config.formatting)
     }
 
+    /// Get the current hover byte budgets.
+    async fn get_hover_budget(&self) -> HoverBudget {
+        let config = self.config.read().await;
+        HoverBudget::from(&config.hover)
+    }
+
     async fn config_hash(&self) -> String {
         let config = self.config.read().await;
         let Ok(bytes) = serde_json::to_vec(&*config) else {
@@ -205,9 +911,27 @@ impl Backend {
         blake3::hash(&bytes).to_hex().to_string()
     }
 
+    /// Detail level for hovers that don't ask for one: the configured level, then
+    /// the initialization option, then Standard.
+    async fn default_detail_level(&self) -> DetailLevel {
+        let configured = self.config.read().await.hover.detail_level;
+        configured
+            .or(*self.init_detail_level.read().await)
+            .unwrap_or(DetailLevel::Standard)
+    }
+
     pub async fn hover_plus(&self, params: HoverPlusParams) -> Result<Option<HoverPlusResponse>> {
         let uri = params.text_document.uri;
-        let detail_level = DetailLevel::parse(params.max_detail_level.as_deref());
+        let detail_level = match params.max_detail_level.as_deref() {
+            Some(level) => DetailLevel::parse(Some(level)),
+            None => self.default_detail_level().await,
+        };
+        self.trace_request("textDocument/hoverPlus", || at(&uri, params.position))
+            .await;
+        if !self.config.read().await.features.hover {
+            return Ok(None);
+        }
+        let _permit = self.request_permit().await;
 
         let Some(state) = ({
             let documents = self.documents.read().await;
@@ -222,18 +946,20 @@ impl Backend {
 
         let config_hash = self.config_hash().await;
         let model_key = HoverCacheKey::model(&uri, state.version, params.position, detail_level);
+        let markdown_key = params
+            .include_markdown
+            .then(|| HoverCacheKey::markdown(&uri, state.version, params.position, detail_level));
 
+        let min_confidence = self.config.read().await.hover.min_confidence;
         if let Some(model) = self.hover_model_cache.lock().await.get(&model_key).cloned() {
-            let markdown = if params.include_markdown {
-                let markdown_key =
-                    HoverCacheKey::markdown(&uri, state.version, params.position, detail_level);
-                Some(self.hover_markdown_for(&markdown_key, &model).await)
-            } else {
-                None
-            };
-            return Ok(Some(HoverPlusResponse { model, markdown }));
+            if !min_confidence.allows(&model.symbol.resolution_confidence) {
+                return Ok(None);
+            }
+            return Ok(Some(self.hover_plus_response(markdown_key, model).await));
         }
 
+        let use_stale_graph = self.config.read().await.hover.use_stale_graph;
+        let (graph, stale) = state.hover_graph(use_stale_graph);
         let model = build_hover_model(HoverBuildInput {
             uri: &uri,
             document_version: state.version,
@@ -242,12 +968,16 @@ impl Backend {
             detail_level,
             line_index: &state.line_index,
             index,
-            graph: state.graph.as_ref(),
+            graph,
+            budget: self.get_hover_budget().await,
         });
 
         let Some(mut model) = model else {
             return Ok(None);
         };
+        if stale {
+            mark_stale(&mut model);
+        }
 
         enforce_json_limits(&mut model);
 
@@ -256,54 +986,672 @@ impl Backend {
             .await
             .put(model_key, model.clone());
 
-        let markdown = if params.include_markdown {
-            let markdown_key =
-                HoverCacheKey::markdown(&uri, state.version, params.position, detail_level);
-            Some(self.hover_markdown_for(&markdown_key, &model).await)
-        } else {
-            None
-        };
-
-        Ok(Some(HoverPlusResponse { model, markdown }))
+        if !min_confidence.allows(&model.symbol.resolution_confidence) {
+            return Ok(None);
+        }
+        Ok(Some(self.hover_plus_response(markdown_key, model).await))
     }
 
-    async fn hover_markdown_for(
+    /// Attach markdown when requested, recording any sections the renderer clipped in
+    /// the model's `limits.truncated_sections`.
+    async fn hover_plus_response(
         &self,
-        key: &HoverCacheKey,
-        model: &crate::hover::HoverModel,
-    ) -> String {
-        if let Some(markdown) = self.hover_markdown_cache.lock().await.get(key).cloned() {
-            return markdown;
-        }
+        markdown_key: Option<HoverCacheKey>,
+        mut model: crate::hover::HoverModel,
+    ) -> HoverPlusResponse {
+        let Some(markdown_key) = markdown_key else {
+            return HoverPlusResponse {
+                model,
+                markdown: None,
+            };
+        };
 
-        let rendered = markdown_renderer::render_markdown(model);
-        if !rendered.truncated_sections.is_empty() {
-            log::debug!(
-                "Hover markdown truncated sections: {:?}",
-                rendered.truncated_sections
-            );
+        let rendered = self.hover_markdown_for(&markdown_key, &model).await;
+        let truncated = &mut model.limits.truncated_sections;
+        truncated.extend(rendered.truncated_sections);
+        truncated.sort();
+        truncated.dedup();
+
+        HoverPlusResponse {
+            model,
+            markdown: Some(rendered.markdown),
         }
-        let markdown = rendered.markdown;
-        self.hover_markdown_cache
-            .lock()
-            .await
-            .put(key.clone(), markdown.clone());
-        markdown
     }
-}
 
-fn enforce_json_limits(model: &mut crate::hover::HoverModel) {
-    let max = model.limits.max_json_bytes;
-    let mut bytes = serde_json::to_vec(model).unwrap_or_default().len();
-    if bytes <= max {
-        return;
+    /// Custom request `sea/moniker`: stable identity of the symbol under the cursor.
+    pub async fn moniker(&self, params: MonikerParams) -> Result<Option<SymbolMoniker>> {
+        Ok(self
+            .symbol_moniker(&params.text_document.uri, params.position)
+            .await)
     }
 
-    model.limits.truncated_sections.push("json".to_string());
+    /// Moniker of the symbol at `position`, shared by `sea/moniker` and the standard
+    /// `textDocument/moniker`.
+    async fn symbol_moniker(&self, uri: &Url, position: Position) -> Option<SymbolMoniker> {
+        if !self.config.read().await.features.hover {
+            return None;
+        }
+        let documents = self.documents.read().await;
+        let state = documents.get(uri)?;
+        let index = state.semantic_index.as_ref()?;
 
-    // Deterministic, loss-first truncation to fit the payload limit.
-    model.related.clear();
-    bytes = serde_json::to_vec(model).unwrap_or_default().len();
+        build_moniker(&state.line_index, position, index, state.graph.as_ref())
+    }
+
+    /// Custom request `sea/resolve`: `resolve_id`, qualified name and confidence of
+    /// the symbol under the cursor, without building a hover model.
+    pub async fn resolve(&self, params: ResolveParams) -> Result<Option<SymbolResolution>> {
+        if !self.config.read().await.features.hover {
+            return Ok(None);
+        }
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(None);
+        };
+
+        Ok(build_resolution(
+            &state.line_index,
+            params.position,
+            index,
+            state.graph.as_ref(),
+        ))
+    }
+
+    /// Custom request `sea/quickInfo`: kind, qualified name and one-line signature of
+    /// the symbol under the cursor.
+    pub async fn quick_info(&self, params: QuickInfoParams) -> Result<Option<QuickInfo>> {
+        if !self.config.read().await.features.hover {
+            return Ok(None);
+        }
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(None);
+        };
+
+        Ok(build_quick_info(
+            &state.line_index,
+            params.position,
+            index,
+            state.graph.as_ref(),
+        ))
+    }
+
+    /// Index every SEA file (per `fileExtensions`) under the workspace roots.
+    ///
+    /// Progress is reported through `$/progress` only when the client advertised
+    /// `window.workDoneProgress` and accepted the progress token.
+    async fn index_workspace(&self) {
+        let extensions = self.config.read().await.file_extensions.clone();
+        let roots = self.workspace_roots.read().await.clone();
+        // The walk and the parses are blocking fs and CPU work; keep them off the executor.
+        let files: Vec<PathBuf> = tokio::task::spawn_blocking(move || {
+            roots
+                .iter()
+                .flat_map(|root| workspace::discover_sea_files(root, &extensions))
+                .collect()
+        })
+        .await
+        .unwrap_or_default();
+        if files.is_empty() {
+            return;
+        }
+
+        let scan = WorkspaceScan::new(files);
+        let total = scan.total();
+        let token = NumberOrString::String("domainforge/indexWorkspace".to_string());
+        let report = self.work_done_progress.load(Ordering::Relaxed)
+            && self
+                .client
+                .send_request::<request::WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                    token: token.clone(),
+                })
+                .await
+                .is_ok();
+
+        if report {
+            self.send_progress(
+                &token,
+                WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Indexing…".to_string(),
+                    cancellable: Some(false),
+                    message: Some(format!("{total} files")),
+                    percentage: Some(0),
+                }),
+            )
+            .await;
+        }
+
+        let (steps_tx, mut steps) = tokio::sync::mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || {
+            for step in scan {
+                if steps_tx.send(step).is_err() {
+                    break;
+                }
+            }
+        });
+        while let Some(step) = steps.recv().await {
+            if let Some(file) = step.file {
+                self.workspace_files
+                    .write()
+                    .await
+                    .insert(step.uri.clone(), file);
+            }
+            if report {
+                self.send_progress(
+                    &token,
+                    WorkDoneProgress::Report(WorkDoneProgressReport {
+                        cancellable: Some(false),
+                        message: Some(step.uri.path().to_string()),
+                        percentage: Some(step.percentage),
+                    }),
+                )
+                .await;
+            }
+        }
+
+        log::info!("Indexed {} workspace files", total);
+        if report {
+            self.send_progress(
+                &token,
+                WorkDoneProgress::End(WorkDoneProgressEnd {
+                    message: Some(format!("Indexed {total} files")),
+                }),
+            )
+            .await;
+        }
+    }
+
+    async fn send_progress(&self, token: &NumberOrString, progress: WorkDoneProgress) {
+        self.client
+            .send_notification::<notification::Progress>(ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(progress),
+            })
+            .await;
+    }
+
+    /// Custom request `sea/breadcrumb`: enclosing declarations at a position, outermost first.
+    pub async fn breadcrumb(&self, params: BreadcrumbParams) -> Result<Vec<BreadcrumbItem>> {
+        if !self.config.read().await.features.navigation {
+            return Ok(Vec::new());
+        }
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(Vec::new());
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(navigation::breadcrumb(
+            &state.line_index,
+            params.position,
+            index,
+            state.graph.as_ref(),
+        ))
+    }
+
+    /// Custom request `sea/references`: references to the entity or resource at a
+    /// position, grouped into flow source, flow target, flow resource, instance type
+    /// and other usages.
+    pub async fn graph_references(
+        &self,
+        params: GraphReferencesParams,
+    ) -> Result<Option<GraphReferences>> {
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(None);
+        };
+
+        Ok(navigation::graph_references(
+            &params.text_document.uri,
+            &state.line_index,
+            params.position,
+            index,
+            state.graph.as_ref(),
+        ))
+    }
+
+    /// Custom request `sea/rename/validate`: whether renaming the symbol at a position
+    /// would collide with an existing declaration of the same kind and namespace.
+    pub async fn validate_rename(
+        &self,
+        params: RenameValidateParams,
+    ) -> Result<Option<RenameValidation>> {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(navigation::validate_rename(
+            &params.text_document.uri,
+            &state.line_index,
+            params.position,
+            &params.new_name,
+            index,
+            graph,
+        ))
+    }
+
+    /// Workspace edit renaming namespace `old` to `new` in open and indexed files;
+    /// `None` when `new` is not a valid namespace identifier.
+    async fn namespace_rename_edit(&self, old: &str, new: &str) -> Option<WorkspaceEdit> {
+        let documents = self.documents.read().await;
+        let workspace_files = self.workspace_files.read().await;
+
+        // Open documents take precedence over their on-disk index.
+        let open = documents.iter().filter_map(|(uri, state)| {
+            Some((uri, &state.line_index, state.semantic_index.as_ref()?))
+        });
+        let indexed = workspace_files
+            .iter()
+            .filter(|(uri, _)| !documents.contains_key(uri))
+            .map(|(uri, file)| (uri, &file.line_index, &file.index));
+
+        Some(WorkspaceEdit {
+            changes: Some(workspace::namespace_rename_edits(
+                open.chain(indexed),
+                old,
+                new,
+            )?),
+            ..Default::default()
+        })
+    }
+
+    /// Notification `$/setTrace`: change how much request handling is traced to the client.
+    pub async fn set_trace(&self, params: SetTraceParams) {
+        *self.trace.write().await = params.value;
+    }
+
+    /// Send a `$/logTrace` for a request being handled, honoring the client's trace level.
+    ///
+    /// `verbose` is only evaluated at the `verbose` level.
+    async fn trace_request(&self, method: &str, verbose: impl FnOnce() -> String) {
+        let verbose = match *self.trace.read().await {
+            TraceValue::Off => return,
+            TraceValue::Messages => None,
+            TraceValue::Verbose => Some(verbose()),
+        };
+        self.client
+            .send_notification::<notification::LogTrace>(LogTraceParams {
+                message: format!("Handling {}", method),
+                verbose,
+            })
+            .await;
+    }
+
+    /// Custom request `sea/outline`: document symbols grouped by namespace.
+    pub async fn outline(&self, params: OutlineParams) -> Result<Option<Outline>> {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(None);
+        };
+
+        Ok(Some(outline::outline(
+            &state.line_index,
+            index,
+            state.graph.as_ref(),
+        )))
+    }
+
+    /// Custom request `sea/dot`: the entity/flow graph as GraphViz DOT.
+    ///
+    /// Fails when the document is not open or does not parse.
+    pub async fn dot(&self, params: DotParams) -> Result<String> {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Err(Error::invalid_params("Document is not open"));
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Err(Error::invalid_params(
+                "Document does not parse; fix its errors to export the graph",
+            ));
+        };
+
+        Ok(flow_graph::to_dot(&state.line_index, index, graph))
+    }
+
+    /// Custom request `sea/diagnosticsAll`: current diagnostics of every open
+    /// document, keyed by URI, computed the same way as pushed diagnostics.
+    pub async fn diagnostics_all(&self) -> Result<BTreeMap<Url, Vec<Diagnostic>>> {
+        let config = self.config.read().await.clone();
+        if !config.features.diagnostics {
+            return Ok(BTreeMap::new());
+        }
+        let documents = self.documents.read().await;
+
+        Ok(documents
+            .iter()
+            .map(|(uri, state)| (uri.clone(), state.diagnostics(uri, &config)))
+            .collect())
+    }
+
+    /// Custom request `sea/graphStats`: declaration counts for the document's graph.
+    ///
+    /// All counts are zero, with `success: false`, when the document is not open or
+    /// does not parse.
+    pub async fn graph_stats(&self, params: GraphStatsParams) -> Result<GraphStats> {
+        let documents = self.documents.read().await;
+        let graph = documents
+            .get(&params.text_document.uri)
+            .and_then(|state| state.graph.as_ref());
+
+        Ok(graph_stats::graph_stats(graph))
+    }
+
+    /// Custom request `sea/workspaceReferences`: references to the symbol at the
+    /// position across open and indexed workspace files.
+    ///
+    /// The origin document may be open or only indexed from disk, so clients that
+    /// never open files (such as the MCP bridge) can still ask.
+    pub async fn workspace_references(&self, params: ReferenceParams) -> Result<Vec<Location>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        self.trace_request("sea/workspaceReferences", || at(uri, position))
+            .await;
+        if !self.config.read().await.features.navigation {
+            return Ok(Vec::new());
+        }
+
+        let documents = self.documents.read().await;
+        let workspace_files = self.workspace_files.read().await;
+
+        let origin = match documents.get(uri) {
+            Some(state) => state
+                .semantic_index
+                .as_ref()
+                .map(|index| (&state.line_index, index)),
+            None => workspace_files
+                .get(uri)
+                .map(|file| (&file.line_index, &file.index)),
+        };
+        let Some(occ) = origin.and_then(|(line_index, index)| {
+            index.symbol_at_offset(line_index.offset_of(position)?)
+        }) else {
+            return Ok(Vec::new());
+        };
+
+        // Open documents take precedence over their on-disk index.
+        let open = documents.iter().filter_map(|(uri, state)| {
+            Some((uri, &state.line_index, state.semantic_index.as_ref()?))
+        });
+        let indexed = workspace_files
+            .iter()
+            .filter(|(uri, _)| !documents.contains_key(uri))
+            .map(|(uri, file)| (uri, &file.line_index, &file.index));
+
+        Ok(workspace::workspace_references(
+            open.chain(indexed),
+            occ.kind,
+            &occ.name,
+            params.context.include_declaration,
+        ))
+    }
+
+    /// Custom request `sea/formatCheck`: whether a document is formatted, with a
+    /// unified diff when it isn't. Checks `text` when given, otherwise the open
+    /// document at `uri`, otherwise the file on disk.
+    pub async fn format_check(
+        &self,
+        params: FormatCheckParams,
+    ) -> Result<Option<FormatCheckResult>> {
+        if !self.config.read().await.features.formatting {
+            return Ok(None);
+        }
+        let text = match (params.text, params.uri) {
+            (Some(text), _) => text,
+            (None, Some(uri)) => self.document_text(&uri).await?,
+            (None, None) => return Err(Error::invalid_params("Expected uri or text")),
+        };
+
+        let format_config = self.get_format_config().await;
+        Ok(Some(format_check(&text, Some(format_config))))
+    }
+
+    /// Custom request `sea/graphDiff`: entities, resources and flows that `proposed`
+    /// adds, removes or changes relative to `base`, or to the document at `uri`.
+    pub async fn graph_diff(&self, params: GraphDiffParams) -> Result<GraphDiff> {
+        let base = match (params.base, params.uri) {
+            (Some(base), _) => base,
+            (None, Some(uri)) => self.document_text(&uri).await?,
+            (None, None) => return Err(Error::invalid_params("Expected uri or base")),
+        };
+
+        let max_bytes = self.config.read().await.max_document_bytes;
+
+        let proposed = params.proposed;
+        tokio::task::spawn_blocking(move || {
+            let (base, base_index) = parse_version("Base", &base, max_bytes)?;
+            let (proposed, proposed_index) = parse_version("Proposed", &proposed, max_bytes)?;
+            Ok::<_, String>(graph_diff::graph_diff(
+                &base,
+                &base_index,
+                &proposed,
+                &proposed_index,
+            ))
+        })
+        .await
+        .map_err(|e| {
+            log::error!("Failed to diff graphs: {}", e);
+            Error::internal_error()
+        })?
+        .map_err(Error::invalid_params)
+    }
+
+    /// Replace the configuration, resetting whatever was sized or cached under the
+    /// old one. Returns whether the file extensions changed, which needs a rescan.
+    async fn apply_config(&self, mut new_config: DomainForgeConfig) -> bool {
+        // Capabilities were advertised for the features set at initialize
+        new_config.features = self.config.read().await.features.clone();
+        log::debug!("Updated configuration: {:?}", new_config);
+        let model_cache_size = new_config.hover.model_cache_size;
+        let markdown_cache_size = new_config.hover.markdown_cache_size;
+        let (extensions_changed, permits_changed) = {
+            let mut config = self.config.write().await;
+            let changed = (
+                config.file_extensions != new_config.file_extensions,
+                config.max_concurrent_requests != new_config.max_concurrent_requests,
+            );
+            *config = new_config;
+            changed
+        };
+        if permits_changed {
+            // In-flight requests keep their permits from the old semaphore
+            let permits = self.config.read().await.max_concurrent_requests;
+            *self.request_permits.write().await = Arc::new(Semaphore::new(permits.get()));
+        }
+        // Cached hovers were built under the previous budgets.
+        self.hover_model_cache.lock().await.clear();
+        self.hover_markdown_cache.lock().await.clear();
+        self.hover_model_cache.lock().await.resize(model_cache_size);
+        self.hover_markdown_cache
+            .lock()
+            .await
+            .resize(markdown_cache_size);
+        extensions_changed
+    }
+
+    /// Ask the client for the `domainforge` settings section and merge it over the
+    /// current configuration, so settings apply even if the client never pushes them.
+    async fn pull_configuration(&self) {
+        let item = ConfigurationItem {
+            scope_uri: None,
+            section: Some("domainforge".to_string()),
+        };
+        let settings = match self.client.configuration(vec![item]).await {
+            Ok(mut values) if !values.is_empty() => values.swap_remove(0),
+            Ok(_) => return,
+            Err(e) => {
+                log::warn!("Failed to pull configuration: {}", e);
+                return;
+            }
+        };
+        // Clients answer null for a section they have no settings for.
+        if !settings.is_object() {
+            return;
+        }
+
+        let mut merged = match serde_json::to_value(&*self.config.read().await) {
+            Ok(current) => current,
+            Err(e) => {
+                log::warn!("Failed to serialize configuration: {}", e);
+                return;
+            }
+        };
+        merge_settings(&mut merged, settings);
+        match serde_json::from_value::<DomainForgeConfig>(merged) {
+            Ok(config) => {
+                self.apply_config(config).await;
+            }
+            Err(e) => log::warn!("Failed to parse pulled configuration: {}", e),
+        }
+    }
+
+    /// Text of the open document at `uri`, otherwise the file on disk.
+    async fn document_text(&self, uri: &Url) -> Result<String> {
+        let open = {
+            let documents = self.documents.read().await;
+            documents.get(uri).map(|s| s.text.clone())
+        };
+        match open {
+            Some(text) => Ok(text),
+            None => uri
+                .to_file_path()
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .ok_or_else(|| Error::invalid_params(format!("Cannot read document {}", uri))),
+        }
+    }
+
+    /// Custom request `sea/flows`: every flow in the document as an adjacency list.
+    pub async fn flows(&self, params: FlowsParams) -> Result<Vec<FlowEdge>> {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(Vec::new());
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(flow_graph::flow_edges(
+            &state.line_index,
+            index,
+            state.graph.as_ref(),
+        ))
+    }
+
+    /// Custom request `sea/neighborhood`: entities within a few flow hops of the one
+    /// under the cursor, with the flows connecting them.
+    pub async fn neighborhood(&self, params: NeighborhoodParams) -> Result<Option<Neighborhood>> {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(flow_graph::neighborhood(
+            &state.line_index,
+            params.position,
+            params.depth.unwrap_or(1),
+            index,
+            graph,
+        ))
+    }
+
+    /// Custom request `sea/whatProvides`: entities that produce and consume the
+    /// resource under the cursor, split by which end of its flows they sit on.
+    pub async fn what_provides(&self, params: WhatProvidesParams) -> Result<Option<WhatProvides>> {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(flow_graph::what_provides(
+            &state.line_index,
+            params.position,
+            index,
+            graph,
+        ))
+    }
+
+    /// `textDocument/hover` contents for `model`, in the first format the client's
+    /// `hover.contentFormat` lists that the server renders. Markdown goes through the
+    /// markdown cache under `markdown_key`.
+    async fn hover_for(&self, markdown_key: &HoverCacheKey, model: &HoverModel) -> Hover {
+        let kind = self.hover_format.read().await.clone();
+        let value = match kind {
+            MarkupKind::PlainText => plaintext_renderer::render_plaintext(model),
+            MarkupKind::Markdown => self.hover_markdown_for(markdown_key, model).await.markdown,
+        };
+        Hover {
+            contents: HoverContents::Markup(MarkupContent { kind, value }),
+            range: None,
+        }
+    }
+
+    async fn hover_markdown_for(
+        &self,
+        key: &HoverCacheKey,
+        model: &crate::hover::HoverModel,
+    ) -> MarkdownRenderResult {
+        if let Some(rendered) = self.hover_markdown_cache.lock().await.get(key).cloned() {
+            return rendered;
+        }
+
+        let rendered = markdown_renderer::render_markdown(model);
+        if !rendered.truncated_sections.is_empty() {
+            log::debug!(
+                "Hover markdown truncated sections: {:?}",
+                rendered.truncated_sections
+            );
+        }
+        self.hover_markdown_cache
+            .lock()
+            .await
+            .put(key.clone(), rendered.clone());
+        rendered
+    }
+}
+
+fn enforce_json_limits(model: &mut crate::hover::HoverModel) {
+    let max = model.limits.max_json_bytes;
+    let mut bytes = serde_json::to_vec(model).unwrap_or_default().len();
+    if bytes <= max {
+        return;
+    }
+
+    model.limits.truncated_sections.push("json".to_string());
+
+    // Deterministic, loss-first truncation to fit the payload limit.
+    model.related.clear();
+    bytes = serde_json::to_vec(model).unwrap_or_default().len();
     if bytes <= max {
         return;
     }
@@ -361,21 +1709,172 @@ impl HoverCacheKey {
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
-        Ok(InitializeResult {
-            server_info: Some(ServerInfo {
-                name: "domainforge-lsp".to_string(),
-                version: Some(env!("CARGO_PKG_VERSION").to_string()),
-            }),
-            capabilities: crate::capabilities::server_capabilities(),
-        })
-    }
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let work_done_progress = params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|w| w.work_done_progress)
+            .unwrap_or(false);
+        self.work_done_progress
+            .store(work_done_progress, Ordering::Relaxed);
+        let register_type_hierarchy = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|t| t.type_hierarchy.as_ref())
+            .and_then(|h| h.dynamic_registration)
+            .unwrap_or(false);
+        self.register_type_hierarchy
+            .store(register_type_hierarchy, Ordering::Relaxed);
+        let pull_configuration = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.configuration)
+            .unwrap_or(false);
+        self.pull_configuration
+            .store(pull_configuration, Ordering::Relaxed);
+        let pull_diagnostics = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|t| t.diagnostic.is_some());
+        self.pull_diagnostics
+            .store(pull_diagnostics, Ordering::Relaxed);
 
-    async fn initialized(&self, _: InitializedParams) {
-        log::info!("DomainForge LSP initialized");
-    }
+        #[allow(deprecated)]
+        let roots: Vec<PathBuf> = match (params.workspace_folders, params.root_uri) {
+            (Some(folders), _) if !folders.is_empty() => folders
+                .iter()
+                .filter_map(|f| f.uri.to_file_path().ok())
+                .collect(),
+            (_, Some(root)) => root.to_file_path().ok().into_iter().collect(),
+            _ => Vec::new(),
+        };
+        *self.workspace_roots.write().await = roots;
+        if let Some(trace) = params.trace {
+            *self.trace.write().await = trace;
+        }
+        // Formats are listed in the client's order of preference; both are supported
+        let hover_format = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|t| t.hover.as_ref())
+            .and_then(|h| h.content_format.as_ref())
+            .and_then(|formats| formats.first().cloned())
+            .unwrap_or(MarkupKind::Markdown);
+        *self.hover_format.write().await = hover_format;
+        // Capabilities are fixed at initialize, so feature switches must arrive here
+        if let Some(features) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("features"))
+        {
+            match serde_json::from_value::<FeaturesConfig>(features.clone()) {
+                Ok(features) => self.config.write().await.features = features,
+                Err(e) => log::warn!("Failed to parse features: {}", e),
+            }
+        }
+        // File operation filters are advertised once too, so `fileExtensions` given
+        // here decides which renames the client reports
+        if let Some(extensions) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("fileExtensions"))
+        {
+            match serde_json::from_value::<Vec<String>>(extensions.clone()) {
+                Ok(extensions) => self.config.write().await.file_extensions = extensions,
+                Err(e) => log::warn!("Failed to parse fileExtensions: {}", e),
+            }
+        }
+        // Trigger characters are advertised once, so they aren't part of the
+        // reloadable `completion` settings; an empty list turns auto-popup off
+        let trigger_characters = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.pointer("/completion/triggerCharacters"))
+            .and_then(|triggers| {
+                serde_json::from_value::<Vec<String>>(triggers.clone())
+                    .map_err(|e| log::warn!("Failed to parse completion.triggerCharacters: {}", e))
+                    .ok()
+            });
+        let (features, file_extensions) = {
+            let config = self.config.read().await;
+            (config.features.clone(), config.file_extensions.clone())
+        };
+        if let Some(level) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.pointer("/hover/detailLevel"))
+        {
+            match serde_json::from_value::<DetailLevel>(level.clone()) {
+                Ok(level) => *self.init_detail_level.write().await = Some(level),
+                Err(e) => log::warn!("Failed to parse hover.detailLevel: {}", e),
+            }
+        }
+        // `logLevel` takes one of the `log` filter names and replaces RUST_LOG's global
+        // level; without it RUST_LOG stands
+        if let Some(level) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("logLevel"))
+            .and_then(Value::as_str)
+            .and_then(|level| level.parse::<log::LevelFilter>().ok())
+        {
+            crate::logging::set_level(level);
+        }
+
+        Ok(InitializeResult {
+            server_info: Some(ServerInfo {
+                name: "domainforge-lsp".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+            capabilities: crate::capabilities::server_capabilities(
+                &features,
+                trigger_characters,
+                pull_diagnostics,
+                &file_extensions,
+            ),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        log::info!("DomainForge LSP initialized");
+        if self.pull_configuration.load(Ordering::Relaxed) {
+            self.pull_configuration().await;
+        }
+        let (navigation, file_extensions) = {
+            let config = self.config.read().await;
+            (config.features.navigation, config.file_extensions.clone())
+        };
+        if self.register_type_hierarchy.load(Ordering::Relaxed) && navigation {
+            let registration = crate::capabilities::type_hierarchy_registration(&file_extensions);
+            if let Err(e) = self.client.register_capability(vec![registration]).await {
+                log::warn!("Failed to register the type hierarchy: {}", e);
+            }
+        }
+        self.index_workspace().await;
+    }
 
     async fn shutdown(&self) -> Result<()> {
+        // Pending debounced validations wake up to find their ticket gone
+        self.validation_debounce.forget_all().await;
+        self.publish_coalescer.cancel_all().await;
+        let documents = std::mem::take(&mut *self.documents.write().await);
+        let indexed = std::mem::take(&mut *self.workspace_files.write().await);
+        let mut models = self.hover_model_cache.lock().await;
+        let mut markdown = self.hover_markdown_cache.lock().await;
+        log::info!(
+            "Shutting down: {} open documents, {} indexed files, {} cached hover models, {} cached hover renderings",
+            documents.len(),
+            indexed.len(),
+            models.len(),
+            markdown.len()
+        );
+        models.clear();
+        markdown.clear();
         Ok(())
     }
 
@@ -387,15 +1886,24 @@ impl LanguageServer for Backend {
         log::info!("Document opened: {}", uri);
 
         // Create document state with parsed graph
-        let state = DocumentState::new(text, version);
+        let (max_bytes, trigger) = {
+            let config = self.config.read().await;
+            (config.max_document_bytes, config.validation.trigger)
+        };
+        let Some(state) = DocumentState::parse(text, version, max_bytes).await else {
+            return;
+        };
 
-        // Validate and publish diagnostics
-        self.validate_document(uri.clone(), &state).await;
+        // Store the document state before publishing, so changes the client sends
+        // once it sees the diagnostics find the document open
+        self.documents
+            .write()
+            .await
+            .insert(uri.clone(), state.clone());
 
-        // Store the document state
-        {
-            let mut documents = self.documents.write().await;
-            documents.insert(uri, state);
+        // Validate and publish diagnostics
+        if trigger.on_change() {
+            self.validate_document(uri, &state).await;
         }
     }
 
@@ -409,22 +1917,35 @@ impl LanguageServer for Backend {
 
             log::debug!("Document changed: {}", uri);
 
-            // Update the document state
-            let state = {
+            // Some clients resend identical text (e.g. format-on-save round-trips);
+            // keep the cached parse and diagnostics and only record the new version
+            {
                 let mut documents = self.documents.write().await;
                 if let Some(doc_state) = documents.get_mut(&uri) {
-                    doc_state.update(text, version);
-                    doc_state.clone()
-                } else {
-                    // Document not found, create new state
-                    let new_state = DocumentState::new(text, version);
-                    documents.insert(uri.clone(), new_state.clone());
-                    new_state
+                    if doc_state.text == text {
+                        doc_state.version = version;
+                        return;
+                    }
                 }
+            }
+
+            // Store the new state as soon as it is parsed so other requests see the
+            // new text
+            let max_bytes = self.config.read().await.max_document_bytes;
+            let Some(state) = DocumentState::parse(text, version, max_bytes).await else {
+                return;
             };
+            if !store_changed_document(&mut *self.documents.write().await, &uri, state) {
+                return;
+            }
 
-            // Re-validate and publish diagnostics
-            self.validate_document(uri, &state).await;
+            if !self.config.read().await.validation.trigger.on_change() {
+                return;
+            }
+
+            // Re-validate once typing pauses; a newer change supersedes this one
+            let ticket = self.validation_debounce.schedule(&uri).await;
+            self.validate_after_debounce(uri, ticket);
         }
     }
 
@@ -438,9 +1959,14 @@ impl LanguageServer for Backend {
             let mut documents = self.documents.write().await;
             documents.remove(&uri);
         }
+        // Drop any pending debounced validation or publish so it doesn't republish
+        self.validation_debounce.forget(&uri).await;
+        self.publish_coalescer.cancel(&uri).await;
 
-        // Clear diagnostics for the closed document
-        self.client.publish_diagnostics(uri, vec![], None).await;
+        // Clear pushed diagnostics for the closed document
+        if !self.pull_diagnostics.load(Ordering::Relaxed) {
+            self.client.publish_diagnostics(uri, vec![], None).await;
+        }
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
@@ -448,6 +1974,10 @@ impl LanguageServer for Backend {
 
         log::info!("Document saved: {}", uri);
 
+        if !self.config.read().await.validation.trigger.on_save() {
+            return;
+        }
+
         // Get the document state from storage
         let state = {
             let documents = self.documents.read().await;
@@ -470,9 +2000,10 @@ impl LanguageServer for Backend {
             if let Some(domainforge) = settings.get("domainforge") {
                 match serde_json::from_value::<DomainForgeConfig>(domainforge.clone()) {
                     Ok(new_config) => {
-                        log::debug!("Updated configuration: {:?}", new_config);
-                        let mut config = self.config.write().await;
-                        *config = new_config;
+                        if self.apply_config(new_config).await {
+                            self.workspace_files.write().await.clear();
+                            self.index_workspace().await;
+                        }
                     }
                     Err(e) => {
                         log::warn!("Failed to parse configuration: {}", e);
@@ -482,8 +2013,291 @@ impl LanguageServer for Backend {
         }
     }
 
+    async fn did_rename_files(&self, params: RenameFilesParams) {
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        let extensions = self.config.read().await.file_extensions.clone();
+
+        for rename in params.files {
+            let (Ok(old_uri), Ok(new_uri)) =
+                (Url::parse(&rename.old_uri), Url::parse(&rename.new_uri))
+            else {
+                continue;
+            };
+            let (Ok(old_path), Ok(new_path)) = (old_uri.to_file_path(), new_uri.to_file_path())
+            else {
+                continue;
+            };
+            log::info!("File renamed: {} -> {}", old_uri, new_uri);
+
+            let documents = self.documents.read().await;
+            let mut workspace_files = self.workspace_files.write().await;
+            if let Some(file) = workspace_files.remove(&old_uri) {
+                workspace_files.insert(new_uri, file);
+            }
+
+            // Open documents take precedence over their on-disk index.
+            let open = documents.iter().filter_map(|(uri, state)| {
+                Some((uri, &state.line_index, state.semantic_index.as_ref()?))
+            });
+            let indexed = workspace_files
+                .iter()
+                .filter(|(uri, _)| !documents.contains_key(uri))
+                .map(|(uri, file)| (uri, &file.line_index, &file.index));
+
+            for (uri, edits) in workspace::import_rename_edits(
+                open.chain(indexed),
+                &old_path,
+                &new_path,
+                &extensions,
+            ) {
+                changes.entry(uri).or_default().extend(edits);
+            }
+        }
+
+        if changes.is_empty() {
+            return;
+        }
+        let edit = WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        };
+        if let Err(e) = self.client.apply_edit(edit).await {
+            log::warn!("Failed to apply import path edits: {}", e);
+        }
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
+        let documents = self.documents.read().await;
+        let workspace_files = self.workspace_files.read().await;
+
+        // Open documents take precedence over their on-disk index.
+        let open = documents.iter().filter_map(|(uri, state)| {
+            Some((uri, &state.line_index, state.semantic_index.as_ref()?))
+        });
+        let indexed = workspace_files
+            .iter()
+            .filter(|(uri, _)| !documents.contains_key(uri))
+            .map(|(uri, file)| (uri, &file.line_index, &file.index));
+
+        Ok(Some(workspace::workspace_symbols(
+            open.chain(indexed),
+            &params.query,
+        )))
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let uri = params.text_document.uri;
+        let state = if self.config.read().await.features.diagnostics {
+            let documents = self.documents.read().await;
+            documents.get(&uri).cloned()
+        } else {
+            None
+        };
+        let Some(state) = state else {
+            return Ok(DocumentDiagnosticReportResult::Report(
+                DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport::default()),
+            ));
+        };
+
+        let config = self.config.read().await.clone();
+        let report =
+            match state.workspace_report(uri, None, params.previous_result_id.as_ref(), &config) {
+                WorkspaceDocumentDiagnosticReport::Full(report) => {
+                    DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: report.full_document_diagnostic_report,
+                    })
+                }
+                WorkspaceDocumentDiagnosticReport::Unchanged(report) => {
+                    DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                        related_documents: None,
+                        unchanged_document_diagnostic_report: report
+                            .unchanged_document_diagnostic_report,
+                    })
+                }
+            };
+        Ok(DocumentDiagnosticReportResult::Report(report))
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        let previous: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|id| (id.uri, id.value))
+            .collect();
+
+        let config = self.config.read().await.clone();
+        if !config.features.diagnostics {
+            return Ok(WorkspaceDiagnosticReportResult::Report(
+                WorkspaceDiagnosticReport { items: Vec::new() },
+            ));
+        }
+
+        // Open documents take precedence over their on-disk index.
+        let mut items: Vec<WorkspaceDocumentDiagnosticReport> = Vec::new();
+        let on_disk: Vec<(Url, IndexedFile)> = {
+            let documents = self.documents.read().await;
+            for (uri, state) in documents.iter() {
+                items.push(state.workspace_report(
+                    uri.clone(),
+                    Some(i64::from(state.version)),
+                    previous.get(uri),
+                    &config,
+                ));
+            }
+            let workspace_files = self.workspace_files.read().await;
+            workspace_files
+                .iter()
+                .filter(|(uri, _)| !documents.contains_key(uri))
+                .map(|(uri, file)| (uri.clone(), file.clone()))
+                .collect()
+        };
+
+        // Indexed files keep only their semantic index, so the text is re-read
+        // off the async executor
+        let indexed = tokio::task::spawn_blocking(move || {
+            on_disk
+                .into_iter()
+                .filter_map(|(uri, file)| {
+                    let previous_result_id = previous.get(&uri);
+                    indexed_file_report(uri, &file, previous_result_id, &config)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+        match indexed {
+            Ok(reports) => items.extend(reports),
+            Err(e) => log::warn!("Failed to report diagnostics for indexed files: {}", e),
+        }
+
+        items.sort_by(|a, b| report_uri(a).cmp(report_uri(b)));
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
+        ))
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        if params.command == NEW_FILE_COMMAND {
+            let [Value::String(namespace)] = params.arguments.as_slice() else {
+                return Err(Error::invalid_params("Expected the namespace name"));
+            };
+            let Some(template) = workspace::new_file_template(namespace) else {
+                return Err(Error::invalid_params(format!(
+                    "Invalid namespace name: {:?}",
+                    namespace
+                )));
+            };
+            return Ok(Some(Value::String(template)));
+        }
+        if params.command == EXTRACT_TO_FILE_COMMAND {
+            let [Value::String(uri), range, Value::String(namespace)] = params.arguments.as_slice()
+            else {
+                return Err(Error::invalid_params(
+                    "Expected the document URI, the selected range and the namespace",
+                ));
+            };
+            let (Ok(uri), Ok(range)) = (
+                Url::parse(uri),
+                serde_json::from_value::<Range>(range.clone()),
+            ) else {
+                return Err(Error::invalid_params("Invalid document URI or range"));
+            };
+            let edit = {
+                let documents = self.documents.read().await;
+                documents.get(&uri).and_then(|state| {
+                    workspace::extract_to_file_edit(
+                        &uri,
+                        &state.text,
+                        &state.line_index,
+                        state.semantic_index.as_ref()?,
+                        range,
+                        namespace,
+                    )
+                })
+            };
+            let Some(edit) = edit else {
+                return Err(Error::invalid_params(
+                    "Nothing to extract: select declarations with identifier names and give a valid namespace",
+                ));
+            };
+            if let Err(e) = self.client.apply_edit(edit).await {
+                log::warn!("Failed to apply extract-to-file edits: {}", e);
+            }
+            return Ok(None);
+        }
+        if params.command == APPLY_NAMESPACE_COMMAND {
+            let [Value::String(uri), range, Value::String(namespace)] = params.arguments.as_slice()
+            else {
+                return Err(Error::invalid_params(
+                    "Expected the document URI, the selected range and the namespace",
+                ));
+            };
+            let (Ok(uri), Ok(range)) = (
+                Url::parse(uri),
+                serde_json::from_value::<Range>(range.clone()),
+            ) else {
+                return Err(Error::invalid_params("Invalid document URI or range"));
+            };
+            let edit = {
+                let documents = self.documents.read().await;
+                documents.get(&uri).and_then(|state| {
+                    code_actions::apply_namespace_edit(&uri, range, &state.text, namespace)
+                })
+            };
+            let Some(edit) = edit else {
+                return Err(Error::invalid_params(
+                    "Nothing to apply: select two or more declarations outside the namespace and give a valid namespace",
+                ));
+            };
+            if let Err(e) = self.client.apply_edit(edit).await {
+                log::warn!("Failed to apply namespace edits: {}", e);
+            }
+            return Ok(None);
+        }
+        if params.command != RENAME_NAMESPACE_COMMAND {
+            return Err(Error::invalid_params(format!(
+                "Unknown command: {}",
+                params.command
+            )));
+        }
+        let [Value::String(old), Value::String(new)] = params.arguments.as_slice() else {
+            return Err(Error::invalid_params(
+                "Expected the old and new namespace names",
+            ));
+        };
+
+        let Some(edit) = self.namespace_rename_edit(old, new).await else {
+            return Err(Error::invalid_params(format!(
+                "'{}' is not a valid namespace name",
+                new
+            )));
+        };
+        if let Err(e) = self.client.apply_edit(edit).await {
+            log::warn!("Failed to apply namespace rename edits: {}", e);
+        }
+        Ok(None)
+    }
+
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         let uri = params.text_document.uri;
+        self.trace_request("textDocument/formatting", || uri.to_string())
+            .await;
+        if !self.config.read().await.features.formatting {
+            return Ok(None);
+        }
+        let _permit = self.request_permit().await;
 
         log::info!("Format document: {}", uri);
 
@@ -491,6 +2305,7 @@ impl LanguageServer for Backend {
         let text = {
             let documents = self.documents.read().await;
             match documents.get(&uri) {
+                Some(state) if state.oversized => return Ok(None),
                 Some(state) => state.text.clone(),
                 None => {
                     log::warn!("Document not found for formatting: {}", uri);
@@ -527,6 +2342,12 @@ impl LanguageServer for Backend {
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
+        self.trace_request("textDocument/completion", || at(&uri, position))
+            .await;
+        if !self.config.read().await.features.completion {
+            return Ok(None);
+        }
+        let _permit = self.request_permit().await;
 
         let Some(state) = ({
             let documents = self.documents.read().await;
@@ -535,12 +2356,35 @@ impl LanguageServer for Backend {
             return Ok(None);
         };
 
+        if state.oversized {
+            return Ok(None);
+        }
+
+        // A space only re-triggers completion after keywords that expect a name, so
+        // typing between words doesn't pop up every symbol.
+        if let Some(CompletionContext {
+            trigger_kind: CompletionTriggerKind::TRIGGER_CHARACTER,
+            trigger_character: Some(trigger),
+        }) = &params.context
+        {
+            if !completion::accepts_trigger(&state.text, &state.line_index, position, trigger) {
+                return Ok(None);
+            }
+        }
+
+        let (max_items, use_stale_graph) = {
+            let config = self.config.read().await;
+            (config.completion.max_items, config.hover.use_stale_graph)
+        };
+        // Names typed before the text broke stay completable mid-edit
+        let (graph, _) = state.hover_graph(use_stale_graph);
         let response = completion::completion(
             &state.text,
             &state.line_index,
             position,
-            state.graph.as_ref(),
+            graph,
             state.semantic_index.as_ref(),
+            max_items,
         );
         Ok(response)
     }
@@ -548,6 +2392,12 @@ impl LanguageServer for Backend {
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
+        self.trace_request("textDocument/hover", || at(&uri, position))
+            .await;
+        if !self.config.read().await.features.hover {
+            return Ok(None);
+        }
+        let _permit = self.request_permit().await;
 
         let Some(state) = ({
             let documents = self.documents.read().await;
@@ -561,21 +2411,20 @@ impl LanguageServer for Backend {
         };
 
         let config_hash = self.config_hash().await;
-        let detail_level = DetailLevel::Standard;
+        let detail_level = self.default_detail_level().await;
         let model_key = HoverCacheKey::model(&uri, state.version, position, detail_level);
 
+        let markdown_key = HoverCacheKey::markdown(&uri, state.version, position, detail_level);
+        let min_confidence = self.config.read().await.hover.min_confidence;
         if let Some(model) = self.hover_model_cache.lock().await.get(&model_key).cloned() {
-            let markdown_key = HoverCacheKey::markdown(&uri, state.version, position, detail_level);
-            let markdown = self.hover_markdown_for(&markdown_key, &model).await;
-            return Ok(Some(Hover {
-                contents: HoverContents::Markup(MarkupContent {
-                    kind: MarkupKind::Markdown,
-                    value: markdown,
-                }),
-                range: None,
-            }));
+            if !min_confidence.allows(&model.symbol.resolution_confidence) {
+                return Ok(None);
+            }
+            return Ok(Some(self.hover_for(&markdown_key, &model).await));
         }
 
+        let use_stale_graph = self.config.read().await.hover.use_stale_graph;
+        let (graph, stale) = state.hover_graph(use_stale_graph);
         let model = build_hover_model(HoverBuildInput {
             uri: &uri,
             document_version: state.version,
@@ -584,27 +2433,26 @@ impl LanguageServer for Backend {
             detail_level,
             line_index: &state.line_index,
             index,
-            graph: state.graph.as_ref(),
+            graph,
+            budget: self.get_hover_budget().await,
         });
 
-        let Some(model) = model else {
+        let Some(mut model) = model else {
             return Ok(None);
         };
+        if stale {
+            mark_stale(&mut model);
+        }
 
         self.hover_model_cache
             .lock()
             .await
             .put(model_key, model.clone());
 
-        let markdown_key = HoverCacheKey::markdown(&uri, state.version, position, detail_level);
-        let markdown = self.hover_markdown_for(&markdown_key, &model).await;
-        Ok(Some(Hover {
-            contents: HoverContents::Markup(MarkupContent {
-                kind: MarkupKind::Markdown,
-                value: markdown,
-            }),
-            range: None,
-        }))
+        if !min_confidence.allows(&model.symbol.resolution_confidence) {
+            return Ok(None);
+        }
+        Ok(Some(self.hover_for(&markdown_key, &model).await))
     }
 
     async fn goto_definition(
@@ -613,6 +2461,11 @@ impl LanguageServer for Backend {
     ) -> Result<Option<GotoDefinitionResponse>> {
         let uri = params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
+        self.trace_request("textDocument/definition", || at(&uri, position))
+            .await;
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
 
         let Some(state) = ({
             let documents = self.documents.read().await;
@@ -624,6 +2477,24 @@ impl LanguageServer for Backend {
             return Ok(None);
         };
 
+        // An import path jumps to the start of the imported file
+        let offset = state.line_index.offset_of(position);
+        if let Some(import) = index
+            .import_paths
+            .iter()
+            .find(|import| offset.is_some_and(|o| import.range.start <= o && o < import.range.end))
+        {
+            let extensions = self.config.read().await.file_extensions.clone();
+            return Ok(
+                workspace::resolve_import(&uri, &import.path, &extensions).map(|target| {
+                    GotoDefinitionResponse::Scalar(Location {
+                        uri: target,
+                        range: Range::default(),
+                    })
+                }),
+            );
+        }
+
         let location = navigation::goto_definition(&uri, &state.line_index, position, index);
         Ok(location.map(GotoDefinitionResponse::Scalar))
     }
@@ -631,6 +2502,11 @@ impl LanguageServer for Backend {
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
+        self.trace_request("textDocument/references", || at(&uri, position))
+            .await;
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
         let include_declaration = params.context.include_declaration;
 
         let Some(state) = ({
@@ -653,36 +2529,214 @@ impl LanguageServer for Backend {
         Ok(Some(locations))
     }
 
-    async fn code_action(
+    async fn moniker(
         &self,
-        params: CodeActionParams,
-    ) -> Result<Option<Vec<CodeActionOrCommand>>> {
+        params: tower_lsp::lsp_types::MonikerParams,
+    ) -> Result<Option<Vec<Moniker>>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        self.trace_request("textDocument/moniker", || at(&uri, position))
+            .await;
+
+        let Some(moniker) = self.symbol_moniker(&uri, position).await else {
+            return Ok(None);
+        };
+        // Declarations can be imported by other files, so every symbol is an export.
+        Ok(Some(vec![Moniker {
+            scheme: moniker.scheme,
+            identifier: moniker.identifier,
+            unique: UniquenessLevel::Scheme,
+            kind: Some(MonikerKind::Export),
+        }]))
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        self.trace_request("textDocument/prepareTypeHierarchy", || at(&uri, position))
+            .await;
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
+
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(
+            navigation::prepare_type_hierarchy(&uri, &state.line_index, position, index, graph)
+                .map(|item| vec![item]),
+        )
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
+        let item = params.item;
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&item.uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(navigation::type_hierarchy_supertypes(
+            &item.uri,
+            &state.line_index,
+            index,
+            graph,
+            &item.name,
+        )))
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
+        let item = params.item;
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&item.uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(navigation::type_hierarchy_subtypes(
+            &item.uri,
+            &state.line_index,
+            index,
+            graph,
+            &item.name,
+        )))
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
         let uri = params.text_document.uri;
-        let range = params.range;
-        let diagnostics = params.context.diagnostics;
 
-        let Some(text) = ({
-            let documents = self.documents.read().await;
-            documents.get(&uri).map(|s| s.text.clone())
-        }) else {
+        let documents = self.documents.read().await;
+        let Some(index) = documents
+            .get(&uri)
+            .and_then(|state| state.semantic_index.as_ref())
+        else {
             return Ok(None);
         };
 
-        let actions = crate::code_actions::provide_code_actions(&uri, range, &diagnostics, &text);
+        Ok(Some(folding::folding_ranges(index)))
+    }
 
-        Ok(Some(actions))
+    async fn inline_value(&self, params: InlineValueParams) -> Result<Option<Vec<InlineValue>>> {
+        if !self.config.read().await.features.hover {
+            return Ok(None);
+        }
+        let uri = params.text_document.uri;
+
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&uri) else {
+            return Ok(None);
+        };
+        let (Some(index), Some(graph)) = (state.semantic_index.as_ref(), state.graph.as_ref())
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(inline_values::inline_values(
+            &state.line_index,
+            index,
+            graph,
+            params.range,
+        )))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::hover::*;
-    use tower_lsp::LspService;
+    async fn linked_editing_range(
+        &self,
+        params: LinkedEditingRangeParams,
+    ) -> Result<Option<LinkedEditingRanges>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        if !self.config.read().await.features.navigation {
+            return Ok(None);
+        }
 
-    #[test]
-    fn hover_plus_json_is_capped_deterministically() {
-        let mut model = HoverModel {
+        let documents = self.documents.read().await;
+        let Some(state) = documents.get(&uri) else {
+            return Ok(None);
+        };
+        let Some(index) = state.semantic_index.as_ref() else {
+            return Ok(None);
+        };
+
+        Ok(navigation::linked_editing_ranges(
+            &state.text,
+            &state.line_index,
+            position,
+            index,
+        ))
+    }
+
+    async fn code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> Result<Option<Vec<CodeActionOrCommand>>> {
+        let uri = params.text_document.uri;
+        let range = params.range;
+        self.trace_request("textDocument/codeAction", || {
+            format!("{} lines {}-{}", uri, range.start.line, range.end.line)
+        })
+        .await;
+        if !self.config.read().await.features.code_actions {
+            return Ok(None);
+        }
+        let diagnostics = params.context.diagnostics;
+
+        let Some(text) = ({
+            let documents = self.documents.read().await;
+            documents
+                .get(&uri)
+                .filter(|s| !s.oversized)
+                .map(|s| s.text.clone())
+        }) else {
+            return Ok(None);
+        };
+
+        let format_config = self.get_format_config().await;
+        let actions =
+            code_actions::provide_code_actions(&uri, range, &diagnostics, &text, &format_config);
+
+        Ok(Some(actions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hover::*;
+    use tower_lsp::LspService;
+
+    #[test]
+    fn hover_plus_json_is_capped_deterministically() {
+        let mut model = HoverModel {
             schema_version: "1.0".to_string(),
             id: "id".to_string(),
             symbol: HoverSymbol {
@@ -734,6 +2788,7 @@ mod tests {
                     qualified_name: format!("default::R{i:03}"),
                     kind: "Resource".to_string(),
                     relevance_score: 1,
+                    range: None,
                 })
                 .collect(),
             limits: HoverLimits {
@@ -797,4 +2852,1960 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
         assert!(resp.markdown.is_some());
         assert!(resp.model.schema_version == "1.0");
     }
+
+    #[tokio::test]
+    async fn hover_markdown_respects_configured_budget() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "hover": { "maxMarkdownBytes": 160 } }
+                }),
+            })
+            .await;
+
+        let uri = Url::parse("file:///budget.sea").unwrap();
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+"#;
+        let line_index = crate::line_index::LineIndex::new(source);
+        let offset = source.find("\"Warehouse\"").unwrap() + 2;
+
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: source.to_string(),
+                },
+            })
+            .await;
+
+        let resp = backend
+            .hover_plus(HoverPlusParams {
+                text_document: HoverTextDocumentIdentifier { uri },
+                position: line_index.position_of(offset),
+                include_markdown: true,
+                include_project_signals: false,
+                max_detail_level: None,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(resp.model.limits.max_markdown_bytes, 160);
+        assert_eq!(resp.model.limits.max_json_bytes, DEFAULT_MAX_JSON_BYTES);
+        let markdown = resp.markdown.unwrap();
+        assert!(
+            markdown.ends_with("… truncated. Use hoverPlus for full detail."),
+            "got: {markdown}"
+        );
+    }
+
+    #[tokio::test]
+    async fn hover_cache_sizes_are_configurable() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "hover": { "modelCacheSize": 1, "markdownCacheSize": 1 } }
+                }),
+            })
+            .await;
+
+        let uri = Url::parse("file:///cache.sea").unwrap();
+        let source = "Entity \"Warehouse\"\nEntity \"Factory\"\n";
+        let line_index = crate::line_index::LineIndex::new(source);
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: source.to_string(),
+                },
+            })
+            .await;
+
+        let first = line_index.position_of(source.find("\"Warehouse\"").unwrap() + 2);
+        let second = line_index.position_of(source.find("\"Factory\"").unwrap() + 2);
+        for position in [first, second] {
+            backend
+                .hover_plus(HoverPlusParams {
+                    text_document: HoverTextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                    include_markdown: true,
+                    include_project_signals: false,
+                    max_detail_level: None,
+                })
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let models = backend.hover_model_cache.lock().await;
+        assert_eq!(models.cap().get(), 1);
+        assert_eq!(models.len(), 1);
+        let key = |position| HoverCacheKey::model(&uri, 1, position, DetailLevel::Standard);
+        assert!(!models.contains(&key(first)), "first hover was evicted");
+        assert!(models.contains(&key(second)));
+        assert_eq!(backend.hover_markdown_cache.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn plain_hover_uses_the_initialization_detail_level() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({
+                    "hover": { "detailLevel": "deep" }
+                })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let uri = Url::parse("file:///detail.sea").unwrap();
+        let source = "Entity \"Warehouse\"\n";
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    source.to_string(),
+                ),
+            })
+            .await;
+        let position = Position::new(0, 9);
+        let hover = || HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                position,
+            ),
+            work_done_progress_params: Default::default(),
+        };
+        let key = |level| HoverCacheKey::model(&uri, 1, position, level);
+
+        LanguageServer::hover(backend, hover())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(backend
+            .hover_model_cache
+            .lock()
+            .await
+            .contains(&key(DetailLevel::Deep)));
+
+        // A configured level wins over the initialization option
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "hover": { "detailLevel": "core" } }
+                }),
+            })
+            .await;
+        LanguageServer::hover(backend, hover())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(backend
+            .hover_model_cache
+            .lock()
+            .await
+            .contains(&key(DetailLevel::Core)));
+    }
+
+    #[tokio::test]
+    async fn standard_moniker_uses_the_qualified_name() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let uri = Url::parse("file:///moniker.sea").unwrap();
+        let source = "@namespace \"logistics\"\n\nEntity \"Warehouse\"\n";
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: source.to_string(),
+                },
+            })
+            .await;
+        let position = Position::new(2, 9);
+
+        let monikers = LanguageServer::moniker(
+            backend,
+            tower_lsp::lsp_types::MonikerParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .expect("moniker for the entity");
+        let resolution = backend
+            .resolve(ResolveParams {
+                text_document: HoverTextDocumentIdentifier { uri },
+                position,
+            })
+            .await
+            .unwrap()
+            .expect("resolution for the entity");
+
+        assert_eq!(monikers.len(), 1);
+        assert_eq!(monikers[0].scheme, "domainforge");
+        assert_eq!(monikers[0].identifier, resolution.qualified_name);
+        assert!(monikers[0].identifier.ends_with("Warehouse"));
+        assert_eq!(monikers[0].unique, UniquenessLevel::Scheme);
+    }
+
+    /// Open a valid document at `uri`, then edit it so it no longer parses. Returns
+    /// the valid text.
+    async fn open_then_break(backend: &Backend, uri: &Url) -> String {
+        let valid = "@namespace \"logistics\"\n\nEntity \"Warehouse\"\n".to_string();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: valid.clone(),
+                },
+            })
+            .await;
+        backend
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 2,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: format!("{valid}Flow \"\n"),
+                }],
+            })
+            .await;
+        assert!(backend.documents.read().await[uri].graph.is_none());
+        valid
+    }
+
+    #[tokio::test]
+    async fn shutdown_drops_open_documents_and_caches() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let uri = Url::parse("file:///shutdown.sea").unwrap();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    "Entity \"Warehouse\"\n".to_string(),
+                ),
+            })
+            .await;
+        LanguageServer::hover(
+            backend,
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(uri.clone()),
+                    Position::new(0, 9),
+                ),
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+        let ticket = backend.validation_debounce.schedule(&uri).await;
+
+        backend.shutdown().await.unwrap();
+
+        assert_eq!(backend.documents.read().await.len(), 0);
+        assert_eq!(backend.hover_model_cache.lock().await.len(), 0);
+        assert!(!backend.validation_debounce.is_latest(&uri, ticket).await);
+    }
+
+    #[tokio::test]
+    async fn min_confidence_hides_ambiguous_hovers() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let uri = Url::parse("file:///ambiguous.sea").unwrap();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    "Entity \"Depot\" in north\nEntity \"Depot\" in south\n".to_string(),
+                ),
+            })
+            .await;
+        let hover = || HoverPlusParams {
+            text_document: HoverTextDocumentIdentifier { uri: uri.clone() },
+            position: Position::new(0, 9),
+            include_markdown: false,
+            include_project_signals: false,
+            max_detail_level: None,
+        };
+
+        let response = backend.hover_plus(hover()).await.unwrap();
+        assert_eq!(
+            response.unwrap().model.symbol.resolution_confidence,
+            "ambiguous"
+        );
+
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "hover": { "minConfidence": "exact" } }
+                }),
+            })
+            .await;
+        assert!(backend.hover_plus(hover()).await.unwrap().is_none());
+        let plain = LanguageServer::hover(
+            backend,
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(uri.clone()),
+                    Position::new(0, 9),
+                ),
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(plain.is_none());
+    }
+
+    #[tokio::test]
+    async fn stale_graph_fallback_can_be_disabled() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "hover": { "useStaleGraph": false } }
+                }),
+            })
+            .await;
+
+        let uri = Url::parse("file:///stale.sea").unwrap();
+        open_then_break(backend, &uri).await;
+        let model = backend
+            .hover_plus(HoverPlusParams {
+                text_document: HoverTextDocumentIdentifier { uri },
+                position: Position::new(2, 9),
+                include_markdown: false,
+                include_project_signals: false,
+                max_detail_level: None,
+            })
+            .await
+            .unwrap()
+            .expect("hover for the entity")
+            .model;
+
+        assert_eq!(model.symbol.resolution_confidence, "error_fallback");
+        assert!(!model.primary.badges.contains(&"stale".to_string()));
+    }
+
+    #[tokio::test]
+    async fn completion_falls_back_to_the_last_good_graph() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///stale.sea").unwrap();
+        let valid = open_then_break(backend, &uri).await;
+        backend
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 3,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: format!("{valid}Flow \"Crates\" from \"\n"),
+                }],
+            })
+            .await;
+        let labels = || async {
+            let response = backend
+                .completion(CompletionParams {
+                    text_document_position: TextDocumentPositionParams::new(
+                        TextDocumentIdentifier::new(uri.clone()),
+                        Position::new(3, "Flow \"Crates\" from \"".len() as u32),
+                    ),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                    context: None,
+                })
+                .await
+                .unwrap();
+            match response {
+                Some(CompletionResponse::Array(items)) => {
+                    items.into_iter().map(|item| item.label).collect::<Vec<_>>()
+                }
+                other => panic!("expected completion items, got {other:?}"),
+            }
+        };
+
+        assert_eq!(labels().await, vec!["Warehouse"]);
+
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "hover": { "useStaleGraph": false } }
+                }),
+            })
+            .await;
+        assert!(labels().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn hover_falls_back_to_the_last_good_graph() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let uri = Url::parse("file:///stale.sea").unwrap();
+        let valid = open_then_break(backend, &uri).await;
+
+        let hover_plus = || HoverPlusParams {
+            text_document: HoverTextDocumentIdentifier { uri: uri.clone() },
+            position: Position::new(2, 9),
+            include_markdown: true,
+            include_project_signals: false,
+            max_detail_level: None,
+        };
+        let response = backend
+            .hover_plus(hover_plus())
+            .await
+            .unwrap()
+            .expect("hover for the entity");
+        let model = response.model;
+
+        assert!(response.markdown.unwrap().contains("stale"));
+        assert_eq!(model.symbol.resolution_confidence, "stale");
+        assert!(model.primary.badges.contains(&"stale".to_string()));
+        assert!(model
+            .primary
+            .facts
+            .contains(&("namespace".to_string(), "logistics".to_string())));
+
+        backend
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 3,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: valid,
+                }],
+            })
+            .await;
+        assert!(backend.documents.read().await[&uri]
+            .last_good_graph
+            .is_none());
+        let model = backend
+            .hover_plus(hover_plus())
+            .await
+            .unwrap()
+            .expect("hover for the entity")
+            .model;
+        assert_eq!(model.symbol.resolution_confidence, "exact");
+    }
+
+    #[tokio::test]
+    async fn diagnostics_all_covers_every_open_document() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let valid = Url::parse("file:///valid.sea").unwrap();
+        let broken = Url::parse("file:///broken.sea").unwrap();
+        for (uri, text) in [
+            (&valid, "Entity \"Warehouse\"\n"),
+            (&broken, "Entity \"Warehouse\"\nFlow \"\n"),
+        ] {
+            backend
+                .did_open(DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem {
+                        uri: uri.clone(),
+                        language_id: "domainforge".to_string(),
+                        version: 1,
+                        text: text.to_string(),
+                    },
+                })
+                .await;
+        }
+
+        let all = backend.diagnostics_all().await.unwrap();
+
+        assert_eq!(all.len(), 2);
+        assert!(all[&valid].is_empty());
+        assert_eq!(all[&broken].len(), 1, "got {:?}", all[&broken]);
+    }
+
+    #[tokio::test]
+    async fn initialization_option_sets_the_log_level() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let env_level = log::max_level();
+
+        backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({ "logLevel": "nope" })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(log::max_level(), env_level);
+
+        backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({ "logLevel": "debug" })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(log::max_level(), log::LevelFilter::Debug);
+        crate::logging::set_level(env_level);
+    }
+
+    #[tokio::test]
+    async fn disabled_features_drop_their_capability_and_handler() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let result = backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({
+                    "features": {
+                        "completion": false,
+                        "diagnostics": false,
+                        "formatting": false,
+                        "navigation": false
+                    }
+                })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(result.capabilities.completion_provider.is_none());
+        assert!(result.capabilities.document_formatting_provider.is_none());
+        assert!(result.capabilities.definition_provider.is_none());
+        assert!(result.capabilities.folding_range_provider.is_none());
+        assert!(result.capabilities.hover_provider.is_some());
+        assert!(result.capabilities.inline_value_provider.is_some());
+        let Some(CodeActionProviderCapability::Options(code_actions)) =
+            result.capabilities.code_action_provider
+        else {
+            panic!("code actions are advertised with their kinds");
+        };
+        assert_eq!(
+            code_actions.code_action_kinds.unwrap(),
+            [
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR,
+                CodeActionKind::REFACTOR_EXTRACT,
+                CodeActionKind::REFACTOR_REWRITE,
+            ]
+        );
+
+        let uri = Url::parse("file:///features.sea").unwrap();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    "Entity \"Warehouse\"\n".to_string(),
+                ),
+            })
+            .await;
+        let completion = LanguageServer::completion(
+            backend,
+            CompletionParams {
+                text_document_position: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(uri.clone()),
+                    Position::new(1, 0),
+                ),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(completion.is_none());
+
+        // Custom requests follow the same switches as the standard ones
+        let text_document = || TextDocumentIdentifier::new(uri.clone());
+        let position = Position::new(0, 9);
+        assert!(backend.diagnostics_all().await.unwrap().is_empty());
+        let check = backend.format_check(FormatCheckParams {
+            uri: Some(uri.clone()),
+            text: None,
+        });
+        assert!(check.await.unwrap().is_none());
+        let breadcrumb = backend.breadcrumb(BreadcrumbParams {
+            text_document: text_document(),
+            position,
+        });
+        assert!(breadcrumb.await.unwrap().is_empty());
+        let references = backend.graph_references(GraphReferencesParams {
+            text_document: text_document(),
+            position,
+        });
+        assert!(references.await.unwrap().is_none());
+        let workspace_references = backend.workspace_references(ReferenceParams {
+            text_document_position: TextDocumentPositionParams::new(text_document(), position),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        });
+        assert!(workspace_references.await.unwrap().is_empty());
+        let folding = LanguageServer::folding_range(
+            backend,
+            FoldingRangeParams {
+                text_document: text_document(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        assert!(folding.await.unwrap().is_none());
+
+        // Settings pushed later keep the switches advertised at initialize
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "completion": { "maxItems": 10 } }
+                }),
+            })
+            .await;
+        assert!(!backend.config.read().await.features.completion);
+        assert_eq!(backend.config.read().await.completion.max_items, 10);
+    }
+
+    #[tokio::test]
+    async fn renames_and_type_hierarchy_cover_every_configured_extension() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let result = backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({
+                    "fileExtensions": ["sea", ".dsea"]
+                })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let did_rename = result
+            .capabilities
+            .workspace
+            .and_then(|w| w.file_operations)
+            .and_then(|ops| ops.did_rename)
+            .unwrap();
+        let globs: Vec<String> = did_rename
+            .filters
+            .into_iter()
+            .map(|filter| filter.pattern.glob)
+            .collect();
+        assert_eq!(globs, ["**/*.sea", "**/*.dsea"]);
+        assert_eq!(
+            backend.config.read().await.file_extensions,
+            ["sea", ".dsea"]
+        );
+
+        let registration = crate::capabilities::type_hierarchy_registration(
+            &backend.config.read().await.file_extensions,
+        );
+        assert_eq!(
+            registration.register_options.unwrap()["documentSelector"],
+            serde_json::json!([{ "pattern": "**/*.sea" }, { "pattern": "**/*.dsea" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn disabled_hover_covers_monikers_quick_info_and_resolve() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let result = backend
+            .initialize(InitializeParams {
+                initialization_options: Some(serde_json::json!({
+                    "features": { "hover": false }
+                })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(result.capabilities.moniker_provider.is_none());
+        assert!(result.capabilities.inline_value_provider.is_none());
+
+        let uri = Url::parse("file:///features.sea").unwrap();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    "Entity \"Warehouse\"\n".to_string(),
+                ),
+            })
+            .await;
+        let text_document = || HoverTextDocumentIdentifier { uri: uri.clone() };
+        let position = Position::new(0, 9);
+
+        let moniker = backend.moniker(MonikerParams {
+            text_document: text_document(),
+            position,
+        });
+        assert!(moniker.await.unwrap().is_none());
+        let resolved = backend.resolve(ResolveParams {
+            text_document: text_document(),
+            position,
+        });
+        assert!(resolved.await.unwrap().is_none());
+        let quick_info = backend.quick_info(QuickInfoParams {
+            text_document: text_document(),
+            position,
+        });
+        assert!(quick_info.await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn inline_values_need_hover_and_an_open_document_that_parses() {
+        let source = "Entity \"Vendor\"\nInstance vendor_123 of \"Vendor\" {\n  name: \"Acme\"\n}\nPolicy known as: @vendor_123 = \"Acme\"\n";
+        let backend_with = |options: Value| async move {
+            let (service, _socket) = LspService::new(Backend::new);
+            service
+                .inner()
+                .initialize(InitializeParams {
+                    initialization_options: Some(options),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            service
+        };
+        let uri = Url::parse("file:///inline.sea").unwrap();
+        let params = || InlineValueParams {
+            work_done_progress_params: Default::default(),
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range: Range::new(Position::new(0, 0), Position::new(5, 0)),
+            context: InlineValueContext {
+                frame_id: 0,
+                stopped_location: Range::default(),
+            },
+        };
+
+        let service = backend_with(serde_json::json!({})).await;
+        let backend = service.inner();
+        // Not open yet
+        assert!(LanguageServer::inline_value(backend, params())
+            .await
+            .unwrap()
+            .is_none());
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    source.to_string(),
+                ),
+            })
+            .await;
+        let values = LanguageServer::inline_value(backend, params())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(values.len(), 1);
+
+        // No graph while the text doesn't parse
+        change_document(backend, &uri, &format!("{source}Entity\n"), 2).await;
+        assert!(LanguageServer::inline_value(backend, params())
+            .await
+            .unwrap()
+            .is_none());
+
+        let service = backend_with(serde_json::json!({ "features": { "hover": false } })).await;
+        let backend = service.inner();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "sea".to_string(),
+                    1,
+                    source.to_string(),
+                ),
+            })
+            .await;
+        assert!(LanguageServer::inline_value(backend, params())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn completion_triggers_are_advertised_and_overridable() {
+        let trigger_characters = |options: Option<Value>| async move {
+            let (service, _socket) = LspService::new(Backend::new);
+            let result = service
+                .inner()
+                .initialize(InitializeParams {
+                    initialization_options: options,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            let completion = result.capabilities.completion_provider.unwrap();
+            assert_eq!(completion.all_commit_characters, None);
+            completion.trigger_characters.unwrap()
+        };
+
+        let defaults = trigger_characters(None).await;
+        assert!(defaults.contains(&"\"".to_string()));
+        assert!(defaults.contains(&"@".to_string()));
+
+        let overridden = trigger_characters(Some(serde_json::json!({
+            "completion": { "triggerCharacters": ["@"] }
+        })))
+        .await;
+        assert_eq!(overridden, vec!["@".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn hover_uses_the_first_preferred_content_format() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        backend
+            .initialize(InitializeParams {
+                capabilities: ClientCapabilities {
+                    text_document: Some(TextDocumentClientCapabilities {
+                        hover: Some(HoverClientCapabilities {
+                            dynamic_registration: None,
+                            content_format: Some(vec![MarkupKind::PlainText, MarkupKind::Markdown]),
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let uri = Url::parse("file:///plain.sea").unwrap();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: "@namespace \"logistics\"\n\nEntity \"Warehouse\"\n".to_string(),
+                },
+            })
+            .await;
+
+        let hover = LanguageServer::hover(
+            backend,
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: Position::new(2, 9),
+                },
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await
+        .unwrap()
+        .expect("hover for the entity");
+
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup content");
+        };
+        assert_eq!(content.kind, MarkupKind::PlainText);
+        assert!(content.value.starts_with("Entity logistics::Warehouse"));
+        assert!(content.value.contains("namespace: logistics"));
+        assert!(!content.value.contains("##"));
+    }
+
+    #[tokio::test]
+    async fn graph_diff_holds_both_versions_to_the_document_size_limit() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        backend.config.write().await.max_document_bytes = 64;
+        let diff = |base: &str, proposed: &str| {
+            backend.graph_diff(GraphDiffParams {
+                uri: None,
+                base: Some(base.to_string()),
+                proposed: proposed.to_string(),
+            })
+        };
+
+        let added = diff("Entity \"Farm\"\n", "Entity \"Farm\"\nEntity \"Depot\"\n")
+            .await
+            .unwrap();
+        assert_eq!(added.entities.added, vec!["default::Depot"]);
+
+        let large = "Entity \"Farm\"\n".repeat(8);
+        let error = diff("Entity \"Farm\"\n", &large).await.unwrap_err();
+        assert!(
+            error.message.starts_with("Proposed text is 112 bytes"),
+            "{}",
+            error.message
+        );
+        let error = diff("Entity\n", "Entity \"Farm\"\n").await.unwrap_err();
+        assert!(error.message.starts_with("Base text does not parse"));
+    }
+
+    #[tokio::test]
+    async fn format_check_reads_inline_text_or_the_open_document() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let messy = backend
+            .format_check(FormatCheckParams {
+                uri: None,
+                text: Some("Entity   \"Test\"    in   domain\n".to_string()),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!messy.formatted);
+        assert!(messy.diff.is_some_and(|diff| !diff.is_empty()));
+
+        let uri = Url::parse("file:///clean.sea").unwrap();
+        let clean = format_document("Entity   \"Test\"    in   domain\n", None)
+            .remove(0)
+            .new_text;
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: clean,
+                },
+            })
+            .await;
+        let result = backend
+            .format_check(FormatCheckParams {
+                uri: Some(uri),
+                text: None,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(result.formatted);
+        assert_eq!(result.diff, None);
+
+        assert!(backend
+            .format_check(FormatCheckParams {
+                uri: None,
+                text: None,
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn hovers_queue_beyond_max_concurrent_requests() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({ "domainforge": { "maxConcurrentRequests": 1 } }),
+            })
+            .await;
+
+        let uri = Url::parse("file:///busy.sea").unwrap();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: "Entity \"Warehouse\"\n".to_string(),
+                },
+            })
+            .await;
+        let hover = || {
+            LanguageServer::hover(
+                backend,
+                HoverParams {
+                    text_document_position_params: TextDocumentPositionParams {
+                        text_document: TextDocumentIdentifier { uri: uri.clone() },
+                        position: Position::new(0, 9),
+                    },
+                    work_done_progress_params: Default::default(),
+                },
+            )
+        };
+
+        // Hold the only slot, as an in-flight hover would
+        let in_flight = backend.request_permit().await;
+        let queued = tokio::time::timeout(Duration::from_millis(100), hover()).await;
+        assert!(queued.is_err(), "hover ran while the only slot was taken");
+
+        drop(in_flight);
+        let hovered = tokio::time::timeout(Duration::from_secs(5), hover())
+            .await
+            .expect("hover runs once the slot frees")
+            .unwrap();
+        assert!(hovered.is_some());
+
+        // Each hover released its slot on return
+        let _again = tokio::time::timeout(Duration::from_secs(1), backend.request_permit())
+            .await
+            .expect("slot released after the hover returned");
+    }
+
+    #[tokio::test]
+    async fn flows_lists_every_flow_with_resolved_unit() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let uri = Url::parse("file:///flows.sea").unwrap();
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Entity "Store"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+Flow "Cameras" from "Factory" to "Store"
+"#;
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text: source.to_string(),
+                },
+            })
+            .await;
+
+        let flows = backend
+            .flows(FlowsParams {
+                text_document: TextDocumentIdentifier { uri },
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].resource, "Cameras");
+        assert_eq!(flows[0].from, "Warehouse");
+        assert_eq!(flows[0].to, "Factory");
+        assert_eq!(flows[0].quantity.as_deref(), Some("10"));
+        assert_eq!(flows[0].unit.as_deref(), Some("units"));
+        assert_eq!(flows[0].range.start.line, 5);
+        assert_eq!(flows[1].from, "Factory");
+        assert_eq!(flows[1].to, "Store");
+        assert_eq!(flows[1].quantity, None);
+        assert_eq!(flows[1].range.start.line, 6);
+    }
+
+    #[tokio::test]
+    async fn diagnostics_are_pulled_or_pushed_as_the_client_declares() {
+        // Returns whether pull diagnostics were advertised and whether a broken
+        // document's diagnostics were then pushed.
+        let negotiate = |capabilities: Value| async move {
+            let (service, socket) = LspService::new(Backend::new);
+            let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+            let (server_read, server_write) = tokio::io::split(server_io);
+            tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+            let (client_read, mut writer) = tokio::io::split(client_io);
+            let mut reader = tokio::io::BufReader::new(client_read);
+
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                    "params": { "capabilities": capabilities }
+                }),
+            )
+            .await;
+            let response = messages_until(1, &mut reader).await.pop().unwrap();
+            let advertised = !response["result"]["capabilities"]["diagnosticProvider"].is_null();
+
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                loop {
+                    if tx.send(read_message(&mut reader).await).is_err() {
+                        return;
+                    }
+                }
+            });
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                    "params": { "textDocument": {
+                        "uri": "file:///broken.sea", "languageId": "domainforge",
+                        "version": 1, "text": "Entity\n"
+                    } }
+                }),
+            )
+            .await;
+            (advertised, published(&mut rx).await)
+        };
+
+        let pull = serde_json::json!({ "textDocument": { "diagnostic": {} } });
+        assert_eq!(negotiate(pull).await, (true, false));
+        assert_eq!(negotiate(serde_json::json!({})).await, (false, true));
+    }
+
+    #[tokio::test]
+    async fn rapid_changes_validate_only_the_latest_version() {
+        let (service, socket) = LspService::new(Backend::new);
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+        let (client_read, mut writer) = tokio::io::split(client_io);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut reader = tokio::io::BufReader::new(client_read);
+            loop {
+                if tx.send(read_message(&mut reader).await).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let uri = "file:///burst.sea";
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                "params": { "capabilities": {} }
+            }),
+        )
+        .await;
+        // Notifications sent before the initialize response are dropped.
+        while rx.recv().await.unwrap()["id"] != 1 {}
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                "params": { "textDocument": {
+                    "uri": uri, "languageId": "domainforge", "version": 1, "text": "Entity \"A\"\n"
+                } }
+            }),
+        )
+        .await;
+        assert!(published(&mut rx).await, "open publishes diagnostics");
+
+        // More changes than tower-lsp handles at once, ending in a broken version.
+        for version in 2..=7 {
+            let text = if version == 7 {
+                "Entity\n".to_string()
+            } else {
+                format!("Entity \"V{version}\"\n")
+            };
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "method": "textDocument/didChange",
+                    "params": {
+                        "textDocument": { "uri": uri, "version": version },
+                        "contentChanges": [{ "text": text }]
+                    }
+                }),
+            )
+            .await;
+        }
+        let sent = std::time::Instant::now();
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover",
+                "params": {
+                    "textDocument": { "uri": uri },
+                    "position": { "line": 0, "character": 0 }
+                }
+            }),
+        )
+        .await;
+
+        // Pending debounces don't hold up other requests.
+        let mut publishes = Vec::new();
+        loop {
+            let message = rx.recv().await.unwrap();
+            if message["id"] == 2 {
+                break;
+            }
+            if message["method"] == "textDocument/publishDiagnostics" {
+                publishes.push(message);
+            }
+        }
+        assert!(sent.elapsed() < VALIDATION_DEBOUNCE);
+
+        let quiet = Duration::from_millis(400);
+        while let Ok(Some(message)) = tokio::time::timeout(quiet, rx.recv()).await {
+            if message["method"] == "textDocument/publishDiagnostics" {
+                publishes.push(message);
+            }
+        }
+        assert_eq!(publishes.len(), 1);
+        let diagnostics = publishes[0]["params"]["diagnostics"].as_array().unwrap();
+        assert!(!diagnostics.is_empty(), "latest version is the broken one");
+    }
+
+    /// Send a full-text `didChange` for `uri`.
+    async fn change_document(backend: &Backend, uri: &Url, text: &str, version: i32) {
+        backend
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: text.to_string(),
+                }],
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn identical_text_change_keeps_the_existing_index() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///same.sea").unwrap();
+        let text = "Entity \"Warehouse\"\nEntity \"Factory\"\n";
+        backend.documents.write().await.insert(
+            uri.clone(),
+            DocumentState::new(text.to_string(), 1, DEFAULT_MAX_DOCUMENT_BYTES),
+        );
+        let snapshot = || async {
+            let documents = backend.documents.read().await;
+            let state = &documents[&uri];
+            let index = state.semantic_index.as_ref().unwrap();
+            (
+                state.version,
+                index.declarations.as_ptr(),
+                index.declarations.len(),
+            )
+        };
+        let (_, before, _) = snapshot().await;
+
+        change_document(backend, &uri, text, 2).await;
+        let (version, after, _) = snapshot().await;
+        assert_eq!(version, 2);
+        assert_eq!(after, before, "index was rebuilt");
+
+        change_document(backend, &uri, &format!("{text}Entity \"Depot\"\n"), 3).await;
+        let (version, _, count) = snapshot().await;
+        assert_eq!((version, count), (3, 3));
+    }
+
+    #[test]
+    fn changes_parsed_after_a_close_or_a_newer_version_are_dropped() {
+        let uri = Url::parse("file:///closed.sea").unwrap();
+        let state = |version| {
+            DocumentState::new(
+                "Entity \"A\"\n".to_string(),
+                version,
+                DEFAULT_MAX_DOCUMENT_BYTES,
+            )
+        };
+        let mut documents = HashMap::new();
+
+        // Closed while version 2 parsed
+        assert!(!store_changed_document(&mut documents, &uri, state(2)));
+        assert!(documents.is_empty(), "closed document was reopened");
+
+        documents.insert(uri.clone(), state(3));
+        assert!(!store_changed_document(&mut documents, &uri, state(2)));
+        assert_eq!(documents[&uri].version, 3);
+        assert!(store_changed_document(&mut documents, &uri, state(4)));
+        assert_eq!(documents[&uri].version, 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn large_documents_open_concurrently() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let source = |prefix: &str| -> String {
+            (0..1000)
+                .map(|i| format!("Entity \"{prefix}{i}\"\n"))
+                .collect()
+        };
+        let open = |name: &str, text: String| {
+            backend.did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::parse(&format!("file:///{name}.sea")).unwrap(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text,
+                },
+            })
+        };
+
+        // A ticker on the runtime keeps running while both documents parse.
+        let parsing = AtomicBool::new(true);
+        let ticker = async {
+            let mut ticks = 0u32;
+            while parsing.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                ticks += 1;
+            }
+            ticks
+        };
+        let opens = async {
+            tokio::join!(open("a", source("A")), open("b", source("B")));
+            parsing.store(false, Ordering::Relaxed);
+        };
+        let ((), ticks) = tokio::time::timeout(Duration::from_secs(30), async {
+            tokio::join!(opens, ticker)
+        })
+        .await
+        .expect("opening large documents timed out");
+        assert!(ticks > 0);
+
+        let documents = backend.documents.read().await;
+        for name in ["a", "b"] {
+            let state = &documents[&Url::parse(&format!("file:///{name}.sea")).unwrap()];
+            let index = state.semantic_index.as_ref().unwrap();
+            assert_eq!(index.declarations.len(), 1000);
+        }
+    }
+
+    #[test]
+    fn orphaned_resource_check_is_opt_in() {
+        let uri = Url::parse("file:///orphans.sea").unwrap();
+        let text = "Entity \"Farm\"\nResource \"Pallets\" units\n".to_string();
+        let state = DocumentState::new(text, 1, DEFAULT_MAX_DOCUMENT_BYTES);
+        let default = DomainForgeConfig::default();
+        let mut opted_in = DomainForgeConfig::default();
+        opted_in.validation.orphaned_resources = true;
+
+        assert!(state.diagnostics(&uri, &default).is_empty());
+        let diagnostics = state.diagnostics(&uri, &opted_in);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("W700".to_string()))
+        );
+        assert_ne!(
+            state.result_id(&uri, &default),
+            state.result_id(&uri, &opted_in)
+        );
+    }
+
+    #[test]
+    fn inferred_required_field_check_is_opt_in() {
+        let uri = Url::parse("file:///fields.sea").unwrap();
+        let text = "Entity \"Vendor\"\n\
+                    Instance acme of \"Vendor\" {\n  name: \"Acme\"\n}\n\
+                    Instance initech of \"Vendor\" {\n  name: \"Initech\"\n}\n\
+                    Instance globex of \"Vendor\"\n"
+            .to_string();
+        let state = DocumentState::new(text, 1, DEFAULT_MAX_DOCUMENT_BYTES);
+        let default = DomainForgeConfig::default();
+        let mut opted_in = DomainForgeConfig::default();
+        opted_in.validation.inferred_required_fields = true;
+
+        assert!(state.diagnostics(&uri, &default).is_empty());
+        let diagnostics = state.diagnostics(&uri, &opted_in);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("W500".to_string()))
+        );
+        assert_ne!(
+            state.result_id(&uri, &default),
+            state.result_id(&uri, &opted_in)
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_documents_disable_language_features() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        backend.config.write().await.max_document_bytes = 64;
+
+        let uri = Url::parse("file:///huge.sea").unwrap();
+        let text: String = (0..10).map(|i| format!("Entity \"E{i}\"\n")).collect();
+        assert!(text.len() > 64);
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "domainforge".to_string(),
+                    version: 1,
+                    text,
+                },
+            })
+            .await;
+
+        let state = backend.documents.read().await[&uri].clone();
+        assert!(state.graph.is_none() && state.semantic_index.is_none());
+        let diagnostics = state.diagnostics(&uri, &DomainForgeConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].severity,
+            Some(DiagnosticSeverity::INFORMATION)
+        );
+
+        let position = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position::new(0, 9),
+        };
+        let hover = backend
+            .hover(HoverParams {
+                text_document_position_params: position.clone(),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        assert!(hover.is_none());
+        let completion = backend
+            .completion(CompletionParams {
+                text_document_position: position,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            })
+            .await
+            .unwrap();
+        assert!(completion.is_none());
+    }
+
+    #[tokio::test]
+    async fn workspace_diagnostics_report_only_changed_files_in_full() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+
+        let dir = tempfile::tempdir().unwrap();
+        let on_disk = dir.path().join("disk.sea");
+        let disk_text = "Entity \"Depot\"\n";
+        std::fs::write(&on_disk, disk_text).unwrap();
+        let disk_uri = Url::from_file_path(&on_disk).unwrap();
+        backend.workspace_files.write().await.insert(
+            disk_uri.clone(),
+            IndexedFile {
+                line_index: LineIndex::new(disk_text),
+                index: SemanticIndex::build(disk_text),
+            },
+        );
+
+        let stable = Url::parse("file:///stable.sea").unwrap();
+        let edited = Url::parse("file:///edited.sea").unwrap();
+        {
+            let mut documents = backend.documents.write().await;
+            documents.insert(
+                stable.clone(),
+                DocumentState::new("Entity \"A\"\n".to_string(), 1, DEFAULT_MAX_DOCUMENT_BYTES),
+            );
+            documents.insert(
+                edited.clone(),
+                DocumentState::new("Entity \"B\"\n".to_string(), 1, DEFAULT_MAX_DOCUMENT_BYTES),
+            );
+        }
+
+        let pull = |previous_result_ids: Vec<PreviousResultId>| async {
+            let WorkspaceDiagnosticReportResult::Report(report) = backend
+                .workspace_diagnostic(WorkspaceDiagnosticParams {
+                    identifier: None,
+                    previous_result_ids,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                })
+                .await
+                .unwrap()
+            else {
+                panic!("expected a complete report");
+            };
+            report.items
+        };
+
+        let first = pull(Vec::new()).await;
+        assert_eq!(first.len(), 3);
+        let previous: Vec<PreviousResultId> = first
+            .iter()
+            .map(|item| match item {
+                WorkspaceDocumentDiagnosticReport::Full(report) => PreviousResultId {
+                    uri: report.uri.clone(),
+                    value: report
+                        .full_document_diagnostic_report
+                        .result_id
+                        .clone()
+                        .unwrap(),
+                },
+                WorkspaceDocumentDiagnosticReport::Unchanged(_) => {
+                    panic!("nothing was reported before")
+                }
+            })
+            .collect();
+
+        backend.documents.write().await.insert(
+            edited.clone(),
+            DocumentState::new(
+                "Entity \"B\"\nEntity \"C\"\n".to_string(),
+                2,
+                DEFAULT_MAX_DOCUMENT_BYTES,
+            ),
+        );
+
+        let second = pull(previous).await;
+        let kinds: HashMap<&Url, &str> = second
+            .iter()
+            .map(|item| match item {
+                WorkspaceDocumentDiagnosticReport::Full(report) => (&report.uri, "full"),
+                WorkspaceDocumentDiagnosticReport::Unchanged(report) => (&report.uri, "unchanged"),
+            })
+            .collect();
+        assert_eq!(kinds[&edited], "full");
+        assert_eq!(kinds[&stable], "unchanged");
+        assert_eq!(kinds[&disk_uri], "unchanged");
+    }
+
+    /// Frame and send one JSON-RPC message over a test transport.
+    async fn send_message(writer: &mut (impl tokio::io::AsyncWrite + Unpin), message: Value) {
+        use tokio::io::AsyncWriteExt;
+        let body = message.to_string();
+        let frame = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        writer.write_all(frame.as_bytes()).await.unwrap();
+    }
+
+    /// Read one framed JSON-RPC message from a test transport.
+    async fn read_message(reader: &mut (impl tokio::io::AsyncBufRead + Unpin)) -> Value {
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Everything the server sends up to and including the response to `id`.
+    async fn messages_until(
+        id: i64,
+        reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+    ) -> Vec<Value> {
+        let mut seen = Vec::new();
+        loop {
+            let message = read_message(reader).await;
+            let done = message["id"] == id;
+            seen.push(message);
+            if done {
+                return seen;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn verbose_trace_emits_log_trace_for_handled_requests() {
+        let (service, socket) = LspService::build(Backend::new)
+            .custom_method("$/setTrace", Backend::set_trace)
+            .finish();
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+        let (client_read, mut writer) = tokio::io::split(client_io);
+        let mut reader = tokio::io::BufReader::new(client_read);
+
+        let hover = |id: i64| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "textDocument/hover",
+                "params": {
+                    "textDocument": { "uri": "file:///traced.sea" },
+                    "position": { "line": 0, "character": 3 }
+                }
+            })
+        };
+        let exchange = async {
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                    "params": { "capabilities": {} }
+                }),
+            )
+            .await;
+            messages_until(1, &mut reader).await;
+            send_message(
+                &mut writer,
+                serde_json::json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            )
+            .await;
+
+            send_message(&mut writer, hover(2)).await;
+            let untraced = messages_until(2, &mut reader).await;
+            assert!(untraced.iter().all(|m| m["method"] != "$/logTrace"));
+
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "method": "$/setTrace",
+                    "params": { "value": "verbose" }
+                }),
+            )
+            .await;
+            send_message(&mut writer, hover(3)).await;
+            // The trace goes out on the client channel, so it may trail the response.
+            let mut seen = messages_until(3, &mut reader).await;
+            while !seen.iter().any(|m| m["method"] == "$/logTrace") {
+                seen.push(read_message(&mut reader).await);
+            }
+            seen
+        };
+        let traced = tokio::time::timeout(Duration::from_secs(5), exchange)
+            .await
+            .expect("server stopped responding");
+
+        let trace = traced.iter().find(|m| m["method"] == "$/logTrace").unwrap();
+        assert_eq!(trace["params"]["message"], "Handling textDocument/hover");
+        assert_eq!(trace["params"]["verbose"], "file:///traced.sea at 0:3");
+    }
+
+    #[tokio::test]
+    async fn workspace_indexing_reports_progress_to_the_client() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.sea"), "Entity \"A\"\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.sea"), "Entity \"B\"\n").unwrap();
+        let root = Url::from_directory_path(temp_dir.path()).unwrap();
+
+        let (service, socket) = LspService::new(Backend::new);
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+        let (client_read, mut writer) = tokio::io::split(client_io);
+        let mut reader = tokio::io::BufReader::new(client_read);
+
+        let exchange = async {
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                    "params": {
+                        "rootUri": root,
+                        "capabilities": { "window": { "workDoneProgress": true } }
+                    }
+                }),
+            )
+            .await;
+            messages_until(1, &mut reader).await;
+            send_message(
+                &mut writer,
+                serde_json::json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            )
+            .await;
+
+            let mut progress = Vec::new();
+            loop {
+                let message = read_message(&mut reader).await;
+                if message["method"] == "window/workDoneProgress/create" {
+                    send_message(
+                        &mut writer,
+                        serde_json::json!({ "jsonrpc": "2.0", "id": message["id"], "result": null }),
+                    )
+                    .await;
+                } else if message["method"] == "$/progress" {
+                    let done = message["params"]["value"]["kind"] == "end";
+                    progress.push(message["params"]["value"].clone());
+                    if done {
+                        return progress;
+                    }
+                }
+            }
+        };
+        let progress = tokio::time::timeout(Duration::from_secs(5), exchange)
+            .await
+            .expect("server stopped responding");
+
+        let kinds: Vec<&str> = progress
+            .iter()
+            .map(|value| value["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["begin", "report", "report", "end"]);
+        assert_eq!(progress[0]["message"], "2 files");
+        assert_eq!(progress[1]["percentage"], 50);
+        assert_eq!(progress[2]["percentage"], 100);
+        assert_eq!(progress[3]["message"], "Indexed 2 files");
+    }
+
+    /// Whether diagnostics are published before the server goes quiet.
+    async fn published(rx: &mut tokio::sync::mpsc::UnboundedReceiver<Value>) -> bool {
+        let quiet = Duration::from_millis(400);
+        while let Ok(Some(message)) = tokio::time::timeout(quiet, rx.recv()).await {
+            if message["method"] == "textDocument/publishDiagnostics" {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Which of open, change and save publish diagnostics under `trigger`.
+    async fn validation_publishes(trigger: &str) -> [bool; 3] {
+        let (service, socket) = LspService::new(Backend::new);
+        service
+            .inner()
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({
+                    "domainforge": { "validation": { "trigger": trigger } }
+                }),
+            })
+            .await;
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+        let (client_read, mut writer) = tokio::io::split(client_io);
+
+        // Read on a task so waiting out a quiet period never drops a partial message.
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut reader = tokio::io::BufReader::new(client_read);
+            loop {
+                if tx.send(read_message(&mut reader).await).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let uri = "file:///trigger.sea";
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                "params": { "capabilities": {} }
+            }),
+        )
+        .await;
+        send_message(
+            &mut writer,
+            serde_json::json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        )
+        .await;
+        // Drain the initialize response.
+        published(&mut rx).await;
+
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                "params": { "textDocument": {
+                    "uri": uri, "languageId": "domainforge", "version": 1, "text": "Entity \"A\"\n"
+                } }
+            }),
+        )
+        .await;
+        let open = published(&mut rx).await;
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "method": "textDocument/didChange",
+                "params": {
+                    "textDocument": { "uri": uri, "version": 2 },
+                    "contentChanges": [{ "text": "Entity \"B\"\n" }]
+                }
+            }),
+        )
+        .await;
+        let change = published(&mut rx).await;
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "method": "textDocument/didSave",
+                "params": { "textDocument": { "uri": uri } }
+            }),
+        )
+        .await;
+        let save = published(&mut rx).await;
+        [open, change, save]
+    }
+
+    #[tokio::test]
+    async fn rapid_validations_publish_once() {
+        let (service, socket) = LspService::new(Backend::new);
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+        let (client_read, mut writer) = tokio::io::split(client_io);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut reader = tokio::io::BufReader::new(client_read);
+            loop {
+                if tx.send(read_message(&mut reader).await).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let uri = "file:///burst.sea";
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                "params": { "capabilities": {} }
+            }),
+        )
+        .await;
+        send_message(
+            &mut writer,
+            serde_json::json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        )
+        .await;
+        // Drain the initialize response.
+        published(&mut rx).await;
+        send_message(
+            &mut writer,
+            serde_json::json!({
+                "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                "params": { "textDocument": {
+                    "uri": uri, "languageId": "domainforge", "version": 1, "text": "Entity \"A\"\n"
+                } }
+            }),
+        )
+        .await;
+        // Each save validates straight away, well inside one publish window.
+        for _ in 0..3 {
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "method": "textDocument/didSave",
+                    "params": { "textDocument": { "uri": uri } }
+                }),
+            )
+            .await;
+        }
+
+        let mut publishes = 0;
+        let quiet = Duration::from_millis(400);
+        while let Ok(Some(message)) = tokio::time::timeout(quiet, rx.recv()).await {
+            if message["method"] == "textDocument/publishDiagnostics" {
+                assert_eq!(message["params"]["uri"], uri);
+                publishes += 1;
+            }
+        }
+        assert_eq!(publishes, 1);
+    }
+
+    #[tokio::test]
+    async fn on_save_trigger_publishes_only_on_save() {
+        assert_eq!(validation_publishes("onSave").await, [false, false, true]);
+    }
+
+    #[tokio::test]
+    async fn on_change_trigger_skips_save() {
+        assert_eq!(validation_publishes("onChange").await, [true, true, false]);
+    }
+
+    #[tokio::test]
+    async fn both_trigger_publishes_on_every_event() {
+        assert_eq!(validation_publishes("both").await, [true, true, true]);
+    }
+
+    #[tokio::test]
+    async fn initialized_pulls_and_applies_the_domainforge_section() {
+        let (service, socket) = LspService::new(Backend::new);
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        tokio::spawn(tower_lsp::Server::new(server_read, server_write, socket).serve(service));
+        let (client_read, mut writer) = tokio::io::split(client_io);
+        let mut reader = tokio::io::BufReader::new(client_read);
+
+        let exchange = async {
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                    "params": { "capabilities": { "workspace": { "configuration": true } } }
+                }),
+            )
+            .await;
+            messages_until(1, &mut reader).await;
+            send_message(
+                &mut writer,
+                serde_json::json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            )
+            .await;
+
+            let request = loop {
+                let message = read_message(&mut reader).await;
+                if message["method"] == "workspace/configuration" {
+                    break message;
+                }
+            };
+            assert_eq!(request["params"]["items"][0]["section"], "domainforge");
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "id": request["id"],
+                    "result": [{ "hover": { "minConfidence": "exact" } }]
+                }),
+            )
+            .await;
+            // Let `initialized` finish applying the answer.
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                    "params": { "textDocument": {
+                        "uri": "file:///pulled.sea", "languageId": "domainforge",
+                        "version": 1,
+                        "text": "Entity \"Depot\" in north\nEntity \"Depot\" in south\n"
+                    } }
+                }),
+            )
+            .await;
+            // Hover once the document is indexed.
+            while read_message(&mut reader).await["method"] != "textDocument/publishDiagnostics" {}
+            send_message(
+                &mut writer,
+                serde_json::json!({
+                    "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover",
+                    "params": {
+                        "textDocument": { "uri": "file:///pulled.sea" },
+                        "position": { "line": 0, "character": 9 }
+                    }
+                }),
+            )
+            .await;
+            messages_until(2, &mut reader).await.pop().unwrap()
+        };
+        let hover = tokio::time::timeout(Duration::from_secs(5), exchange)
+            .await
+            .expect("server stopped responding");
+
+        // The ambiguous name falls below the pulled confidence threshold
+        assert_eq!(hover["result"], Value::Null);
+    }
 }
@@ -5,18 +5,44 @@
 
 use tower_lsp::lsp_types::*;
 
+use crate::backend::FeaturesConfig;
+use crate::code_actions::APPLY_NAMESPACE_COMMAND;
+use crate::completion;
+use crate::workspace::{EXTRACT_TO_FILE_COMMAND, NEW_FILE_COMMAND, RENAME_NAMESPACE_COMMAND};
+
+/// Dynamic registration for `textDocument/prepareTypeHierarchy`.
+///
+/// `ServerCapabilities` in lsp-types 0.94 has no `typeHierarchyProvider` field, so
+/// the type hierarchy is registered after `initialized` for clients that allow it.
+/// It covers files with one of `file_extensions`.
+pub fn type_hierarchy_registration(file_extensions: &[String]) -> Registration {
+    let selector: Vec<serde_json::Value> = extension_globs(file_extensions)
+        .into_iter()
+        .map(|glob| serde_json::json!({ "pattern": glob }))
+        .collect();
+    Registration {
+        id: "domainforge-type-hierarchy".to_string(),
+        method: "textDocument/prepareTypeHierarchy".to_string(),
+        register_options: Some(serde_json::json!({
+            "documentSelector": selector,
+        })),
+    }
+}
+
 /// Returns the server capabilities to be sent during initialization.
 ///
-/// Currently declares:
-/// - Text document sync (open/change/close)
-/// - Document formatting (Phase 2)
+/// Every feature enabled in `features` is advertised; switched-off ones are left out.
+/// Renames are watched for files with one of `file_extensions`.
 ///
-/// Future phases will add:
-/// - Completion
-/// - Hover
-/// - Go to definition
-/// - Find references
-pub fn server_capabilities() -> ServerCapabilities {
+/// Completion triggers on `trigger_characters` when set, otherwise on
+/// [`completion::TRIGGER_CHARACTERS`]. Pull diagnostics are only advertised when the
+/// client can pull (`pull_diagnostics`); other clients get them pushed.
+pub fn server_capabilities(
+    features: &FeaturesConfig,
+    trigger_characters: Option<Vec<String>>,
+    pull_diagnostics: bool,
+    file_extensions: &[String],
+) -> ServerCapabilities {
     ServerCapabilities {
         // Full document sync - receive entire document on each change
         text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -28,19 +54,87 @@ pub fn server_capabilities() -> ServerCapabilities {
             },
         )),
         // Document formatting (Phase 2)
-        document_formatting_provider: Some(OneOf::Left(true)),
-        completion_provider: Some(CompletionOptions {
+        document_formatting_provider: features.formatting.then_some(OneOf::Left(true)),
+        completion_provider: features.completion.then(|| CompletionOptions {
             resolve_provider: Some(false),
-            trigger_characters: Some(vec!["\"".to_string(), "@".to_string(), ".".to_string()]),
+            trigger_characters: Some(
+                trigger_characters.unwrap_or_else(|| to_strings(completion::TRIGGER_CHARACTERS)),
+            ),
             ..Default::default()
         }),
-        hover_provider: Some(HoverProviderCapability::Simple(true)),
-        definition_provider: Some(OneOf::Left(true)),
-        references_provider: Some(OneOf::Left(true)),
-        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
-            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+        hover_provider: features
+            .hover
+            .then_some(HoverProviderCapability::Simple(true)),
+        definition_provider: features.navigation.then_some(OneOf::Left(true)),
+        references_provider: features.navigation.then_some(OneOf::Left(true)),
+        workspace_symbol_provider: features.navigation.then_some(OneOf::Left(true)),
+        folding_range_provider: features
+            .navigation
+            .then_some(FoldingRangeProviderCapability::Simple(true)),
+        moniker_provider: features.hover.then_some(OneOf::Left(true)),
+        inline_value_provider: features.hover.then_some(OneOf::Left(true)),
+        linked_editing_range_provider: features
+            .navigation
+            .then_some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        code_action_provider: features.code_actions.then(|| {
+            CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![
+                    CodeActionKind::QUICKFIX,
+                    CodeActionKind::REFACTOR,
+                    CodeActionKind::REFACTOR_EXTRACT,
+                    CodeActionKind::REFACTOR_REWRITE,
+                ]),
+                ..Default::default()
+            })
+        }),
+        diagnostic_provider: (features.diagnostics && pull_diagnostics).then(|| {
+            DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: Some("domainforge".to_string()),
+                inter_file_dependencies: false,
+                workspace_diagnostics: true,
+                ..Default::default()
+            })
+        }),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![
+                RENAME_NAMESPACE_COMMAND.to_string(),
+                NEW_FILE_COMMAND.to_string(),
+                EXTRACT_TO_FILE_COMMAND.to_string(),
+                APPLY_NAMESPACE_COMMAND.to_string(),
+            ],
             ..Default::default()
-        })),
+        }),
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: None,
+            file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                did_rename: Some(FileOperationRegistrationOptions {
+                    filters: extension_globs(file_extensions)
+                        .into_iter()
+                        .map(|glob| FileOperationFilter {
+                            scheme: Some("file".to_string()),
+                            pattern: FileOperationPattern {
+                                glob,
+                                matches: Some(FileOperationPatternKind::File),
+                                options: None,
+                            },
+                        })
+                        .collect(),
+                }),
+                ..Default::default()
+            }),
+        }),
         ..Default::default()
     }
 }
+
+/// A `**/*.<ext>` glob per extension, given with or without the dot.
+fn extension_globs(extensions: &[String]) -> Vec<String> {
+    extensions
+        .iter()
+        .map(|ext| format!("**/*.{}", ext.trim_start_matches('.')))
+        .collect()
+}
+
+fn to_strings(chars: &[&str]) -> Vec<String> {
+    chars.iter().map(|c| c.to_string()).collect()
+}
@@ -5,6 +5,11 @@
 
 use tower_lsp::lsp_types::*;
 
+use crate::entity_fields;
+use crate::formatting::LspFormatConfig;
+use crate::line_index::LineIndex;
+use crate::semantic_index::{ByteRange, Declaration, NamespaceRole, SemanticIndex, SymbolKind};
+
 /// Provide available code actions for a given range and context.
 ///
 /// # Arguments
@@ -13,13 +18,16 @@ use tower_lsp::lsp_types::*;
 /// * `range` - The range for which code actions are requested
 /// * `diagnostics` - The diagnostics present in the context
 /// * `text` - The full text content of the document (used for analyzing context)
+/// * `format_config` - The current formatting settings, used to indent inserted code
 pub fn provide_code_actions(
     uri: &Url,
     range: Range,
     diagnostics: &[Diagnostic],
     text: &str,
+    format_config: &LspFormatConfig,
 ) -> Vec<CodeActionOrCommand> {
     let mut actions = Vec::new();
+    let indent = format_config.indent_unit();
     let end_position = calculate_end_position(text);
 
     // Quick fixes based on diagnostics
@@ -45,21 +53,56 @@ pub fn provide_code_actions(
                         actions.push(fix);
                     }
                 }
+                "E501" => {
+                    // Imported file not found - offer to create it
+                    if let Some(fix) = create_missing_file_fix(uri, diagnostic) {
+                        actions.push(fix);
+                    }
+                }
                 "E504" => {
                     // Symbol not exported - offer to use wildcard import or suggest available exports
                     if let Some(fix) = create_symbol_export_fix(uri, diagnostic) {
                         actions.push(fix);
                     }
                 }
-                "E000" => {
-                    // Generic Error (legacy fallback for namespace issues)
-                    // TODO: Remove this once all namespace errors use E500+
-                    if diagnostic.message.to_lowercase().contains("module")
-                        && diagnostic.message.to_lowercase().contains("resolved")
+                "W010" => {
+                    // Reference matches declarations in several namespaces - qualify it
+                    actions.extend(create_qualify_reference_fixes(uri, diagnostic, text));
+                }
+                "E007" => {
+                    // Symbol declared more than once - drop identical repeats
+                    if let Some(fix) = create_merge_duplicates_fix(uri, diagnostic, text) {
+                        actions.push(fix);
+                    }
+                }
+                "W900" => {
+                    // Policy references an undefined instance - declare it
+                    if let Some(fix) = create_missing_instance_fix(uri, diagnostic, text) {
+                        actions.push(fix);
+                    }
+                }
+                "W800" => {
+                    // Mixed indentation - reformat with the configured indent
+                    if let Some(fix) =
+                        create_fix_indentation_action(uri, diagnostic, text, format_config)
                     {
-                        if let Some(fix) = create_missing_import_fix(uri, diagnostic) {
-                            actions.push(fix);
-                        }
+                        actions.push(fix);
+                    }
+                }
+                "W500" => {
+                    // Instance missing required fields - scaffold them
+                    if let Some(fix) = create_missing_fields_fix(uri, diagnostic, text, &indent) {
+                        actions.push(fix);
+                    }
+                }
+                // Generic Error (legacy fallback for namespace issues)
+                // TODO: Remove this once all namespace errors use E500+
+                "E000"
+                    if diagnostic.message.to_lowercase().contains("module")
+                        && diagnostic.message.to_lowercase().contains("resolved") =>
+                {
+                    if let Some(fix) = create_missing_import_fix(uri, diagnostic) {
+                        actions.push(fix);
                     }
                 }
                 _ => {}
@@ -68,7 +111,7 @@ pub fn provide_code_actions(
     }
 
     // Refactoring actions based on selection
-    actions.extend(provide_refactoring_actions(uri, range, text));
+    actions.extend(provide_refactoring_actions(uri, range, text, &indent));
 
     actions
 }
@@ -77,10 +120,13 @@ pub fn provide_code_actions(
 ///
 /// These are not diagnostic-based fixes, but refactoring operations triggered
 /// when the user selects text.
+///
+/// `indent` is one indent level of inserted code.
 pub fn provide_refactoring_actions(
     uri: &Url,
     range: Range,
     text: &str,
+    indent: &str,
 ) -> Vec<CodeActionOrCommand> {
     let mut actions = Vec::new();
 
@@ -89,6 +135,26 @@ pub fn provide_refactoring_actions(
         actions.push(action);
     }
 
+    // Check for a single-line instance body to expand
+    if let Some(action) = create_expand_instance_body_action(uri, range, text, indent) {
+        actions.push(action);
+    }
+
+    // Check for a declaration that could name the file namespace explicitly
+    if let Some(action) = create_add_in_clause_action(uri, range, text) {
+        actions.push(action);
+    }
+
+    // Check for several namespace-less declarations to move into a namespace
+    if let Some(action) = create_apply_namespace_action(uri, range, text) {
+        actions.push(action);
+    }
+
+    // Check for an entity definition to instantiate
+    if let Some(action) = create_instance_from_entity_action(uri, range, text, indent) {
+        actions.push(action);
+    }
+
     actions
 }
 
@@ -335,6 +401,78 @@ fn create_namespace_import_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<Cod
     }))
 }
 
+/// Create a Quick Fix for E501: Imported file not found.
+/// Creates the file next to the importing document with a `@namespace` header named
+/// after the file.
+fn create_missing_file_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeActionOrCommand> {
+    // Message format: "Imported file not found: <path>"
+    let path = diagnostic
+        .message
+        .strip_prefix("Imported file not found: ")?
+        .trim();
+    let target = crate::workspace::import_target(uri, path)?;
+    let file_name = target.path_segments()?.next_back()?.to_string();
+    let stem = file_name.split('.').next().unwrap_or(&file_name);
+    let namespace = stem.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_");
+
+    let create = DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+        uri: target.clone(),
+        options: Some(CreateFileOptions {
+            overwrite: Some(false),
+            ignore_if_exists: Some(true),
+        }),
+        annotation_id: None,
+    }));
+    let header = DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier {
+            uri: target,
+            version: None,
+        },
+        edits: vec![OneOf::Left(TextEdit {
+            range: Range::default(),
+            new_text: format!("@namespace \"{}\"\n", namespace),
+        })],
+    });
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Create file '{}'", file_name),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(vec![create, header])),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    }))
+}
+
+/// Create a Quick Fix for W800: Mixed indentation.
+/// Applies the document formatter's edits, which re-indent with the configured style.
+fn create_fix_indentation_action(
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    text: &str,
+    format_config: &LspFormatConfig,
+) -> Option<CodeActionOrCommand> {
+    let edits = crate::formatting::format_document(text, Some(format_config.clone()));
+    if edits.is_empty() {
+        return None;
+    }
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Fix indentation".to_string(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(vec![(uri.clone(), edits)].into_iter().collect()),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    }))
+}
+
 /// Create a Quick Fix for E504: Symbol not exported.
 /// Suggests using a wildcard import or lists available exports.
 fn create_symbol_export_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeActionOrCommand> {
@@ -387,6 +525,736 @@ fn create_symbol_export_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeAc
     }))
 }
 
+/// Create a Quick Fix for W500: insert the missing fields into the instance body.
+///
+/// Field names come from the diagnostic's `data.missingFields`, falling back to the
+/// list at the end of the message. Each field gets an empty string placeholder.
+fn create_missing_fields_fix(
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    text: &str,
+    indent: &str,
+) -> Option<CodeActionOrCommand> {
+    let fields: Vec<String> = match diagnostic
+        .data
+        .as_ref()
+        .and_then(|data| data.get("missingFields"))
+        .and_then(|fields| fields.as_array())
+    {
+        Some(fields) => fields
+            .iter()
+            .filter_map(|f| f.as_str().map(str::to_string))
+            .collect(),
+        None => diagnostic
+            .message
+            .rsplit_once(": ")?
+            .1
+            .split(", ")
+            .map(str::to_string)
+            .collect(),
+    };
+    if fields.is_empty() {
+        return None;
+    }
+
+    let line_index = LineIndex::new(text);
+    let decl = line_index.offset_of(diagnostic.range.start)?;
+    let line_end = text[decl..].find('\n').map_or(text.len(), |i| decl + i);
+    let open = decl + text[decl..line_end].find('{')?;
+    let close = entity_fields::instance_body_at(text, open + 1)?.close?;
+    let decl_indent = line_indent(text, open);
+    let placeholders = fields.iter().map(|field| format!("{}: \"\"", field));
+
+    let (start, end, new_text) = if text[open..close].contains('\n') {
+        let field_indent = text[open + 1..close]
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+            .unwrap_or_else(|| format!("{}{}", decl_indent, indent));
+        let close_line = text[..close].rfind('\n').map_or(0, |i| i + 1);
+        let lines: String = placeholders
+            .map(|p| format!("{}{}\n", field_indent, p))
+            .collect();
+        if text[close_line..close].trim().is_empty() {
+            (close_line, close_line, lines)
+        } else {
+            // `}` shares a line with the last field; move it onto its own line.
+            (close, close, format!("\n{}{}", lines, decl_indent))
+        }
+    } else {
+        // Single-line body: expand it, keeping any fields already written.
+        let placeholders: Vec<String> = placeholders.collect();
+        (
+            open,
+            close + 1,
+            expand_single_line_body(text, open, close, indent, &placeholders),
+        )
+    };
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Add missing fields: {}", fields.join(", ")),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: Range {
+                            start: line_index.position_of(start),
+                            end: line_index.position_of(end),
+                        },
+                        new_text,
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    }))
+}
+
+/// Offer ` in <namespace>` for a namespace-less declaration on the range's start line,
+/// when the file declares `@namespace` with a plain identifier value.
+fn create_add_in_clause_action(uri: &Url, range: Range, text: &str) -> Option<CodeActionOrCommand> {
+    let index = SemanticIndex::build(text);
+    let namespace = index
+        .annotations
+        .iter()
+        .find(|a| a.key == "namespace")
+        .map(|a| a.value.as_str())?;
+    if !is_plain_identifier(namespace) {
+        return None;
+    }
+
+    let line_index = LineIndex::new(text);
+    let offset = line_index.offset_of(Position {
+        line: range.start.line,
+        character: 0,
+    })?;
+    let line_end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+
+    // `in` ends these declarations, so the clause can be appended to them. Spans may
+    // run into trailing whitespace, so they are trimmed before matching the line.
+    let trimmed_end = |start: usize, end: usize| start + text[start..end].trim_end().len();
+    let decl = index.declarations.iter().find(|decl| {
+        matches!(
+            decl.kind,
+            SymbolKind::Entity | SymbolKind::Resource | SymbolKind::Role
+        ) && decl.range.start <= line_end
+            && offset <= trimmed_end(decl.range.start, decl.range.end)
+    })?;
+    let has_in_clause = index.namespaces.iter().any(|occ| {
+        occ.role == NamespaceRole::InClause
+            && decl.range.start <= occ.range.start
+            && occ.range.end <= decl.range.end
+    });
+    if has_in_clause {
+        return None;
+    }
+
+    let insert_at = line_index.position_of(trimmed_end(decl.range.start, decl.range.end));
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!(
+            "Add `in {}` to {:?} \"{}\"",
+            namespace, decl.kind, decl.name
+        ),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: Range {
+                            start: insert_at,
+                            end: insert_at,
+                        },
+                        new_text: format!(" in {}", namespace),
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }),
+        is_preferred: Some(false),
+        ..Default::default()
+    }))
+}
+
+/// Whether `name` can follow `in` unquoted: an ASCII identifier.
+fn is_plain_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `workspace/executeCommand` name moving the selected declarations into a namespace;
+/// arguments are the document URI, the selected range and the namespace.
+pub const APPLY_NAMESPACE_COMMAND: &str = "domainforge.applyNamespace";
+
+/// Offer "Move N declarations to namespace 'ns'" when the selection spans two or
+/// more entity, resource or role declarations without an `in` clause.
+///
+/// The action runs [`APPLY_NAMESPACE_COMMAND`] so the client may prompt for another
+/// namespace; the default is the file's `@namespace`, or one named after the file
+/// when it has no header. It is only offered when the default changes something.
+fn create_apply_namespace_action(
+    uri: &Url,
+    range: Range,
+    text: &str,
+) -> Option<CodeActionOrCommand> {
+    let index = SemanticIndex::build(text);
+    let namespace = match file_namespace(&index) {
+        Some(namespace) => namespace,
+        None => {
+            let file_name = uri.path_segments()?.next_back()?;
+            let stem = file_name.split('.').next().unwrap_or(file_name);
+            stem.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_")
+        }
+    };
+    apply_namespace_edits(&index, range, text, &namespace)?;
+    let count = namespace_less_declarations(&index, &LineIndex::new(text), range).len();
+
+    let title = format!("Move {} declarations to namespace '{}'", count, namespace);
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        command: Some(Command {
+            title,
+            command: APPLY_NAMESPACE_COMMAND.to_string(),
+            arguments: Some(vec![
+                serde_json::json!(uri),
+                serde_json::json!(range),
+                serde_json::json!(namespace),
+            ]),
+        }),
+        is_preferred: Some(false),
+        ..Default::default()
+    }))
+}
+
+/// Edit moving the namespace-less declarations the selection touches into
+/// `namespace`, or `None` when there are fewer than two or nothing would change.
+///
+/// Declarations without `in` already belong to the file's `@namespace`, so with a
+/// matching header there is nothing to do. Without a header, one is inserted when the
+/// selection covers every namespace-less declaration; otherwise each selected
+/// declaration gets its own ` in <ns>` edit.
+pub fn apply_namespace_edit(
+    uri: &Url,
+    range: Range,
+    text: &str,
+    namespace: &str,
+) -> Option<WorkspaceEdit> {
+    let index = SemanticIndex::build(text);
+    let edits = apply_namespace_edits(&index, range, text, namespace)?;
+    Some(WorkspaceEdit {
+        changes: Some(vec![(uri.clone(), edits)].into_iter().collect()),
+        ..Default::default()
+    })
+}
+
+fn apply_namespace_edits(
+    index: &SemanticIndex,
+    range: Range,
+    text: &str,
+    namespace: &str,
+) -> Option<Vec<TextEdit>> {
+    if !is_plain_identifier(namespace) {
+        return None;
+    }
+    let header = file_namespace(index);
+    if header.as_deref() == Some(namespace) {
+        return None;
+    }
+
+    let line_index = LineIndex::new(text);
+    let selected = namespace_less_declarations(index, &line_index, range);
+    if selected.len() < 2 {
+        return None;
+    }
+
+    let everything = Range {
+        start: Position::new(0, 0),
+        end: Position::new(u32::MAX, 0),
+    };
+    if header.is_none()
+        && selected.len() == namespace_less_declarations(index, &line_index, everything).len()
+    {
+        return Some(vec![TextEdit {
+            range: Range::default(),
+            new_text: format!("@namespace \"{}\"\n\n", namespace),
+        }]);
+    }
+
+    let trimmed_end = |start: usize, end: usize| start + text[start..end].trim_end().len();
+    Some(
+        selected
+            .iter()
+            .map(|decl| {
+                let insert_at =
+                    line_index.position_of(trimmed_end(decl.range.start, decl.range.end));
+                TextEdit {
+                    range: Range {
+                        start: insert_at,
+                        end: insert_at,
+                    },
+                    new_text: format!(" in {}", namespace),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// The value of the file's `@namespace` header.
+fn file_namespace(index: &SemanticIndex) -> Option<String> {
+    index
+        .annotations
+        .iter()
+        .find(|a| a.key == "namespace")
+        .map(|a| a.value.clone())
+}
+
+/// Entity, resource and role declarations starting on a line of `range` that have
+/// no `in` clause.
+fn namespace_less_declarations<'a>(
+    index: &'a SemanticIndex,
+    line_index: &LineIndex,
+    range: Range,
+) -> Vec<&'a Declaration> {
+    index
+        .declarations
+        .iter()
+        .filter(|decl| {
+            matches!(
+                decl.kind,
+                SymbolKind::Entity | SymbolKind::Resource | SymbolKind::Role
+            ) && (range.start.line..=range.end.line)
+                .contains(&line_index.position_of(decl.range.start).line)
+                && !index.namespaces.iter().any(|occ| {
+                    occ.role == NamespaceRole::InClause
+                        && decl.range.start <= occ.range.start
+                        && occ.range.end <= decl.range.end
+                })
+        })
+        .collect()
+}
+
+/// Offer "Create instance of 'X'" when the range starts on the name of an `Entity`
+/// definition, appending an instance with an unused identifier at the end of the file.
+///
+/// When other instances of the entity exist, their fields are scaffolded as empty
+/// placeholders.
+fn create_instance_from_entity_action(
+    uri: &Url,
+    range: Range,
+    text: &str,
+    indent: &str,
+) -> Option<CodeActionOrCommand> {
+    let index = SemanticIndex::build(text);
+    let line_index = LineIndex::new(text);
+    let occ = index.symbol_at_offset(line_index.offset_of(range.start)?)?;
+    if occ.kind != SymbolKind::Entity || !occ.is_definition {
+        return None;
+    }
+    let entity = occ.name.as_str();
+
+    let mut stem: String = entity
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !stem.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        stem.insert(0, '_');
+    }
+    let identifier = (1..)
+        .map(|n| format!("{}_{}", stem, n))
+        .find(|id| index.definition_range(SymbolKind::Instance, id).is_none())?;
+
+    let fields = sea_core::parse_to_graph(text)
+        .map(|graph| entity_fields::known_fields(&graph, entity))
+        .unwrap_or_default();
+    let body = if fields.is_empty() {
+        "{}".to_string()
+    } else {
+        let lines: String = fields
+            .iter()
+            .map(|field| format!("{}{}: \"\"\n", indent, field))
+            .collect();
+        format!("{{\n{}}}", lines)
+    };
+
+    let separator = append_separator(text);
+    let end_pos = calculate_end_position(text);
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Create instance of '{}'", entity),
+        kind: Some(CodeActionKind::REFACTOR),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: Range {
+                            start: end_pos,
+                            end: end_pos,
+                        },
+                        new_text: format!(
+                            "{}Instance {} of \"{}\" {}\n",
+                            separator, identifier, entity, body
+                        ),
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }),
+        is_preferred: Some(false),
+        ..Default::default()
+    }))
+}
+
+/// Blank lines needed before a declaration appended to `text`, so it is separated
+/// from the previous one by exactly one empty line.
+fn append_separator(text: &str) -> &'static str {
+    if text.is_empty() || text.ends_with("\n\n") {
+        ""
+    } else if text.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    }
+}
+
+/// Create a quick fix for E007 deleting every declaration of the symbol at the
+/// diagnostic but the first. Offered only when all of them match token for token,
+/// so no body is lost.
+fn create_merge_duplicates_fix(
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    text: &str,
+) -> Option<CodeActionOrCommand> {
+    let line_index = LineIndex::new(text);
+    let offset = line_index.offset_of(diagnostic.range.start)?;
+    let index = SemanticIndex::build(text);
+    let target = index
+        .declarations
+        .iter()
+        .find(|decl| decl.range.contains(offset))?;
+    let duplicates: Vec<_> = index
+        .declarations
+        .iter()
+        .filter(|decl| decl.kind == target.kind && decl.name == target.name)
+        .collect();
+    let (first, rest) = duplicates.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let tokens = |range: ByteRange| declaration_tokens(&text[range.start..range.end]);
+    let original = tokens(first.range);
+    if rest.iter().any(|decl| tokens(decl.range) != original) {
+        return None;
+    }
+
+    let edits = rest
+        .iter()
+        .map(|decl| {
+            // Take the line break after the declaration with it
+            let end = if text[decl.range.end..].starts_with('\n') {
+                decl.range.end + 1
+            } else {
+                decl.range.end
+            };
+            TextEdit {
+                range: Range {
+                    start: line_index.position_of(decl.range.start),
+                    end: line_index.position_of(end),
+                },
+                new_text: String::new(),
+            }
+        })
+        .collect();
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Remove duplicate declarations of '{}'", target.name),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(vec![(uri.clone(), edits)].into_iter().collect()),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    }))
+}
+
+/// `text` split on whitespace, keeping each string literal whole so spacing inside
+/// a literal still tells two declarations apart.
+fn declaration_tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let (mut in_literal, mut escaped) = (false, false);
+    for (i, c) in text.char_indices() {
+        if in_literal {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_literal = false;
+            }
+        } else if c.is_whitespace() {
+            if let Some(start) = start.take() {
+                tokens.push(&text[start..i]);
+            }
+        } else {
+            start.get_or_insert(i);
+            in_literal = c == '"';
+        }
+    }
+    if let Some(start) = start {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Create one "Qualify as 'ns::Name'" quick fix per candidate namespace of an
+/// ambiguous reference, taken from the W010 diagnostic's `data.namespaces`.
+fn create_qualify_reference_fixes(
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    text: &str,
+) -> Vec<CodeActionOrCommand> {
+    let Some(reference) = get_text_at_range(text, diagnostic.range) else {
+        return Vec::new();
+    };
+    let Some(name) = reference
+        .trim()
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+    else {
+        return Vec::new();
+    };
+    let Some(namespaces) = diagnostic
+        .data
+        .as_ref()
+        .and_then(|data| data.get("namespaces"))
+        .and_then(|namespaces| namespaces.as_array())
+    else {
+        return Vec::new();
+    };
+
+    namespaces
+        .iter()
+        .filter_map(|namespace| namespace.as_str())
+        .map(|namespace| {
+            let qualified = format!("{}::{}", namespace, name);
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Qualify as '{}'", qualified),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(
+                        vec![(
+                            uri.clone(),
+                            vec![TextEdit {
+                                range: diagnostic.range,
+                                new_text: format!("\"{}\"", qualified),
+                            }],
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Create a Quick Fix for W900: Policy references an undefined instance.
+/// Appends an empty instance declaration for the `@name` under the diagnostic. The
+/// entity type is filled in when the document declares exactly one entity and left
+/// as `""` otherwise.
+fn create_missing_instance_fix(
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    text: &str,
+) -> Option<CodeActionOrCommand> {
+    let reference = get_text_at_range(text, diagnostic.range)?;
+    let name = reference.trim().strip_prefix('@')?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let index = SemanticIndex::build(text);
+    let mut entities = index
+        .occurrences
+        .iter()
+        .filter(|occ| occ.kind == SymbolKind::Entity && occ.is_definition);
+    let entity = match (entities.next(), entities.next()) {
+        (Some(only), None) => only.name.as_str(),
+        _ => "",
+    };
+    let end_pos = calculate_end_position(text);
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Create instance '@{}'", name),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: Range {
+                            start: end_pos,
+                            end: end_pos,
+                        },
+                        new_text: format!(
+                            "{}Instance {} of \"{}\" {{}}\n",
+                            append_separator(text),
+                            name,
+                            entity
+                        ),
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    }))
+}
+
+/// Create a "Convert to multi-line body" refactoring for `Instance x of "E" { ... }`
+/// written on a single line.
+///
+/// Offered when the selection starts on that line; the declaration itself is left
+/// untouched and each field moves onto its own indented line.
+fn create_expand_instance_body_action(
+    uri: &Url,
+    range: Range,
+    text: &str,
+    indent: &str,
+) -> Option<CodeActionOrCommand> {
+    let line_index = LineIndex::new(text);
+    let line_start = line_index.offset_of(Position {
+        line: range.start.line,
+        character: 0,
+    })?;
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |i| line_start + i);
+    let line = &text[line_start..line_end];
+    if !line
+        .trim_start()
+        .to_ascii_lowercase()
+        .starts_with("instance ")
+    {
+        return None;
+    }
+
+    let open = line_start + line.find('{')?;
+    let close = entity_fields::instance_body_at(text, open + 1)?.close?;
+    if close >= line_end {
+        return None;
+    }
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Convert instance body to multi-line".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: Range {
+                            start: line_index.position_of(open),
+                            end: line_index.position_of(close + 1),
+                        },
+                        new_text: expand_single_line_body(text, open, close, indent, &[]),
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }),
+        is_preferred: Some(false),
+        ..Default::default()
+    }))
+}
+
+/// Rewrite the single-line body between `open` and `close` as one field per line,
+/// followed by `extra` fields. Returns the replacement text for `{ ... }`.
+fn expand_single_line_body(
+    text: &str,
+    open: usize,
+    close: usize,
+    indent: &str,
+    extra: &[String],
+) -> String {
+    let decl_indent = line_indent(text, open);
+    let lines: String = split_fields(&text[open + 1..close])
+        .into_iter()
+        .map(str::to_string)
+        .chain(extra.iter().cloned())
+        .map(|field| format!("{}{}{}\n", decl_indent, indent, field))
+        .collect();
+    format!("{{\n{}{}}}", lines, decl_indent)
+}
+
+/// Split single-line body text on the commas between fields, ignoring commas
+/// inside string literals.
+fn split_fields(body: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    for (idx, c) in body.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                fields.push(&body[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&body[start..]);
+    fields
+        .into_iter()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .collect()
+}
+
+/// Leading whitespace of the line containing `offset`.
+fn line_indent(text: &str, offset: usize) -> &str {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &text[line_start..];
+    &line[..line.len() - line.trim_start().len()]
+}
+
 /// Create an "Extract to Pattern" refactoring action.
 ///
 /// This action is offered when the user selects a string literal that looks like
@@ -717,7 +1585,13 @@ mod tests {
         let text = "Instance x of \"MyEntity\"";
 
         // Mock end position calc
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         assert_eq!(actions.len(), 1);
         match &actions[0] {
@@ -741,7 +1615,13 @@ mod tests {
         let diag = create_diagnostic("E002", "Undefined resource: MyRes");
         let text = "Flow \"MyRes\" from A to B";
 
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         assert_eq!(actions.len(), 1);
         match &actions[0] {
@@ -762,7 +1642,13 @@ mod tests {
         let diag = create_diagnostic("E005", "Syntax error...");
         let text = "invalid syntax";
 
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         assert!(actions.is_empty());
     }
@@ -774,7 +1660,13 @@ mod tests {
         let diag = create_diagnostic("E000", "Module 'com.example' could not be resolved");
         let text = "import 'com.example'";
 
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         assert_eq!(actions.len(), 1);
         match &actions[0] {
@@ -800,7 +1692,13 @@ mod tests {
         // L2\n -> line 2 start
         // L3 -> line 2 end
 
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         match &actions[0] {
             CodeActionOrCommand::CodeAction(action) => {
@@ -887,7 +1785,7 @@ Policy "CheckUser" when user.valid"#;
             },
         };
 
-        let actions = provide_refactoring_actions(&uri, range, text);
+        let actions = provide_refactoring_actions(&uri, range, text, "    ");
 
         assert_eq!(actions.len(), 1);
         match &actions[0] {
@@ -920,10 +1818,13 @@ Policy "CheckUser" when user.valid"#;
             },
         };
 
-        let actions = provide_refactoring_actions(&uri, range, text);
+        let actions = provide_refactoring_actions(&uri, range, text, "    ");
 
         // Should not offer Extract to Pattern for plain strings
-        assert!(actions.is_empty());
+        assert!(!actions.iter().any(|action| matches!(
+            action,
+            CodeActionOrCommand::CodeAction(a) if a.title.starts_with("Extract")
+        )));
     }
 
     #[test]
@@ -932,7 +1833,13 @@ Policy "CheckUser" when user.valid"#;
         let diag = create_diagnostic("E500", "Namespace 'com.example' not found");
         let text = "import com.example";
 
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         assert_eq!(actions.len(), 1);
         match &actions[0] {
@@ -944,6 +1851,252 @@ Policy "CheckUser" when user.valid"#;
         }
     }
 
+    #[test]
+    fn test_e501_code_action_creates_the_imported_file() {
+        let uri = Url::parse("file:///project/models/main.sea").unwrap();
+        let diag = create_diagnostic("E501", "Imported file not found: ../shared/depots.sea");
+        let text = "import * as depots from \"../shared/depots.sea\"\n";
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected CodeAction");
+        };
+        assert_eq!(action.title, "Create file 'depots.sea'");
+        let Some(DocumentChanges::Operations(operations)) = action
+            .edit
+            .as_ref()
+            .and_then(|edit| edit.document_changes.as_ref())
+        else {
+            panic!("Expected document change operations");
+        };
+        let target = Url::parse("file:///project/shared/depots.sea").unwrap();
+        assert!(matches!(
+            &operations[0],
+            DocumentChangeOperation::Op(ResourceOp::Create(create)) if create.uri == target
+        ));
+        match &operations[1] {
+            DocumentChangeOperation::Edit(edit) => {
+                assert_eq!(edit.text_document.uri, target);
+                assert_eq!(
+                    edit.edits,
+                    vec![OneOf::Left(TextEdit {
+                        range: Range::default(),
+                        new_text: "@namespace \"depots\"\n".to_string(),
+                    })]
+                );
+            }
+            other => panic!("Expected header edit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_w800_code_action_formats_the_document() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Instance depot of \"Warehouse\" {\n    name: \"North\",\n\tcity: \"Oslo\"\n}\n";
+        let diagnostics = crate::diagnostics::mixed_indentation_diagnostics(text, false);
+        assert_eq!(diagnostics.len(), 1);
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &diagnostics,
+            text,
+            &LspFormatConfig::default(),
+        );
+
+        let fix = actions
+            .iter()
+            .find_map(|action| match action {
+                CodeActionOrCommand::CodeAction(a) if a.title == "Fix indentation" => Some(a),
+                _ => None,
+            })
+            .expect("Fix indentation action");
+        let edits = &fix.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(
+            edits,
+            &crate::formatting::format_document(text, Some(LspFormatConfig::default()))
+        );
+        assert!(!edits[0].new_text.contains('\t'));
+    }
+
+    #[test]
+    fn test_w900_code_action_declares_the_missing_instance() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Entity \"Warehouse\"\n\nPolicy stocked as: @depot_1 = @depot_1\n";
+        let undefined = |text: &str| {
+            crate::diagnostics::undefined_instance_diagnostics(
+                &sea_core::parse_to_graph(text).unwrap(),
+                &SemanticIndex::build(text),
+                &LineIndex::new(text),
+            )
+        };
+        let diagnostics = undefined(text);
+        assert_eq!(diagnostics.len(), 2, "one per reference: {:?}", diagnostics);
+        let column = "Policy stocked as: ".len() as u32;
+        assert_eq!(diagnostics[0].range.start, Position::new(2, column));
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &diagnostics[..1],
+            text,
+            &LspFormatConfig::default(),
+        );
+
+        let fix = actions
+            .iter()
+            .find_map(|action| match action {
+                CodeActionOrCommand::CodeAction(a) if a.title == "Create instance '@depot_1'" => {
+                    Some(a)
+                }
+                _ => None,
+            })
+            .expect("Create instance action");
+        let edit = &fix.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri][0];
+        assert_eq!(edit.new_text, "\nInstance depot_1 of \"Warehouse\" {}\n");
+
+        let fixed = format!("{}{}", text, edit.new_text);
+        let index = SemanticIndex::build(&fixed);
+        assert!(index
+            .definition_range(SymbolKind::Instance, "depot_1")
+            .is_some());
+        assert!(undefined(&fixed).is_empty());
+    }
+
+    #[test]
+    fn test_w010_code_actions_qualify_with_each_namespace() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = r#"Entity "Warehouse" in logistics
+Entity "Warehouse" in staging
+Entity "Factory"
+Resource "Cameras" units
+
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+"#;
+        let graph = sea_core::parse_to_graph(text).unwrap();
+        let diagnostics = crate::diagnostics::ambiguous_reference_diagnostics(
+            &uri,
+            &graph,
+            &SemanticIndex::build(text),
+            &crate::line_index::LineIndex::new(text),
+        );
+        assert_eq!(diagnostics.len(), 1);
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &diagnostics,
+            text,
+            &LspFormatConfig::default(),
+        );
+
+        let fixes: Vec<(&str, &str)> = actions
+            .iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(a) if a.title.starts_with("Qualify as") => {
+                    let edit = &a.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri][0];
+                    assert_eq!(edit.range, diagnostics[0].range);
+                    Some((a.title.as_str(), edit.new_text.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            fixes,
+            vec![
+                (
+                    "Qualify as 'logistics::Warehouse'",
+                    "\"logistics::Warehouse\""
+                ),
+                ("Qualify as 'staging::Warehouse'", "\"staging::Warehouse\""),
+            ]
+        );
+
+        // The rewrite is the qualified form the index records a namespace for
+        let qualified = text.replace("\"Warehouse\" to", &format!("{} to", fixes[0].1));
+        let index = SemanticIndex::build(&qualified);
+        assert!(index
+            .namespaces
+            .iter()
+            .any(|occ| { occ.role == NamespaceRole::Qualifier && occ.namespace == "logistics" }));
+        assert!(index
+            .occurrences
+            .iter()
+            .any(|occ| occ.kind == SymbolKind::Entity && occ.name == "logistics::Warehouse"));
+    }
+
+    #[test]
+    fn test_e007_code_action_removes_identical_duplicates() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Entity \"Warehouse\"\nEntity \"Factory\"\nEntity  \"Warehouse\"\n";
+        let diagnostics = crate::diagnostics::collect_parse_diagnostics(text);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("E007".to_string()))
+        );
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &diagnostics,
+            text,
+            &LspFormatConfig::default(),
+        );
+        let fix = actions
+            .iter()
+            .find_map(|action| match action {
+                CodeActionOrCommand::CodeAction(a) if a.title.starts_with("Remove duplicate") => {
+                    Some(a)
+                }
+                _ => None,
+            })
+            .expect("Remove duplicate declarations action");
+        let edits = &fix.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start, Position::new(2, 0));
+        assert_eq!(edits[0].range.end, Position::new(3, 0));
+        assert!(edits[0].new_text.is_empty());
+    }
+
+    #[test]
+    fn test_e007_code_action_keeps_duplicates_that_differ() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        // A different body, or different spacing inside a literal, would be lost
+        for text in [
+            "Entity \"Depot\" @replaces \"Store\"\nEntity \"Depot\" @replaces \"Shed\"\n",
+            "Entity \"Depot\" @replaces \"Old Depot\"\nEntity \"Depot\" @replaces \"Old  Depot\"\n",
+        ] {
+            let diagnostics = crate::diagnostics::collect_parse_diagnostics(text);
+            assert_eq!(
+                diagnostics[0].code,
+                Some(NumberOrString::String("E007".to_string()))
+            );
+
+            let actions = provide_code_actions(
+                &uri,
+                Range::default(),
+                &diagnostics,
+                text,
+                &LspFormatConfig::default(),
+            );
+            assert!(
+                !actions.iter().any(|action| matches!(
+                    action,
+                    CodeActionOrCommand::CodeAction(a) if a.title.starts_with("Remove duplicate")
+                )),
+                "{text}"
+            );
+        }
+    }
+
     #[test]
     fn test_e504_code_action() {
         let uri = Url::parse("file:///test.sea").unwrap();
@@ -953,7 +2106,13 @@ Policy "CheckUser" when user.valid"#;
         );
         let text = "import { Foo } from com.example";
 
-        let actions = provide_code_actions(&uri, Range::default(), &[diag], text);
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
 
         assert_eq!(actions.len(), 1);
         match &actions[0] {
@@ -964,4 +2123,244 @@ Policy "CheckUser" when user.valid"#;
             _ => panic!("Expected CodeAction"),
         }
     }
+
+    fn apply_single_edit(text: &str, action: &CodeActionOrCommand, uri: &Url) -> String {
+        let CodeActionOrCommand::CodeAction(action) = action else {
+            panic!("Expected CodeAction");
+        };
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[uri];
+        assert_eq!(edits.len(), 1);
+        let line_index = LineIndex::new(text);
+        let start = line_index.offset_of(edits[0].range.start).unwrap();
+        let end = line_index.offset_of(edits[0].range.end).unwrap();
+        format!("{}{}{}", &text[..start], edits[0].new_text, &text[end..])
+    }
+
+    fn missing_fields_diagnostic(text: &str, name: &str, fields: &[&str]) -> Diagnostic {
+        let line_index = LineIndex::new(text);
+        let start = text.find(name).unwrap();
+        let mut diag = create_diagnostic(
+            "W500",
+            &format!(
+                "Instance '{name}' is missing fields required by Vendor: {}",
+                fields.join(", ")
+            ),
+        );
+        diag.range = Range {
+            start: line_index.position_of(start),
+            end: line_index.position_of(start + name.len()),
+        };
+        diag.data = Some(serde_json::json!({ "missingFields": fields }));
+        diag
+    }
+
+    #[test]
+    fn test_w500_fix_inserts_fields_with_body_indentation() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "  Instance globex of \"Vendor\" {\n      name: \"Globex\"\n  }\n";
+        let diag = missing_fields_diagnostic(text, "globex", &["region", "tier"]);
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
+        assert_eq!(actions.len(), 1);
+        assert_eq!(
+            apply_single_edit(text, &actions[0], &uri),
+            "  Instance globex of \"Vendor\" {\n      name: \"Globex\"\n      region: \"\"\n      tier: \"\"\n  }\n"
+        );
+    }
+
+    #[test]
+    fn test_w500_fix_expands_single_line_body() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Instance globex of \"Vendor\" {}\n";
+        let diag = missing_fields_diagnostic(text, "globex", &["region"]);
+
+        let actions = provide_code_actions(
+            &uri,
+            Range::default(),
+            &[diag],
+            text,
+            &LspFormatConfig::default(),
+        );
+        assert_eq!(
+            apply_single_edit(text, &actions[0], &uri),
+            "Instance globex of \"Vendor\" {\n    region: \"\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_single_line_instance_body_uses_indent_config() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Entity \"Vendor\"\n\nInstance acme of \"Vendor\" { name: \"Acme, Inc\", region: \"EU\" }\n";
+        let range = Range {
+            start: Position {
+                line: 2,
+                character: 3,
+            },
+            end: Position {
+                line: 2,
+                character: 3,
+            },
+        };
+        let config = LspFormatConfig {
+            indent_width: 2,
+            use_tabs: false,
+        };
+
+        let actions = provide_code_actions(&uri, range, &[], text, &config);
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected CodeAction");
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR_REWRITE));
+        assert_eq!(
+            apply_single_edit(text, &actions[0], &uri),
+            "Entity \"Vendor\"\n\nInstance acme of \"Vendor\" {\n  name: \"Acme, Inc\"\n  region: \"EU\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_instance_body_not_offered_for_multi_line_body() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Instance acme of \"Vendor\" {\n    name: \"Acme\"\n}\n";
+
+        let actions = provide_refactoring_actions(&uri, Range::default(), text, "    ");
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn test_add_in_clause_to_bare_entity_in_namespaced_file() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text =
+            "@namespace \"logistics\"\n\nEntity \"Warehouse\"\nEntity \"Depot\" in logistics\n";
+        let at_line = |line| Range {
+            start: Position { line, character: 2 },
+            end: Position { line, character: 2 },
+        };
+
+        let actions = provide_refactoring_actions(&uri, at_line(2), text, "    ");
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected CodeAction");
+        };
+        assert_eq!(action.title, "Add `in logistics` to Entity \"Warehouse\"");
+        assert_eq!(
+            apply_single_edit(text, &actions[0], &uri),
+            "@namespace \"logistics\"\n\nEntity \"Warehouse\" in logistics\nEntity \"Depot\" in logistics\n"
+        );
+
+        assert!(provide_refactoring_actions(&uri, at_line(3), text, "    ").is_empty());
+        let unnamespaced = "Entity \"Warehouse\"\n";
+        assert!(provide_refactoring_actions(&uri, at_line(0), unnamespaced, "    ").is_empty());
+    }
+
+    #[test]
+    fn test_apply_namespace_to_selected_declarations() {
+        let uri = Url::parse("file:///depots.sea").unwrap();
+        let text = "Entity \"Warehouse\"\nEntity \"Factory\"\nEntity \"Clerk\" in staffing\nEntity \"Port\"\n";
+        let selection = Range {
+            start: Position::new(0, 0),
+            end: Position::new(2, 0),
+        };
+        let apply = |text: &str, range: Range, namespace: &str| {
+            let edit = apply_namespace_edit(&uri, range, text, namespace)?;
+            let edits = &edit.changes.as_ref().unwrap()[&uri];
+            let line_index = LineIndex::new(text);
+            let mut applied = text.to_string();
+            for edit in edits.iter().rev() {
+                let start = line_index.offset_of(edit.range.start).unwrap();
+                let end = line_index.offset_of(edit.range.end).unwrap();
+                applied.replace_range(start..end, &edit.new_text);
+            }
+            Some(applied)
+        };
+
+        let actions = provide_refactoring_actions(&uri, selection, text, "    ");
+        let action = actions
+            .iter()
+            .find_map(|action| match action {
+                CodeActionOrCommand::CodeAction(a) if a.title.starts_with("Move") => Some(a),
+                _ => None,
+            })
+            .expect("apply namespace action");
+        assert_eq!(action.title, "Move 2 declarations to namespace 'depots'");
+        let command = action.command.as_ref().unwrap();
+        assert_eq!(command.command, APPLY_NAMESPACE_COMMAND);
+        assert_eq!(command.arguments.as_ref().unwrap()[2], "depots");
+
+        // "Port" stays outside the selection, so only the selected declarations move
+        assert_eq!(
+            apply(text, selection, "depots").unwrap(),
+            "Entity \"Warehouse\" in depots\nEntity \"Factory\" in depots\nEntity \"Clerk\" in staffing\nEntity \"Port\"\n"
+        );
+        // Covering every namespace-less declaration, the header alone does it
+        let all = Range {
+            start: Position::new(0, 0),
+            end: Position::new(3, 0),
+        };
+        assert_eq!(
+            apply(text, all, "depots").unwrap(),
+            "@namespace \"depots\"\n\nEntity \"Warehouse\"\nEntity \"Factory\"\nEntity \"Clerk\" in staffing\nEntity \"Port\"\n"
+        );
+
+        // With a header the declarations already belong to it; another namespace
+        // arrives as the command argument
+        let headed = format!("@namespace \"depots\"\n\n{}", text);
+        let headed_selection = Range {
+            start: Position::new(2, 0),
+            end: Position::new(4, 0),
+        };
+        assert!(apply(&headed, headed_selection, "depots").is_none());
+        assert!(provide_refactoring_actions(&uri, headed_selection, &headed, "    ")
+            .iter()
+            .all(|action| !matches!(action, CodeActionOrCommand::CodeAction(a) if a.title.starts_with("Move"))));
+        assert_eq!(
+            apply(&headed, headed_selection, "ports").unwrap(),
+            "@namespace \"depots\"\n\nEntity \"Warehouse\" in ports\nEntity \"Factory\" in ports\nEntity \"Clerk\" in staffing\nEntity \"Port\"\n"
+        );
+    }
+
+    #[test]
+    fn test_create_instance_from_entity_definition() {
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let text = "Entity \"Cold Store\"\n\nInstance cold_store_1 of \"Cold Store\" {\n    capacity: 40\n}\n";
+        let on_name = Range {
+            start: Position {
+                line: 0,
+                character: 9,
+            },
+            end: Position {
+                line: 0,
+                character: 9,
+            },
+        };
+
+        let actions = provide_refactoring_actions(&uri, on_name, text, "    ");
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected CodeAction");
+        };
+        assert_eq!(action.title, "Create instance of 'Cold Store'");
+        let edited = apply_single_edit(text, &actions[0], &uri);
+        assert!(
+            edited.ends_with(
+                "}\n\nInstance cold_store_2 of \"Cold Store\" {\n    capacity: \"\"\n}\n"
+            ),
+            "got: {edited}"
+        );
+        let graph = sea_core::parse_to_graph(&edited).expect("generated instance parses");
+        assert!(graph
+            .all_entity_instances()
+            .iter()
+            .any(|i| i.name() == "cold_store_2" && i.entity_type() == "Cold Store"));
+
+        // Not offered away from the definition's name.
+        let on_keyword = Range::default();
+        assert!(provide_refactoring_actions(&uri, on_keyword, text, "    ").is_empty());
+    }
 }
@@ -1,29 +1,84 @@
 use sea_core::Graph;
-use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, CompletionResponse, Position};
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionList, CompletionResponse, Position,
+};
 
+use crate::entity_fields::{self, InstanceBody};
 use crate::line_index::LineIndex;
-use crate::semantic_index::SemanticIndex;
+use crate::semantic_index::{SemanticIndex, SymbolKind};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Default cap on the number of items returned by a single completion request.
+pub const DEFAULT_MAX_ITEMS: usize = 200;
+
+/// Characters that open a context [`detect_context`] completes: a quoted name, an
+/// `@instance` reference, or the name after a keyword such as `Instance`.
+pub const TRIGGER_CHARACTERS: &[&str] = &["\"", "@", " "];
+
+/// Characters that accept the selected item when typed: closing the quoted name.
+/// Only set on items where an existing entity must be named (`of "`, `from "`,
+/// `to "`), since elsewhere the quote may close a new name instead.
+pub const COMMIT_CHARACTERS: &[&str] = &["\""];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum CompletionContext {
     Any,
     EntityName,
     ResourceName,
     InstanceRef,
+    /// The identifier right after the `Instance` keyword.
+    InstanceName,
+    RoleName,
     ImportPrefix,
+    /// A free-form literal, such as a relation predicate, with nothing to suggest.
+    FreeText,
+    /// Field-name position inside an instance body.
+    InstanceField(InstanceBody),
 }
 
+/// Completion items at `position`, sorted and capped at `max_items`.
+///
+/// A capped result is returned as an incomplete list so clients re-query as the
+/// partial word grows instead of filtering a truncated set locally.
 pub fn completion(
     source: &str,
     line_index: &LineIndex,
     position: Position,
     graph: Option<&Graph>,
     index: Option<&SemanticIndex>,
+    max_items: usize,
 ) -> Option<CompletionResponse> {
     let offset = line_index.offset_of(position)?;
-    let ctx = detect_context(source, line_index, offset);
+    let (word_start, partial) = partial_word(source, line_index, offset);
+    let ctx = detect_context(source, line_index, word_start);
 
     let mut items = Vec::new();
+    if let (CompletionContext::InstanceField(body), Some(graph)) = (&ctx, graph) {
+        let present = body.field_names(source);
+        for field in entity_fields::known_fields(graph, &body.entity) {
+            if present.contains(&field.as_str()) {
+                continue;
+            }
+            items.push(CompletionItem {
+                insert_text: Some(format!("{}: ", field)),
+                label: field,
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(format!("Field of {}", body.entity)),
+                ..Default::default()
+            });
+        }
+    }
+
+    if ctx == CompletionContext::InstanceName {
+        let identifier = fresh_instance_identifier(graph, index);
+        items.push(CompletionItem {
+            label: identifier.clone(),
+            kind: Some(CompletionItemKind::VARIABLE),
+            detail: Some("New instance identifier".to_string()),
+            insert_text: Some(identifier),
+            ..Default::default()
+        });
+    }
+
     if let Some(graph) = graph {
         if matches!(ctx, CompletionContext::Any | CompletionContext::EntityName) {
             for entity in graph.all_entities() {
@@ -50,6 +105,17 @@ pub fn completion(
             }
         }
 
+        if ctx == CompletionContext::RoleName {
+            for role in graph.all_roles() {
+                items.push(CompletionItem {
+                    label: role.name().to_string(),
+                    kind: Some(CompletionItemKind::INTERFACE),
+                    detail: Some("Role".to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+
         if matches!(ctx, CompletionContext::Any | CompletionContext::InstanceRef) {
             for inst in graph.all_entity_instances() {
                 items.push(CompletionItem {
@@ -85,18 +151,125 @@ pub fn completion(
             .then_with(|| a.label.cmp(&b.label))
     });
     items.dedup_by(|a, b| a.label == b.label && a.kind == b.kind);
+    for item in &mut items {
+        item.sort_text = Some(sort_text(item));
+        if ctx == CompletionContext::EntityName {
+            item.commit_characters =
+                Some(COMMIT_CHARACTERS.iter().map(|c| c.to_string()).collect());
+        }
+    }
+
+    if !partial.is_empty() {
+        let needle = partial.to_lowercase();
+        items.retain(|item| filter_key(item).to_lowercase().starts_with(&needle));
+        for item in &mut items {
+            item.filter_text = Some(filter_key(item).to_string());
+        }
+    }
+
+    if items.len() > max_items {
+        items.truncate(max_items);
+        return Some(CompletionResponse::List(CompletionList {
+            is_incomplete: true,
+            items,
+        }));
+    }
 
     Some(CompletionResponse::Array(items))
 }
 
-fn kind_rank(kind: Option<CompletionItemKind>) -> u8 {
-    match kind {
-        Some(k) if k == CompletionItemKind::CLASS => 0,
-        Some(k) if k == CompletionItemKind::CONSTANT => 1,
-        Some(k) if k == CompletionItemKind::VARIABLE => 2,
-        Some(k) if k == CompletionItemKind::MODULE => 3,
-        _ => 9,
+/// Whether typing `trigger` at `position` should open completion.
+///
+/// Every trigger does except a space, which only does where a keyword expects a
+/// name next (`Instance `, `import * as `) rather than anywhere between words.
+pub fn accepts_trigger(
+    source: &str,
+    line_index: &LineIndex,
+    position: Position,
+    trigger: &str,
+) -> bool {
+    if trigger != " " {
+        return true;
     }
+    let Some(offset) = line_index.offset_of(position) else {
+        return false;
+    };
+    !matches!(
+        detect_context(source, line_index, offset),
+        CompletionContext::Any | CompletionContext::FreeText
+    )
+}
+
+/// The first `instance_<n>` no instance in the graph or the syntax index uses. The
+/// index covers instances the graph misses while the document does not parse.
+fn fresh_instance_identifier(graph: Option<&Graph>, index: Option<&SemanticIndex>) -> String {
+    let taken = |id: &str| {
+        graph.is_some_and(|graph| graph.get_entity_instance(id).is_some())
+            || index.is_some_and(|index| index.definition_range(SymbolKind::Instance, id).is_some())
+    };
+    (1..)
+        .map(|n| format!("instance_{}", n))
+        .find(|id| !taken(id))
+        .expect("unbounded range always yields an unused identifier")
+}
+
+/// The text a completion item is matched against: its label without the `@` instance sigil.
+fn filter_key(item: &CompletionItem) -> &str {
+    item.label.trim_start_matches('@')
+}
+
+/// Find the partially typed word ending at `offset`.
+///
+/// Inside a string literal the word is everything after the opening quote (names may
+/// contain spaces); otherwise it is the trailing run of identifier characters.
+/// Returns the byte offset where the word starts together with the word itself.
+fn partial_word<'a>(source: &'a str, line_index: &LineIndex, offset: usize) -> (usize, &'a str) {
+    let line = line_index.position_of(offset).line;
+    let line_start = line_index
+        .offset_of(Position { line, character: 0 })
+        .unwrap_or(0);
+    let Some(prefix) = source.get(line_start..offset) else {
+        return (offset, "");
+    };
+
+    let start = if prefix.matches('"').count() % 2 == 1 {
+        prefix.rfind('"').map(|idx| idx + 1).unwrap_or(0)
+    } else {
+        prefix
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+            .last()
+            .map(|(idx, _)| idx)
+            .unwrap_or(prefix.len())
+    };
+    (line_start + start, &prefix[start..])
+}
+
+/// Documented order of completion kinds: declarations first, then references,
+/// imports, fields, and kinds reserved for units, policies, keywords and snippets.
+/// Kinds not listed sort after all of these.
+const KIND_ORDER: &[CompletionItemKind] = &[
+    CompletionItemKind::CLASS,
+    CompletionItemKind::CONSTANT,
+    CompletionItemKind::INTERFACE,
+    CompletionItemKind::VARIABLE,
+    CompletionItemKind::MODULE,
+    CompletionItemKind::FIELD,
+    CompletionItemKind::UNIT,
+    CompletionItemKind::FUNCTION,
+    CompletionItemKind::KEYWORD,
+    CompletionItemKind::SNIPPET,
+];
+
+fn kind_rank(kind: Option<CompletionItemKind>) -> u8 {
+    kind.and_then(|k| KIND_ORDER.iter().position(|ordered| *ordered == k))
+        .unwrap_or(KIND_ORDER.len()) as u8
+}
+
+/// `sort_text` encoding our order, for clients that sort by it instead of list order.
+fn sort_text(item: &CompletionItem) -> String {
+    format!("{:02}_{}", kind_rank(item.kind), filter_key(item))
 }
 
 fn detect_context(source: &str, line_index: &LineIndex, offset: usize) -> CompletionContext {
@@ -110,12 +283,29 @@ fn detect_context(source: &str, line_index: &LineIndex, offset: usize) -> Comple
     };
     let prefix = &source[line_start_offset.min(source.len())..offset.min(source.len())];
     let prefix_trimmed = prefix.trim_end();
-    let lower = prefix_trimmed.to_ascii_lowercase();
 
-    if lower.ends_with("@") {
+    // A field name starts a line (or follows `{` / `,`) inside an instance body.
+    if prefix_trimmed.is_empty() || prefix_trimmed.ends_with(['{', ',']) {
+        if let Some(body) = entity_fields::instance_body_at(source, offset) {
+            return CompletionContext::InstanceField(body);
+        }
+    }
+    // Only an `@` directly before the word being typed starts an instance reference;
+    // `@ ` followed by whitespace does not.
+    if prefix.ends_with('@') {
         return CompletionContext::InstanceRef;
     }
 
+    if let Some(ctx) = relation_slot(source, line_index, offset) {
+        return ctx;
+    }
+
+    let lower = prefix_trimmed.to_ascii_lowercase();
+
+    if lower.trim_start() == "instance" && prefix.len() > prefix_trimmed.len() {
+        return CompletionContext::InstanceName;
+    }
+
     for needle in [" of \"", " from \"", " to \""] {
         if lower.ends_with(needle) {
             return CompletionContext::EntityName;
@@ -131,6 +321,63 @@ fn detect_context(source: &str, line_index: &LineIndex, offset: usize) -> Comple
     CompletionContext::Any
 }
 
+/// Keywords that open a top-level declaration or import.
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "entity", "resource", "flow", "pattern", "role", "relation", "instance", "policy", "import",
+];
+
+/// Context for a string literal starting at `offset` inside a relation declaration.
+///
+/// Relations name roles, never entities: `subject` and `object` take `Role` names and
+/// the optional `via: flow` takes a resource, while `predicate` is free text. The
+/// slot is the keyword before the literal's `:`; any other literal in a relation,
+/// such as its new name, is free text too.
+fn relation_slot(source: &str, line_index: &LineIndex, offset: usize) -> Option<CompletionContext> {
+    let quote_start = offset.checked_sub(1)?;
+    if source.get(quote_start..offset)? != "\"" {
+        return None;
+    }
+    // The enclosing declaration starts on the nearest line opening one; only a
+    // relation there makes this a relation slot.
+    let line = line_index.position_of(offset).line;
+    let (keyword, decl_start) = (0..=line).rev().find_map(|line| {
+        let start = line_index.offset_of(Position { line, character: 0 })?;
+        let text = source.get(start..quote_start)?.lines().next()?;
+        let trimmed = text.trim_start();
+        let word = trimmed
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()?
+            .to_ascii_lowercase();
+        DECLARATION_KEYWORDS
+            .contains(&word.as_str())
+            .then(|| (word, start + text.len() - trimmed.len()))
+    })?;
+    if keyword != "relation" {
+        return None;
+    }
+
+    let prefix = source[decl_start..quote_start].trim_end();
+    // `via: flow "` names the keyword after the colon
+    let (prefix, after_colon) = match prefix.rsplit_once(char::is_whitespace) {
+        Some((rest, word)) if word.eq_ignore_ascii_case("flow") => (rest.trim_end(), "flow"),
+        _ => (prefix, ""),
+    };
+    let keyword = prefix
+        .strip_suffix(':')
+        .and_then(|rest| {
+            rest.trim_end()
+                .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .next()
+        })
+        .map(str::to_ascii_lowercase);
+
+    match (keyword.as_deref(), after_colon) {
+        (Some("subject" | "object"), "") => Some(CompletionContext::RoleName),
+        (Some("via"), "flow") => Some(CompletionContext::ResourceName),
+        _ => Some(CompletionContext::FreeText),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,8 +400,15 @@ Instance vendor_123 of "Vendor"
         let offset = source.rfind("of \"Vendor\"").unwrap() + "of \"".len();
         let position = line_index.position_of(offset);
 
-        let result = completion(source, &line_index, position, Some(&graph), Some(&index))
-            .expect("completion response");
+        let result = completion(
+            source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .expect("completion response");
         let CompletionResponse::Array(items) = result else {
             panic!("expected array response");
         };
@@ -166,6 +420,12 @@ Instance vendor_123 of "Vendor"
             items.iter().any(|i| i.label == "Warehouse"),
             "should suggest entity names"
         );
+        assert!(
+            items
+                .iter()
+                .all(|i| i.commit_characters == Some(vec!["\"".to_string()])),
+            "a closing quote should accept the entity name"
+        );
     }
 
     #[test]
@@ -184,7 +444,15 @@ Flow "Cameras" from "Warehouse" to "Factory"
         let offset = source.find("Flow \"Cameras\"").unwrap() + "Flow \"".len();
         let position = line_index.position_of(offset);
 
-        let result = completion(source, &line_index, position, Some(&graph), Some(&index)).unwrap();
+        let result = completion(
+            source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
         let CompletionResponse::Array(items) = result else {
             panic!("expected array response");
         };
@@ -193,6 +461,10 @@ Flow "Cameras" from "Warehouse" to "Factory"
             items.iter().any(|i| i.label == "Cameras"),
             "should suggest resource names"
         );
+        assert!(
+            items.iter().all(|i| i.commit_characters.is_none()),
+            "only entity slots commit on a closing quote"
+        );
 
         let mut seen = HashSet::new();
         for item in &items {
@@ -203,4 +475,416 @@ Flow "Cameras" from "Warehouse" to "Factory"
             );
         }
     }
+
+    #[test]
+    fn filters_by_typed_prefix() {
+        let valid = r#"
+Entity "Warehouse"
+Entity "WarRoom"
+Entity "Factory"
+"#;
+        let graph = sea_core::parse_to_graph(valid).unwrap();
+        let source = format!("{}\nInstance depot of \"War", valid);
+        let line_index = LineIndex::new(&source);
+        let index = SemanticIndex::build(valid);
+
+        let position = line_index.position_of(source.len());
+        let result = completion(
+            &source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
+        let CompletionResponse::Array(items) = result else {
+            panic!("expected array response");
+        };
+
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["WarRoom", "Warehouse"]);
+        assert!(items
+            .iter()
+            .all(|i| i.filter_text.as_deref() == Some(i.label.as_str())));
+    }
+
+    #[test]
+    fn returns_everything_without_partial_word() {
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Instance depot of "Warehouse"
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+
+        let offset = source.rfind("of \"").unwrap() + "of \"".len();
+        let position = line_index.position_of(offset);
+        let result = completion(
+            source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
+        let CompletionResponse::Array(items) = result else {
+            panic!("expected array response");
+        };
+
+        assert!(items.iter().any(|i| i.label == "Factory"));
+        assert!(items.iter().all(|i| i.filter_text.is_none()));
+    }
+
+    #[test]
+    fn suggests_an_unused_instance_identifier_after_the_keyword() {
+        let source = r#"Entity "Warehouse"
+Instance instance_1 of "Warehouse"
+Instance instance_2 of "Warehouse"
+Instance "#;
+        let valid = source.trim_end_matches("Instance ");
+        let graph = sea_core::parse_to_graph(valid).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let result = completion(
+            source,
+            &line_index,
+            line_index.position_of(source.len()),
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
+        let CompletionResponse::Array(items) = result else {
+            panic!("expected array response");
+        };
+
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["instance_3"]);
+    }
+
+    #[test]
+    fn space_only_triggers_after_keywords_expecting_a_name() {
+        let source = "Entity \"Warehouse\"\nInstance \nEntity ";
+        let line_index = LineIndex::new(source);
+        let after_instance = line_index.position_of(source.find("Instance ").unwrap() + 9);
+        let after_entity = line_index.position_of(source.len());
+
+        assert!(accepts_trigger(source, &line_index, after_instance, " "));
+        assert!(!accepts_trigger(source, &line_index, after_entity, " "));
+        assert!(accepts_trigger(source, &line_index, after_entity, "\""));
+    }
+
+    #[test]
+    fn suggests_known_fields_inside_instance_body() {
+        let valid = r#"
+Entity "Vendor"
+
+Instance acme of "Vendor" {
+  name: "Acme"
+  region: "EU"
+}
+"#;
+        let graph = sea_core::parse_to_graph(valid).unwrap();
+        let index = SemanticIndex::build(valid);
+        let source = format!(
+            "{}\nInstance globex of \"Vendor\" {{\n  name: \"Globex\"\n  ",
+            valid
+        );
+        let line_index = LineIndex::new(&source);
+
+        let position = line_index.position_of(source.len());
+        let result = completion(
+            &source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
+        let CompletionResponse::Array(items) = result else {
+            panic!("expected array response");
+        };
+
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["region"],
+            "name is already present in the body"
+        );
+        assert_eq!(items[0].kind, Some(CompletionItemKind::FIELD));
+        assert_eq!(items[0].insert_text.as_deref(), Some("region: "));
+    }
+
+    fn labels_at_end(source: &str, graph: &Graph, index: &SemanticIndex) -> Vec<String> {
+        let line_index = LineIndex::new(source);
+        let position = line_index.position_of(source.len());
+        let result = completion(
+            source,
+            &line_index,
+            position,
+            Some(graph),
+            Some(index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
+        let CompletionResponse::Array(items) = result else {
+            panic!("expected array response");
+        };
+        items.into_iter().map(|i| i.label).collect()
+    }
+
+    #[test]
+    fn instance_sigil_triggers_only_when_directly_before_cursor() {
+        let valid = r#"
+Entity "Vendor"
+Instance vendor_1 of "Vendor"
+Instance acme of "Vendor"
+"#;
+        let graph = sea_core::parse_to_graph(valid).unwrap();
+        let index = SemanticIndex::build(valid);
+        let policy = |typed: &str| format!("{}\nPolicy \"check\" as: {}", valid, typed);
+
+        assert_eq!(
+            labels_at_end(&policy("@"), &graph, &index),
+            vec!["@acme", "@vendor_1"]
+        );
+        assert_eq!(
+            labels_at_end(&policy("@ven"), &graph, &index),
+            vec!["@vendor_1"]
+        );
+
+        let after_space = labels_at_end(&policy("@ "), &graph, &index);
+        assert!(
+            after_space.contains(&"Vendor".to_string()),
+            "`@ ` is not an instance reference: {after_space:?}"
+        );
+    }
+
+    #[test]
+    fn mixed_kinds_sort_in_documented_order_with_stable_sort_text() {
+        let source = r#"
+import * as shared from "shared.sea"
+
+Entity "Warehouse"
+Resource "Cameras" units
+Instance depot of "Warehouse"
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+
+        let position = line_index.position_of(source.len());
+        let CompletionResponse::Array(items) = completion(
+            source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap() else {
+            panic!("expected array response");
+        };
+
+        let kinds: Vec<_> = items.iter().filter_map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CompletionItemKind::CLASS,
+                CompletionItemKind::CONSTANT,
+                CompletionItemKind::VARIABLE,
+                CompletionItemKind::MODULE,
+            ]
+        );
+        let sort_texts: Vec<&str> = items
+            .iter()
+            .map(|i| i.sort_text.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            sort_texts,
+            vec!["00_Warehouse", "01_Cameras", "03_depot", "04_shared"]
+        );
+        assert!(sort_texts.windows(2).all(|w| w[0] < w[1]));
+        // Roles are declarations too, listed ahead of instances
+        assert_eq!(kind_rank(Some(CompletionItemKind::INTERFACE)), 2);
+    }
+
+    #[test]
+    fn results_over_the_limit_are_capped_and_marked_incomplete() {
+        let source: String = (0..12)
+            .map(|i| format!("Entity \"Site{i:02}\"\n"))
+            .collect();
+        let graph = sea_core::parse_to_graph(&source).unwrap();
+        let line_index = LineIndex::new(&source);
+        let index = SemanticIndex::build(&source);
+        let position = line_index.position_of(source.len());
+
+        let CompletionResponse::List(list) = completion(
+            &source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            5,
+        )
+        .unwrap() else {
+            panic!("expected a list response when truncated");
+        };
+        assert!(list.is_incomplete);
+        assert_eq!(list.items.len(), 5);
+        assert_eq!(list.items[0].label, "Site00");
+
+        let uncapped = completion(
+            &source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            50,
+        );
+        assert!(matches!(uncapped, Some(CompletionResponse::Array(items)) if items.len() == 12));
+    }
+
+    fn relation_slot_labels(partial: &str) -> Vec<(String, Option<CompletionItemKind>)> {
+        let declarations = r#"
+Entity "Customer"
+Role "Buyer"
+Role "Seller"
+Resource "Money" USD
+"#;
+        let graph = sea_core::parse_to_graph(declarations).unwrap();
+        let source = format!("{declarations}\nRelation \"Purchase\"\n  {partial}");
+        let index = SemanticIndex::build(&source);
+        let line_index = LineIndex::new(&source);
+        let position = line_index.position_of(source.len());
+        let result = completion(
+            &source,
+            &line_index,
+            position,
+            Some(&graph),
+            Some(&index),
+            DEFAULT_MAX_ITEMS,
+        )
+        .unwrap();
+        let CompletionResponse::Array(items) = result else {
+            panic!("expected array response");
+        };
+        items.into_iter().map(|i| (i.label, i.kind)).collect()
+    }
+
+    #[test]
+    fn relation_subject_and_object_slots_suggest_roles() {
+        let roles = vec![
+            ("Buyer".to_string(), Some(CompletionItemKind::INTERFACE)),
+            ("Seller".to_string(), Some(CompletionItemKind::INTERFACE)),
+        ];
+        assert_eq!(relation_slot_labels("subject: \""), roles);
+        assert_eq!(
+            relation_slot_labels("subject: \"Buyer\"\n  predicate: \"pays\"\n  object: \""),
+            roles
+        );
+        assert_eq!(
+            relation_slot_labels("subject: \"Buyer\"\n  predicate: \"pays\"\n  object: \"Se"),
+            roles[1..].to_vec()
+        );
+    }
+
+    #[test]
+    fn relation_via_slot_suggests_resources_and_predicate_nothing() {
+        assert_eq!(
+            relation_slot_labels(
+                "subject: \"Buyer\"\n  predicate: \"pays\"\n  object: \"Seller\"\n  via: flow \""
+            ),
+            vec![("Money".to_string(), Some(CompletionItemKind::CONSTANT))]
+        );
+        assert!(relation_slot_labels("subject: \"Buyer\"\n  predicate: \"").is_empty());
+    }
+
+    #[test]
+    fn relation_slots_follow_their_keyword() {
+        let roles = vec![
+            ("Buyer".to_string(), Some(CompletionItemKind::INTERFACE)),
+            ("Seller".to_string(), Some(CompletionItemKind::INTERFACE)),
+        ];
+        // Keywords in any case and spacing, on one line or several
+        assert_eq!(relation_slot_labels("SUBJECT : \""), roles);
+        assert_eq!(
+            relation_slot_labels("subject: \"Buyer\" predicate: \"pays\" object:\""),
+            roles
+        );
+        assert!(relation_slot_labels("subject: \"Buyer\" Predicate: \"").is_empty());
+        assert_eq!(
+            relation_slot_labels(
+                "subject: \"Buyer\" predicate: \"pays\" object: \"Seller\" via:  Flow \""
+            ),
+            vec![("Money".to_string(), Some(CompletionItemKind::CONSTANT))]
+        );
+        // `via:` needs `flow` before its resource
+        assert!(
+            relation_slot_labels("subject: \"Buyer\" predicate: \"p\" object: \"S\" via: \"")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn declarations_after_a_relation_complete_as_usual() {
+        let declarations = r#"
+Entity "Customer"
+Entity "Shop"
+Role "Buyer"
+Role "Seller"
+Resource "Money" USD
+Relation "Purchase"
+  subject: "Buyer"
+  predicate: "pays"
+  object: "Seller"
+"#;
+        let graph = sea_core::parse_to_graph(declarations).unwrap();
+        let labels = |partial: &str| {
+            let source = format!("{declarations}{partial}");
+            let index = SemanticIndex::build(&source);
+            let line_index = LineIndex::new(&source);
+            let position = line_index.position_of(source.len());
+            let Some(CompletionResponse::Array(items)) = completion(
+                &source,
+                &line_index,
+                position,
+                Some(&graph),
+                Some(&index),
+                DEFAULT_MAX_ITEMS,
+            ) else {
+                panic!("expected array response");
+            };
+            items.into_iter().map(|i| i.label).collect::<Vec<_>>()
+        };
+
+        assert_eq!(labels("Flow \""), ["Money"]);
+        assert_eq!(labels("Flow \"Money\" from \""), ["Customer", "Shop"]);
+        assert_eq!(labels("Instance shop_1 of \""), ["Customer", "Shop"]);
+    }
+
+    #[test]
+    fn entities_are_not_offered_after_a_role() {
+        // The grammar has no entity slot: once the subject names a role, the object
+        // does too.
+        for partial in [
+            "subject: \"Buyer\"\n  predicate: \"pays\"\n  object: \"",
+            "subject: \"Buyer\"\n  predicate: \"pays\"\n  object: \"Cu",
+        ] {
+            let labels = relation_slot_labels(partial);
+            assert!(
+                labels
+                    .iter()
+                    .all(|(_, kind)| *kind == Some(CompletionItemKind::INTERFACE)),
+                "{partial:?} offered {labels:?}"
+            );
+            assert!(!labels.iter().any(|(label, _)| label == "Customer"));
+        }
+    }
 }
@@ -3,8 +3,24 @@
 //! This module provides functions to convert sea-core validation errors
 //! into LSP diagnostics that can be displayed in the editor.
 
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use sea_core::parse_to_graph;
 use sea_core::parser::ParseError;
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+use sea_core::Graph;
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, Position, Range, Url,
+};
+
+use crate::entity_fields;
+use crate::hover::symbol_resolver::resource_unit;
+use crate::line_index::LineIndex;
+use crate::semantic_index::{
+    blank_line, ByteRange, ImportPath, NamespaceRole, SemanticIndex, SymbolKind,
+};
+use crate::workspace;
 
 /// Convert a sea-core `ParseError` to an LSP `Diagnostic`.
 ///
@@ -128,6 +144,449 @@ pub fn parse_error_to_diagnostic(error: &ParseError) -> Diagnostic {
     }
 }
 
+/// Warn about instances that omit fields their entity type appears to require (`W500`).
+///
+/// sea-core has no field schema, so "required" is inferred from sibling instances
+/// (see [`entity_fields::required_fields`]) and the check is opt-in through
+/// `validation.inferredRequiredFields`. Only instances whose entity type resolves in
+/// the graph are checked. The missing field names are attached as
+/// `data.missingFields` for the scaffolding quick fix.
+pub fn missing_field_diagnostics(
+    graph: &Graph,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let entities: HashSet<&str> = graph.all_entities().into_iter().map(|e| e.name()).collect();
+
+    for instance in graph.all_entity_instances() {
+        let entity = instance.entity_type();
+        if !entities.contains(entity) {
+            continue;
+        }
+        let Some(def) = index.definition_range(SymbolKind::Instance, instance.name()) else {
+            continue;
+        };
+
+        let missing: Vec<String> = entity_fields::required_fields(graph, entity, instance.name())
+            .into_iter()
+            .filter(|field| !instance.fields().contains_key(field))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+
+        let range = Range {
+            start: line_index.position_of(def.start),
+            end: line_index.position_of(def.end),
+        };
+        let mut diagnostic = warning_diagnostic(
+            range,
+            format!(
+                "Instance '{}' is missing fields required by {}: {}",
+                instance.name(),
+                entity,
+                missing.join(", ")
+            ),
+            "W500".to_string(),
+        );
+        diagnostic.data = Some(json!({ "missingFields": missing }));
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+/// Flag entity and resource references whose name the graph declares in more than
+/// one namespace (`W010`), the same clash hover reports as `ambiguous`.
+///
+/// The candidate namespaces are attached as `data.namespaces`, and each candidate
+/// declared in this document is linked from `related_information`. No quick fix
+/// qualifies the reference yet: sea-core resolves flow endpoints by bare name, so a
+/// `"ns::Name"` rewrite would not parse.
+pub fn ambiguous_reference_diagnostics(
+    uri: &Url,
+    graph: &Graph,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    let to_range = |range: ByteRange| Range {
+        start: line_index.position_of(range.start),
+        end: line_index.position_of(range.end),
+    };
+    let file_namespace = index
+        .namespaces
+        .iter()
+        .find(|occ| occ.role == NamespaceRole::Declaration)
+        .map(|occ| occ.namespace.as_str());
+
+    let mut declared: HashMap<(SymbolKind, &str), BTreeSet<&str>> = HashMap::new();
+    for entity in graph.all_entities() {
+        declared
+            .entry((SymbolKind::Entity, entity.name()))
+            .or_default()
+            .insert(entity.namespace());
+    }
+    for resource in graph.all_resources() {
+        declared
+            .entry((SymbolKind::Resource, resource.name()))
+            .or_default()
+            .insert(resource.namespace());
+    }
+
+    let mut diagnostics = Vec::new();
+
+    for occ in index.occurrences.iter().filter(|occ| !occ.is_definition) {
+        let Some(namespaces) = declared.get(&(occ.kind, occ.name.as_str())) else {
+            continue;
+        };
+        if namespaces.len() < 2 {
+            continue;
+        }
+        let namespaces: Vec<&str> = namespaces.iter().copied().collect();
+
+        let candidates = index
+            .occurrences
+            .iter()
+            .filter(|def| def.is_definition && def.kind == occ.kind && def.name == occ.name)
+            .map(|def| {
+                // The `in` clause of the enclosing declaration, else the file namespace
+                let namespace = index
+                    .declarations
+                    .iter()
+                    .find(|decl| {
+                        decl.range.start <= def.range.start && def.range.end <= decl.range.end
+                    })
+                    .and_then(|decl| {
+                        index.namespaces.iter().find(|ns| {
+                            ns.role == NamespaceRole::InClause
+                                && decl.range.start <= ns.range.start
+                                && ns.range.end <= decl.range.end
+                        })
+                    })
+                    .map(|ns| ns.namespace.as_str())
+                    .or(file_namespace);
+                DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: uri.clone(),
+                        range: to_range(def.range),
+                    },
+                    message: match namespace {
+                        Some(namespace) => format!("Declared in {}", namespace),
+                        None => "Declared here".to_string(),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut diagnostic = info_diagnostic(
+            to_range(occ.range),
+            format!(
+                "'{}' is declared in several namespaces: {}",
+                occ.name,
+                namespaces.join(", ")
+            ),
+            "W010".to_string(),
+        );
+        diagnostic.data = Some(json!({ "namespaces": namespaces }));
+        if !candidates.is_empty() {
+            diagnostic.related_information = Some(candidates);
+        }
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+/// Warn about flows whose inline quantity unit differs from the resource's unit (`W600`).
+///
+/// Flows without an inline unit, or whose resource does not resolve, are skipped.
+pub fn flow_unit_diagnostics(
+    graph: &Graph,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for flow in &index.flows {
+        let Some((unit, unit_range)) = flow.unit.as_ref() else {
+            continue;
+        };
+        let Some(expected) = resource_unit(graph, &flow.resource) else {
+            continue;
+        };
+        if *unit == expected {
+            continue;
+        }
+
+        let range = Range {
+            start: line_index.position_of(unit_range.start),
+            end: line_index.position_of(unit_range.end),
+        };
+        let mut diagnostic = warning_diagnostic(
+            range,
+            format!(
+                "Flow quantity is in '{}' but resource '{}' is measured in '{}'",
+                unit, flow.resource, expected
+            ),
+            "W600".to_string(),
+        );
+        diagnostic.data = Some(json!({ "expectedUnit": expected }));
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+/// Flag resources declared in this document that no flow in the graph moves (`W700`).
+///
+/// Reported as information tagged `UNNECESSARY`, so editors fade the declaration
+/// rather than underlining it.
+pub fn orphaned_resource_diagnostics(
+    graph: &Graph,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    let used: HashSet<String> = graph
+        .all_flows()
+        .into_iter()
+        .map(|flow| flow.resource_id().to_string())
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for res in graph.all_resources() {
+        if used.contains(&res.id().to_string()) {
+            continue;
+        }
+        let Some(def_range) = index.definition_range(SymbolKind::Resource, res.name()) else {
+            continue;
+        };
+
+        let range = Range {
+            start: line_index.position_of(def_range.start),
+            end: line_index.position_of(def_range.end),
+        };
+        let mut diagnostic = info_diagnostic(
+            range,
+            format!("Resource '{}' is not used by any flow", res.name()),
+            "W700".to_string(),
+        );
+        diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+/// Flag roles declared in this document that nothing refers to and no entity plays
+/// (`W710`), tagged `UNNECESSARY` like `W700`.
+pub fn unused_role_diagnostics(
+    graph: &Graph,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    let assigned: HashSet<String> = graph
+        .all_entities()
+        .into_iter()
+        .flat_map(|entity| graph.role_names_for_entity(entity.id()))
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for role in graph.all_roles() {
+        if assigned.contains(role.name())
+            || !index
+                .reference_ranges(SymbolKind::Role, role.name())
+                .is_empty()
+        {
+            continue;
+        }
+        let Some(def_range) = index.definition_range(SymbolKind::Role, role.name()) else {
+            continue;
+        };
+
+        let range = Range {
+            start: line_index.position_of(def_range.start),
+            end: line_index.position_of(def_range.end),
+        };
+        let mut diagnostic = info_diagnostic(
+            range,
+            format!("Role '{}' is never referenced", role.name()),
+            "W710".to_string(),
+        );
+        diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+/// Flag `@instance` references inside policies that no instance declares (`W900`).
+///
+/// sea-core accepts these, so the policy would only fail once evaluated.
+pub fn undefined_instance_diagnostics(
+    graph: &Graph,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Vec<Diagnostic> {
+    let policies: Vec<ByteRange> = index
+        .declarations
+        .iter()
+        .filter(|decl| decl.kind == SymbolKind::Policy)
+        .map(|decl| decl.range)
+        .collect();
+
+    index
+        .occurrences
+        .iter()
+        .filter(|occ| occ.kind == SymbolKind::Instance && !occ.is_definition)
+        .filter(|occ| {
+            policies
+                .iter()
+                .any(|p| p.start <= occ.range.start && occ.range.end <= p.end)
+        })
+        .filter(|occ| graph.get_entity_instance(&occ.name).is_none())
+        .map(|occ| {
+            let range = Range {
+                start: line_index.position_of(occ.range.start),
+                end: line_index.position_of(occ.range.end),
+            };
+            warning_diagnostic(
+                range,
+                format!("Undefined instance '@{}'", occ.name),
+                "W900".to_string(),
+            )
+        })
+        .collect()
+}
+
+/// File imports in the document at `uri` whose target does not exist on disk.
+///
+/// Only path literals that name files (see `workspace::is_file_import`) are checked;
+/// namespace imports are left to sea-core. Documents without a file path have
+/// nothing to resolve against and report none.
+pub fn unresolved_imports<'a>(
+    uri: &Url,
+    index: &'a SemanticIndex,
+    extensions: &[String],
+) -> Vec<&'a ImportPath> {
+    if uri.to_file_path().is_err() {
+        return Vec::new();
+    }
+    index
+        .import_paths
+        .iter()
+        .filter(|import| workspace::is_file_import(&import.path, extensions))
+        .filter(|import| workspace::resolve_import(uri, &import.path, extensions).is_none())
+        .collect()
+}
+
+/// Report file imports that do not resolve to a file (`E501`), at the path literal.
+pub fn unresolved_import_diagnostics(
+    uri: &Url,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+    extensions: &[String],
+) -> Vec<Diagnostic> {
+    unresolved_imports(uri, index, extensions)
+        .into_iter()
+        .map(|import| {
+            let range = Range {
+                start: line_index.position_of(import.range.start),
+                end: line_index.position_of(import.range.end),
+            };
+            error_diagnostic(
+                range,
+                format!("Imported file not found: {}", import.path),
+                "E501".to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Flag lines indented with the character `use_tabs` rules out (`W800`), when the
+/// document indents with both tabs and spaces. Files indented consistently either
+/// way report nothing.
+pub fn mixed_indentation_diagnostics(text: &str, use_tabs: bool) -> Vec<Diagnostic> {
+    let indents: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let width = line.len() - line.trim_start_matches([' ', '\t']).len();
+            (i, &line[..width])
+        })
+        .collect();
+    let uses_tabs = indents.iter().any(|(_, indent)| indent.contains('\t'));
+    let uses_spaces = indents.iter().any(|(_, indent)| indent.contains(' '));
+    if !(uses_tabs && uses_spaces) {
+        return Vec::new();
+    }
+
+    let (wrong, message) = if use_tabs {
+        (
+            ' ',
+            "Line is indented with spaces; this file is configured for tabs",
+        )
+    } else {
+        (
+            '\t',
+            "Line is indented with tabs; this file is configured for spaces",
+        )
+    };
+    indents
+        .into_iter()
+        .filter(|(_, indent)| indent.contains(wrong))
+        .map(|(i, indent)| {
+            let range = Range {
+                start: Position::new(i as u32, 0),
+                end: Position::new(i as u32, indent.len() as u32),
+            };
+            warning_diagnostic(range, message.to_string(), "W800".to_string())
+        })
+        .collect()
+}
+
+/// Upper bound on syntax errors reported in a single validation pass.
+const MAX_SYNTAX_ERRORS: usize = 8;
+
+/// Parse `source` and convert every error found into diagnostics.
+///
+/// `parse_to_graph` stops at the first error. After a syntax error, the offending
+/// line is blanked out (keeping all offsets intact) and the text is parsed again to
+/// find independent errors further down. Errors found after recovery may be fallout
+/// from the first one, so they are reported as warnings.
+pub fn collect_parse_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut patched = source.to_string();
+
+    while diagnostics.len() < MAX_SYNTAX_ERRORS {
+        let error = match parse_to_graph(&patched) {
+            Ok(_) => break,
+            Err(error) => error,
+        };
+
+        let mut diagnostic = parse_error_to_diagnostic(&error);
+        if !diagnostics.is_empty() {
+            diagnostic.severity = Some(DiagnosticSeverity::WARNING);
+            diagnostic.message = format!(
+                "{} (found after recovering from an earlier error)",
+                diagnostic.message
+            );
+        }
+        let line = diagnostic.range.start.line as usize;
+        diagnostics.push(diagnostic);
+
+        // Only syntax errors are line-local; semantic errors would cascade.
+        if !matches!(error, ParseError::SyntaxError { .. }) || !blank_line(&mut patched, line) {
+            break;
+        }
+    }
+
+    diagnostics
+}
+
 /// Convert a sea-core source range to an LSP range.
 ///
 /// **IMPORTANT**: sea-core uses 1-based line/column indexing,
@@ -176,6 +635,18 @@ pub fn error_diagnostic(range: Range, message: String, code: String) -> Diagnost
     }
 }
 
+/// Create an informational diagnostic at the given range.
+pub fn info_diagnostic(range: Range, message: String, code: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        code: Some(NumberOrString::String(code)),
+        source: Some("domainforge".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
 /// Create a warning diagnostic at the given range.
 #[allow(dead_code)]
 pub fn warning_diagnostic(range: Range, message: String, code: String) -> Diagnostic {
@@ -283,4 +754,229 @@ mod tests {
         assert_eq!(diag.severity, Some(DiagnosticSeverity::WARNING));
         assert_eq!(diag.code, Some(NumberOrString::String("W001".to_string())));
     }
+
+    #[test]
+    fn test_reports_independent_syntax_errors() {
+        let source = "Entity Broken\"\nEntity \"Valid\"\nResource Bad\" units\n";
+
+        let diagnostics = collect_parse_diagnostics(source);
+        assert!(
+            diagnostics.len() >= 2,
+            "expected at least two diagnostics, got {diagnostics:?}"
+        );
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(diagnostics[0].range.start.line, 0);
+        assert!(diagnostics[1..]
+            .iter()
+            .all(|d| d.severity == Some(DiagnosticSeverity::WARNING)));
+        assert!(diagnostics.iter().any(|d| d.range.start.line == 2));
+    }
+
+    #[test]
+    fn test_valid_source_has_no_parse_diagnostics() {
+        assert!(collect_parse_diagnostics("Entity \"Valid\"\n").is_empty());
+    }
+
+    #[test]
+    fn test_instance_missing_required_field_warns_w500() {
+        let source = r#"
+Entity "Vendor"
+
+Instance acme of "Vendor" {
+  name: "Acme"
+  region: "EU"
+}
+
+Instance initech of "Vendor" {
+  name: "Initech"
+  region: "US"
+}
+
+Instance globex of "Vendor" {
+  name: "Globex"
+}
+"#;
+        let graph = parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let diagnostics = missing_field_diagnostics(&graph, &index, &line_index);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("W500".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert!(diagnostic.message.contains("region"));
+        let line = source.lines().position(|l| l.contains("globex")).unwrap();
+        assert_eq!(diagnostic.range.start.line as usize, line);
+    }
+
+    #[test]
+    fn test_flow_unit_mismatch_warns_w600() {
+        let source = r#"
+Entity "Farm"
+Entity "Market"
+Resource "Crates" units
+
+Flow "Crates" from "Farm" to "Market" quantity 40 kg
+Flow "Crates" from "Farm" to "Market" quantity 12 units
+Flow "Crates" from "Farm" to "Market" quantity 5
+"#;
+        let graph = parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let diagnostics = flow_unit_diagnostics(&graph, &index, &line_index);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("W600".to_string()))
+        );
+        let line = source.lines().position(|l| l.ends_with("kg")).unwrap();
+        assert_eq!(diagnostic.range.start.line as usize, line);
+        assert!(diagnostic.message.contains("'kg'"));
+        assert!(diagnostic.message.contains("'units'"));
+    }
+
+    #[test]
+    fn test_ambiguous_reference_reports_w010() {
+        let source = r#"
+Entity "Warehouse" in logistics
+Entity "Warehouse" in staging
+Entity "Factory"
+Resource "Cameras" units
+
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+"#;
+        let graph = parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let uri = Url::parse("file:///clash.sea").unwrap();
+        let diagnostics = ambiguous_reference_diagnostics(&uri, &graph, &index, &line_index);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("W010".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::INFORMATION));
+        let line = source.lines().position(|l| l.starts_with("Flow")).unwrap();
+        assert_eq!(diagnostic.range.start.line as usize, line);
+        assert!(diagnostic.message.contains("logistics, staging"));
+
+        let related = diagnostic.related_information.as_ref().unwrap();
+        let candidates: Vec<(u32, &str)> = related
+            .iter()
+            .map(|info| (info.location.range.start.line, info.message.as_str()))
+            .collect();
+        assert_eq!(
+            candidates,
+            vec![(1, "Declared in logistics"), (2, "Declared in staging")]
+        );
+        assert!(related.iter().all(|info| info.location.uri == uri));
+    }
+
+    #[test]
+    fn test_orphaned_resource_reports_w700() {
+        let source = r#"
+Entity "Farm"
+Entity "Market"
+Resource "Crates" units
+Resource "Pallets" units
+
+Flow "Crates" from "Farm" to "Market" quantity 40
+"#;
+        let graph = parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let diagnostics = orphaned_resource_diagnostics(&graph, &index, &line_index);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("W700".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::INFORMATION));
+        assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+        let line = source.lines().position(|l| l.contains("Pallets")).unwrap();
+        assert_eq!(diagnostic.range.start.line as usize, line);
+    }
+
+    #[test]
+    fn test_unused_role_reports_w710() {
+        let source = r#"
+Role "Buyer"
+Role "Seller"
+Role "Auditor"
+
+Relation "Purchase"
+  subject: "Buyer"
+  predicate: "buys from"
+  object: "Seller"
+"#;
+        let graph = parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let diagnostics = unused_role_diagnostics(&graph, &index, &line_index);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("W710".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::INFORMATION));
+        assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+        let line = source.lines().position(|l| l.contains("Auditor")).unwrap();
+        assert_eq!(diagnostic.range.start.line as usize, line);
+    }
+
+    #[test]
+    fn test_mixed_indentation_reports_w800() {
+        let source =
+            "Instance depot of \"Warehouse\" {\n    name: \"North\",\n\tcity: \"Oslo\"\n}\n";
+
+        let diagnostics = mixed_indentation_diagnostics(source, false);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("W800".to_string()))
+        );
+        assert_eq!(diagnostics[0].range.start, Position::new(2, 0));
+        assert_eq!(diagnostics[0].range.end, Position::new(2, 1));
+
+        let with_tabs = mixed_indentation_diagnostics(source, true);
+        assert_eq!(with_tabs.len(), 1);
+        assert_eq!(with_tabs[0].range.start.line, 1);
+
+        let consistent = source.replace('\t', "    ");
+        assert!(mixed_indentation_diagnostics(&consistent, false).is_empty());
+    }
+
+    #[test]
+    fn test_unresolved_file_import_reports_e501() {
+        let dir = tempfile::tempdir().unwrap();
+        let uri = Url::from_file_path(dir.path().join("main.sea")).unwrap();
+        let extensions = [crate::workspace::DEFAULT_FILE_EXTENSION.to_string()];
+        let source = "import * as shared from \"shared.sea\"\nimport { Depot } from \"com.example\"\n\nEntity \"Hub\"\n";
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let diagnostics = unresolved_import_diagnostics(&uri, &index, &line_index, &extensions);
+        assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("E501".to_string()))
+        );
+        let start = line_index.offset_of(diagnostics[0].range.start).unwrap();
+        assert_eq!(&source[start..start + 12], "\"shared.sea\"");
+
+        std::fs::write(dir.path().join("shared.sea"), "Entity \"Depot\"\n").unwrap();
+        assert!(unresolved_import_diagnostics(&uri, &index, &line_index, &extensions).is_empty());
+    }
 }
@@ -0,0 +1,127 @@
+//! Field names of entity instances.
+//!
+//! sea-core does not attach a field schema to entities, so the fields known for an
+//! entity are inferred from the instances that declare it as their type.
+
+use std::collections::BTreeSet;
+
+use sea_core::Graph;
+
+/// Fields used by any instance of `entity`, sorted.
+pub fn known_fields(graph: &Graph, entity: &str) -> BTreeSet<String> {
+    graph
+        .all_entity_instances()
+        .into_iter()
+        .filter(|inst| inst.entity_type() == entity)
+        .flat_map(|inst| inst.fields().keys().cloned().collect::<Vec<_>>())
+        .collect()
+}
+
+/// Minimum number of other instances before their common fields count as required.
+const MIN_INSTANCES_FOR_REQUIRED: usize = 2;
+
+/// Fields that every instance of `entity` other than `instance` sets.
+///
+/// With no declared schema, a field is treated as required once at least
+/// `MIN_INSTANCES_FOR_REQUIRED` sibling instances all provide it.
+pub fn required_fields(graph: &Graph, entity: &str, instance: &str) -> BTreeSet<String> {
+    let siblings: Vec<BTreeSet<String>> = graph
+        .all_entity_instances()
+        .into_iter()
+        .filter(|inst| inst.entity_type() == entity && inst.name() != instance)
+        .map(|inst| inst.fields().keys().cloned().collect())
+        .collect();
+    if siblings.len() < MIN_INSTANCES_FOR_REQUIRED {
+        return BTreeSet::new();
+    }
+
+    let mut iter = siblings.into_iter();
+    let first = iter.next().unwrap_or_default();
+    iter.fold(first, |acc, fields| &acc & &fields)
+}
+
+/// The `{ ... }` body of an `Instance x of "Entity"` declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceBody {
+    /// Entity type named after `of`.
+    pub entity: String,
+    /// Byte offset of the opening `{`.
+    pub open: usize,
+    /// Byte offset of the matching `}`, if the body is closed.
+    pub close: Option<usize>,
+}
+
+impl InstanceBody {
+    /// Field names already written in the body, in source order.
+    pub fn field_names<'a>(&self, source: &'a str) -> Vec<&'a str> {
+        let end = self.close.unwrap_or(source.len());
+        let Some(body) = source.get(self.open + 1..end) else {
+            return Vec::new();
+        };
+        body.split(['\n', ','])
+            .filter_map(|part| part.split_once(':'))
+            .map(|(name, _)| name.trim())
+            .filter(|name| {
+                !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+            })
+            .collect()
+    }
+}
+
+/// Find the instance body enclosing `offset`, if any.
+///
+/// This is a text scan rather than a parse so it also works while the body is being
+/// typed and the document does not parse.
+pub fn instance_body_at(source: &str, offset: usize) -> Option<InstanceBody> {
+    let before = source.get(..offset)?;
+
+    let mut depth = 0usize;
+    let mut open = None;
+    for (idx, c) in before.char_indices().rev() {
+        match c {
+            '}' => depth += 1,
+            '{' if depth == 0 => {
+                open = Some(idx);
+                break;
+            }
+            '{' => depth -= 1,
+            _ => {}
+        }
+    }
+    let open = open?;
+
+    let header_start = before[..open].rfind('\n').map_or(0, |i| i + 1);
+    let entity = instance_header_entity(before[header_start..open].trim())?;
+
+    let mut depth = 0usize;
+    let mut close = None;
+    for (idx, c) in source[open + 1..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => {
+                close = Some(open + 1 + idx);
+                break;
+            }
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    Some(InstanceBody {
+        entity,
+        open,
+        close,
+    })
+}
+
+/// Extract `Entity` from a header of the form `Instance name of "Entity"`.
+fn instance_header_entity(header: &str) -> Option<String> {
+    let lower = header.to_ascii_lowercase();
+    if !lower.starts_with("instance ") {
+        return None;
+    }
+    let of_idx = lower.find(" of \"")?;
+    let rest = &header[of_idx + " of \"".len()..];
+    let end = rest.find('"')?;
+    Some(rest[..end].to_string())
+}
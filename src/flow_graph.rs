@@ -0,0 +1,347 @@
+//! Whole-document views of the flow graph, served through custom requests so agents
+//! can read supply-chain structure without issuing one hover per flow.
+
+use std::collections::{BTreeSet, HashSet};
+
+use sea_core::Graph;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range, TextDocumentIdentifier};
+
+use crate::hover::symbol_resolver::resource_unit;
+use crate::line_index::LineIndex;
+use crate::semantic_index::{SemanticIndex, SymbolKind};
+
+/// Largest `depth` honoured by `sea/neighborhood`; deeper requests are clamped.
+pub const MAX_NEIGHBORHOOD_DEPTH: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// One `Flow` declaration as an edge between two entities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowEdge {
+    pub resource: String,
+    pub from: String,
+    pub to: String,
+    pub quantity: Option<String>,
+    /// Unit symbol of the resource, when the graph resolves it.
+    pub unit: Option<String>,
+    /// Range of the whole flow declaration.
+    pub range: Range,
+}
+
+/// Every flow declared in the document, in source order.
+pub fn flow_edges(
+    line_index: &LineIndex,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> Vec<FlowEdge> {
+    index
+        .flows
+        .iter()
+        .map(|flow| FlowEdge {
+            resource: flow.resource.clone(),
+            from: flow.from_entity.clone(),
+            to: flow.to_entity.clone(),
+            quantity: flow.quantity.clone(),
+            unit: graph.and_then(|graph| resource_unit(graph, &flow.resource)),
+            range: Range {
+                start: line_index.position_of(flow.range.start),
+                end: line_index.position_of(flow.range.end),
+            },
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DotParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Render the document's entities and flows as a GraphViz `digraph`.
+///
+/// Every entity in the graph becomes a node, sorted by name; every flow becomes an
+/// edge labelled with its resource and, when given, its quantity and unit.
+pub fn to_dot(line_index: &LineIndex, index: &SemanticIndex, graph: &Graph) -> String {
+    let mut entities: Vec<String> = graph
+        .all_entities()
+        .into_iter()
+        .map(|e| e.name().to_string())
+        .collect();
+    entities.sort();
+
+    let mut dot = String::from("digraph sea {\n");
+    for entity in &entities {
+        dot.push_str(&format!("  \"{}\";\n", escape_dot(entity)));
+    }
+    for edge in flow_edges(line_index, index, Some(graph)) {
+        let label = match (&edge.quantity, &edge.unit) {
+            (Some(quantity), Some(unit)) => format!("{} ({} {})", edge.resource, quantity, unit),
+            (Some(quantity), None) => format!("{} ({})", edge.resource, quantity),
+            (None, _) => edge.resource.clone(),
+        };
+        dot.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+            escape_dot(&edge.from),
+            escape_dot(&edge.to),
+            escape_dot(&label)
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Escape `value` for use inside a double-quoted DOT identifier.
+fn escape_dot(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeighborhoodParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+    /// Flow hops to follow from the entity; defaults to 1 and is capped at
+    /// `MAX_NEIGHBORHOOD_DEPTH`.
+    #[serde(default)]
+    pub depth: Option<u32>,
+}
+
+/// A flow connecting two entities of a neighborhood.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NeighborhoodFlow {
+    pub resource: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Entities reachable from `entity` within `depth` flow hops, in either direction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighborhood {
+    pub entity: String,
+    /// The depth actually used, after clamping.
+    pub depth: u32,
+    /// Sorted entity names, including `entity` itself.
+    pub entities: Vec<String>,
+    /// Flows traversed while expanding the neighborhood, in discovery order.
+    pub flows: Vec<NeighborhoodFlow>,
+}
+
+/// Expand the flow neighborhood of the entity under `position`.
+///
+/// Returns `None` unless the cursor is on an entity name the graph resolves.
+pub fn neighborhood(
+    line_index: &LineIndex,
+    position: Position,
+    depth: u32,
+    index: &SemanticIndex,
+    graph: &Graph,
+) -> Option<Neighborhood> {
+    let offset = line_index.offset_of(position)?;
+    let occurrence = index.symbol_at_offset(offset)?;
+    if occurrence.kind != SymbolKind::Entity {
+        return None;
+    }
+    let center = graph
+        .all_entities()
+        .into_iter()
+        .find(|e| e.name() == occurrence.name)?;
+
+    let depth = depth.min(MAX_NEIGHBORHOOD_DEPTH);
+    let entity_name = |id| graph.get_entity(id).map(|e| e.name().to_string());
+    let mut entities = BTreeSet::from([center.name().to_string()]);
+    let mut seen_flows = HashSet::new();
+    let mut flows = Vec::new();
+    let mut frontier = vec![center.id().clone()];
+
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for id in &frontier {
+            for flow in graph.flows_from(id).into_iter().chain(graph.flows_to(id)) {
+                if !seen_flows.insert(flow.id().clone()) {
+                    continue;
+                }
+                let (Some(from), Some(to)) =
+                    (entity_name(flow.from_id()), entity_name(flow.to_id()))
+                else {
+                    continue;
+                };
+                let resource = graph
+                    .get_resource(flow.resource_id())
+                    .map(|r| r.name().to_string())
+                    .unwrap_or_default();
+
+                let (other, other_name) = if flow.from_id() == id {
+                    (flow.to_id(), &to)
+                } else {
+                    (flow.from_id(), &from)
+                };
+                if entities.insert(other_name.clone()) {
+                    next.push(other.clone());
+                }
+                flows.push(NeighborhoodFlow { resource, from, to });
+            }
+        }
+        frontier = next;
+    }
+
+    Some(Neighborhood {
+        entity: center.name().to_string(),
+        depth,
+        entities: entities.into_iter().collect(),
+        flows,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatProvidesParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// Entities on either end of the flows that move one resource.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhatProvides {
+    pub resource: String,
+    /// Sorted names of entities a flow of the resource starts `from`.
+    pub producers: Vec<String>,
+    /// Sorted names of entities a flow of the resource goes `to`.
+    pub consumers: Vec<String>,
+}
+
+/// Split the flows of the resource under `position` into producing and consuming
+/// entities. An entity that both emits and receives the resource is in both lists.
+///
+/// Returns `None` unless the cursor is on a resource name the graph resolves.
+pub fn what_provides(
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: &Graph,
+) -> Option<WhatProvides> {
+    let offset = line_index.offset_of(position)?;
+    let occurrence = index.symbol_at_offset(offset)?;
+    if occurrence.kind != SymbolKind::Resource {
+        return None;
+    }
+    let resource = graph
+        .all_resources()
+        .into_iter()
+        .find(|r| r.name() == occurrence.name)?;
+
+    let entity_name = |id| graph.get_entity(id).map(|e| e.name().to_string());
+    let mut producers = BTreeSet::new();
+    let mut consumers = BTreeSet::new();
+    for flow in graph.all_flows() {
+        if graph
+            .get_resource(flow.resource_id())
+            .is_none_or(|r| r.name() != resource.name())
+        {
+            continue;
+        }
+        producers.extend(entity_name(flow.from_id()));
+        consumers.extend(entity_name(flow.to_id()));
+    }
+
+    Some(WhatProvides {
+        resource: resource.name().to_string(),
+        producers: producers.into_iter().collect(),
+        consumers: consumers.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn what_provides_separates_producers_from_consumers() {
+        let source = r#"
+Entity "Farm"
+Entity "Mill"
+Entity "Bakery"
+Entity "Shop"
+Resource "Flour" kg
+Resource "Bread" units
+Flow "Flour" from "Mill" to "Bakery" quantity 50
+Flow "Flour" from "Farm" to "Bakery"
+Flow "Bread" from "Bakery" to "Shop"
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+        let position = line_index.position_of(source.find("\"Flour\"").unwrap() + 2);
+
+        let result = what_provides(&line_index, position, &index, &graph).unwrap();
+        assert_eq!(result.resource, "Flour");
+        assert_eq!(result.producers, vec!["Farm", "Mill"]);
+        assert_eq!(result.consumers, vec!["Bakery"]);
+
+        let on_entity = line_index.position_of(source.find("\"Shop\"").unwrap() + 2);
+        assert!(what_provides(&line_index, on_entity, &index, &graph).is_none());
+    }
+
+    #[test]
+    fn depth_one_returns_only_direct_neighbors() {
+        let source = r#"
+Entity "Supplier"
+Entity "Warehouse"
+Entity "Factory"
+Entity "Store"
+Resource "Cameras" units
+Flow "Cameras" from "Supplier" to "Warehouse"
+Flow "Cameras" from "Warehouse" to "Factory"
+Flow "Cameras" from "Factory" to "Store"
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+        let position = line_index.position_of(source.find("\"Factory\"").unwrap() + 2);
+
+        let result = neighborhood(&line_index, position, 1, &index, &graph).unwrap();
+        assert_eq!(result.entity, "Factory");
+        assert_eq!(result.entities, vec!["Factory", "Store", "Warehouse"]);
+        assert_eq!(result.flows.len(), 2);
+        assert!(result
+            .flows
+            .iter()
+            .all(|f| f.from != "Supplier" && f.resource == "Cameras"));
+
+        let capped = neighborhood(&line_index, position, 99, &index, &graph).unwrap();
+        assert_eq!(capped.depth, MAX_NEIGHBORHOOD_DEPTH);
+        assert_eq!(capped.entities.len(), 4);
+    }
+
+    #[test]
+    fn dot_has_a_node_per_entity_and_an_edge_per_flow() {
+        let source = r#"
+Entity "Farm"
+Entity "Market"
+Entity "Depot \"North\""
+Resource "Crates" units
+Flow "Crates" from "Farm" to "Market" quantity 40
+Flow "Crates" from "Market" to "Depot \"North\""
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let dot = to_dot(&line_index, &index, &graph);
+        assert!(dot.starts_with("digraph sea {\n") && dot.ends_with("}\n"));
+        let nodes = dot.lines().filter(|l| l.ends_with("\";")).count();
+        let edges = dot.lines().filter(|l| l.contains(" -> ")).count();
+        assert_eq!((nodes, edges), (3, 2));
+        assert!(dot.contains(r#"  "Farm" -> "Market" [label="Crates (40 units)"];"#));
+        assert!(dot.contains(r#""Market" -> "Depot \"North\"" [label="Crates"]"#));
+    }
+}
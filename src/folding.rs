@@ -0,0 +1,54 @@
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
+
+use crate::semantic_index::SemanticIndex;
+
+/// Compute folding ranges for a document.
+///
+/// Consecutive `import` statements collapse into a single imports-kind region so a
+/// long header can be hidden. A lone import is not foldable.
+pub fn folding_ranges(index: &SemanticIndex) -> Vec<FoldingRange> {
+    index
+        .import_blocks
+        .iter()
+        .filter(|block| block.end_line > block.start_line)
+        .map(|block| FoldingRange {
+            start_line: block.start_line,
+            start_character: None,
+            end_line: block.end_line,
+            end_character: None,
+            kind: Some(FoldingRangeKind::Imports),
+            collapsed_text: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_consecutive_imports_into_one_region() {
+        let source = r#"import * as logistics from "logistics.sea"
+import * as finance from "finance.sea"
+import { Warehouse } from "warehouse.sea"
+import { Factory as Plant } from "factory.sea"
+
+Entity "Depot"
+"#;
+        let index = SemanticIndex::build(source);
+        let ranges = folding_ranges(&index);
+
+        assert_eq!(ranges.len(), 1, "expected a single import fold: {ranges:?}");
+        let fold = &ranges[0];
+        assert_eq!(fold.start_line, 0);
+        assert_eq!(fold.end_line, 3);
+        assert_eq!(fold.kind, Some(FoldingRangeKind::Imports));
+    }
+
+    #[test]
+    fn single_import_is_not_folded() {
+        let source = "import * as logistics from \"logistics.sea\"\n\nEntity \"Depot\"\n";
+        let index = SemanticIndex::build(source);
+        assert!(folding_ranges(&index).is_empty());
+    }
+}
@@ -3,7 +3,14 @@
 //! This module provides document formatting using sea-core's formatter.
 
 use sea_core::formatter::{format, FormatConfig, IndentStyle};
-use tower_lsp::lsp_types::{Position, Range, TextEdit};
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range, TextEdit, Url};
+
+/// Lines of unchanged context around each hunk of a format check diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Largest line-pair table the diff will build; bigger changes become one hunk.
+const MAX_DIFF_CELLS: usize = 4_000_000;
 
 /// Configuration for formatting, derived from LSP formatting options.
 #[derive(Debug, Clone)]
@@ -23,6 +30,17 @@ impl Default for LspFormatConfig {
     }
 }
 
+impl LspFormatConfig {
+    /// The text of one indent level under this configuration.
+    pub fn indent_unit(&self) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+}
+
 impl From<LspFormatConfig> for FormatConfig {
     fn from(lsp_config: LspFormatConfig) -> Self {
         FormatConfig {
@@ -91,6 +109,168 @@ pub fn format_document(source: &str, config: Option<LspFormatConfig>) -> Vec<Tex
     }
 }
 
+/// Parameters for `sea/formatCheck`: an open or on-disk document, or inline text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatCheckParams {
+    pub uri: Option<Url>,
+    /// Checked instead of the document at `uri` when given.
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatCheckResult {
+    /// Whether `format_document` would leave the text unchanged. Text that fails to
+    /// parse is never formatted.
+    pub formatted: bool,
+    /// Unified diff from the text to its formatted form, when they differ.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Why the text could not be formatted, when it fails to parse.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+}
+
+/// Check whether `source` is already formatted, without applying any edits.
+pub fn format_check(source: &str, config: Option<LspFormatConfig>) -> FormatCheckResult {
+    let format_config: FormatConfig = config.unwrap_or_default().into();
+    match format(source, format_config) {
+        Ok(formatted) if formatted == source => FormatCheckResult {
+            formatted: true,
+            diff: None,
+            parse_error: None,
+        },
+        Ok(formatted) => FormatCheckResult {
+            formatted: false,
+            diff: Some(unified_diff(source, &formatted)),
+            parse_error: None,
+        },
+        Err(e) => FormatCheckResult {
+            formatted: false,
+            diff: None,
+            parse_error: Some(e.to_string()),
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DiffOp {
+    Keep(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Unified diff of `original` against `formatted`, line by line.
+fn unified_diff(original: &str, formatted: &str) -> String {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = formatted.lines().collect();
+    let ops = diff_ops(&old, &new);
+
+    let mut out = String::from("--- original\n+++ formatted\n");
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Keep(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changes {
+        let lo = i.saturating_sub(DIFF_CONTEXT_LINES);
+        let hi = (i + DIFF_CONTEXT_LINES + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if lo <= last.1 => last.1 = hi,
+            _ => hunks.push((lo, hi)),
+        }
+    }
+
+    // Old and new line numbers reached before each op
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut o, mut n) = (0, 0);
+    for op in &ops {
+        positions.push((o, n));
+        match op {
+            DiffOp::Keep(_) => (o, n) = (o + 1, n + 1),
+            DiffOp::Delete(_) => o += 1,
+            DiffOp::Insert(_) => n += 1,
+        }
+    }
+    positions.push((o, n));
+
+    for (lo, hi) in hunks {
+        let (old_start, new_start) = positions[lo];
+        let (old_end, new_end) = positions[hi];
+        let start = |from: usize, len: usize| if len == 0 { from } else { from + 1 };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            start(old_start, old_end - old_start),
+            old_end - old_start,
+            start(new_start, new_end - new_start),
+            new_end - new_start
+        ));
+        for op in &ops[lo..hi] {
+            let (prefix, line) = match *op {
+                DiffOp::Keep(i) => (' ', old[i]),
+                DiffOp::Delete(i) => ('-', old[i]),
+                DiffOp::Insert(j) => ('+', new[j]),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Edit script turning `old` into `new`: a longest-common-subsequence alignment of
+/// the lines between the common prefix and suffix.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(DiffOp::Keep).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        ops.extend((0..old_mid.len()).map(|i| DiffOp::Delete(prefix + i)));
+        ops.extend((0..new_mid.len()).map(|j| DiffOp::Insert(prefix + j)));
+    } else {
+        // lcs[i][j]: common subsequence length of old_mid[i..] and new_mid[j..]
+        let (rows, cols) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0usize; cols + 1]; rows + 1];
+        for i in (0..rows).rev() {
+            for j in (0..cols).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < rows || j < cols {
+            if i < rows && j < cols && old_mid[i] == new_mid[j] {
+                ops.push(DiffOp::Keep(prefix + i));
+                (i, j) = (i + 1, j + 1);
+            } else if j == cols || (i < rows && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                ops.push(DiffOp::Delete(prefix + i));
+                i += 1;
+            } else {
+                ops.push(DiffOp::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    }
+    ops.extend((old.len() - suffix..old.len()).map(DiffOp::Keep));
+    ops
+}
+
 /// Extract formatting configuration from LSP formatting options.
 ///
 /// # Arguments
@@ -201,6 +381,37 @@ Relation "Test"
         }
     }
 
+    #[test]
+    fn test_format_check_reports_a_diff_for_unformatted_text() {
+        let source = "Entity   \"Test\"    in   domain\n";
+        let result = format_check(source, None);
+
+        assert!(!result.formatted);
+        let diff = result.diff.expect("diff for unformatted text");
+        assert!(diff.starts_with("--- original\n+++ formatted\n@@ -1,1 "));
+        assert!(diff.contains("\n-Entity   \"Test\"    in   domain\n"));
+        assert!(diff.contains("\n+Entity \"Test\""));
+
+        let formatted = format_document(source, None).remove(0).new_text;
+        assert_eq!(
+            format_check(&formatted, None),
+            FormatCheckResult {
+                formatted: true,
+                diff: None,
+                parse_error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_format_check_does_not_pass_unparsable_text() {
+        let result = format_check("Entity \"Test\" in\n", None);
+
+        assert!(!result.formatted);
+        assert_eq!(result.diff, None);
+        assert!(result.parse_error.is_some_and(|e| !e.is_empty()));
+    }
+
     #[test]
     fn test_extract_format_options() {
         use tower_lsp::lsp_types::FormattingOptions;
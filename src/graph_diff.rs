@@ -0,0 +1,258 @@
+//! Semantic difference between two versions of a document for `sea/graphDiff`.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use sea_core::Graph;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::Url;
+
+use crate::semantic_index::SemanticIndex;
+
+/// Parameters for `sea/graphDiff`: `proposed` compared with `base`, or with the open
+/// document at `uri` when `base` is not given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphDiffParams {
+    pub uri: Option<Url>,
+    pub base: Option<String>,
+    pub proposed: String,
+}
+
+/// Qualified names that appear only in one version, or in both with different details.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffSection {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    /// Keyed by `namespace::Name`; changed when the `@replaces` target differs.
+    pub entities: DiffSection,
+    /// Keyed by `namespace::Name`; changed when the unit differs.
+    pub resources: DiffSection,
+    /// Keyed by `From -> To (Resource)` with qualified names; changed when the
+    /// quantity or its unit differs. Repeated flows between the same pair are
+    /// counted, so each extra or missing copy is listed.
+    pub flows: DiffSection,
+}
+
+/// Compare the entities, resources and flows of two graphs. Each graph's semantic
+/// index supplies the flow quantities as written.
+pub fn graph_diff(
+    base: &Graph,
+    base_index: &SemanticIndex,
+    proposed: &Graph,
+    proposed_index: &SemanticIndex,
+) -> GraphDiff {
+    GraphDiff {
+        entities: diff(entity_details(base), entity_details(proposed)),
+        resources: diff(resource_details(base), resource_details(proposed)),
+        flows: diff_counted(
+            flow_details(base, base_index),
+            flow_details(proposed, proposed_index),
+        ),
+    }
+}
+
+fn entity_details(graph: &Graph) -> BTreeMap<String, Option<String>> {
+    graph
+        .all_entities()
+        .into_iter()
+        .map(|e| {
+            (
+                format!("{}::{}", e.namespace(), e.name()),
+                e.replaces().map(str::to_string),
+            )
+        })
+        .collect()
+}
+
+fn resource_details(graph: &Graph) -> BTreeMap<String, Option<String>> {
+    graph
+        .all_resources()
+        .into_iter()
+        .map(|r| {
+            (
+                format!("{}::{}", r.namespace(), r.name()),
+                Some(r.unit().symbol().to_string()),
+            )
+        })
+        .collect()
+}
+
+/// The `quantity unit` of every flow under its key. Graph flows carry ids, so each
+/// takes the detail of the next flow written with the same bare names.
+fn flow_details(graph: &Graph, index: &SemanticIndex) -> BTreeMap<String, Vec<Option<String>>> {
+    let mut written: HashMap<(&str, &str, &str), VecDeque<_>> = HashMap::new();
+    for flow in &index.flows {
+        written
+            .entry((
+                flow.resource.as_str(),
+                flow.from_entity.as_str(),
+                flow.to_entity.as_str(),
+            ))
+            .or_default()
+            .push_back(flow);
+    }
+    let qualified_entity = |id| {
+        graph
+            .get_entity(id)
+            .map(|e| (e.name(), format!("{}::{}", e.namespace(), e.name())))
+    };
+
+    let mut details: BTreeMap<String, Vec<Option<String>>> = BTreeMap::new();
+    for flow in graph.all_flows() {
+        let (Some(resource), Some((from, qualified_from)), Some((to, qualified_to))) = (
+            graph.get_resource(flow.resource_id()),
+            qualified_entity(flow.from_id()),
+            qualified_entity(flow.to_id()),
+        ) else {
+            continue;
+        };
+        let key = format!(
+            "{} -> {} ({}::{})",
+            qualified_from,
+            qualified_to,
+            resource.namespace(),
+            resource.name()
+        );
+        let detail = written
+            .get_mut(&(resource.name(), from, to))
+            .and_then(VecDeque::pop_front)
+            .and_then(|decl| {
+                let quantity = decl.quantity.as_ref()?;
+                let unit = decl
+                    .unit
+                    .as_ref()
+                    .map_or_else(|| resource.unit().symbol().to_string(), |(u, _)| u.clone());
+                Some(format!("{} {}", quantity, unit))
+            });
+        details.entry(key).or_default().push(detail);
+    }
+    details
+}
+
+fn diff(
+    base: BTreeMap<String, Option<String>>,
+    proposed: BTreeMap<String, Option<String>>,
+) -> DiffSection {
+    let mut section = DiffSection::default();
+    for (name, detail) in &proposed {
+        match base.get(name) {
+            None => section.added.push(name.clone()),
+            Some(old) if old != detail => section.changed.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    section.removed = base
+        .into_keys()
+        .filter(|name| !proposed.contains_key(name))
+        .collect();
+    section
+}
+
+/// [`diff`] for keys that may repeat. Copies whose details match on both sides
+/// cancel out; the rest pair up as changes, and any surplus is added or removed.
+fn diff_counted(
+    mut base: BTreeMap<String, Vec<Option<String>>>,
+    proposed: BTreeMap<String, Vec<Option<String>>>,
+) -> DiffSection {
+    let mut section = DiffSection::default();
+    for (name, details) in proposed {
+        let mut old = base.remove(&name).unwrap_or_default();
+        let mut new = Vec::new();
+        for detail in details {
+            match old.iter().position(|d| *d == detail) {
+                Some(i) => {
+                    old.swap_remove(i);
+                }
+                None => new.push(detail),
+            }
+        }
+        let changed = old.len().min(new.len());
+        section
+            .changed
+            .extend(std::iter::repeat_n(name.clone(), changed));
+        section
+            .added
+            .extend(std::iter::repeat_n(name.clone(), new.len() - changed));
+        section
+            .removed
+            .extend(std::iter::repeat_n(name, old.len() - changed));
+    }
+    for (name, details) in base {
+        section
+            .removed
+            .extend(std::iter::repeat_n(name, details.len()));
+    }
+    section.removed.sort();
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff_texts(base: &str, proposed: &str) -> GraphDiff {
+        graph_diff(
+            &sea_core::parse_to_graph(base).unwrap(),
+            &SemanticIndex::build(base),
+            &sea_core::parse_to_graph(proposed).unwrap(),
+            &SemanticIndex::build(proposed),
+        )
+    }
+
+    #[test]
+    fn reports_an_added_entity_and_a_removed_flow() {
+        let base = r#"Entity "Farm"
+Entity "Market"
+Resource "Crates" units
+Flow "Crates" from "Farm" to "Market"
+"#;
+        let proposed = r#"Entity "Farm"
+Entity "Market"
+Entity "Depot"
+Resource "Crates" units
+"#;
+        let diff = diff_texts(base, proposed);
+
+        assert_eq!(diff.entities.added, vec!["default::Depot"]);
+        assert!(diff.entities.removed.is_empty() && diff.entities.changed.is_empty());
+        assert_eq!(diff.resources, DiffSection::default());
+        assert!(diff.flows.added.is_empty());
+        assert_eq!(
+            diff.flows.removed,
+            vec!["default::Farm -> default::Market (default::Crates)"]
+        );
+    }
+
+    #[test]
+    fn flows_differ_by_quantity_and_count_repeats() {
+        let base = r#"Entity "Farm"
+Entity "Market"
+Resource "Crates" units
+Flow "Crates" from "Farm" to "Market" quantity 5
+Flow "Crates" from "Farm" to "Market" quantity 5
+Flow "Crates" from "Market" to "Farm" quantity 1
+"#;
+        let proposed = r#"Entity "Farm"
+Entity "Market"
+Resource "Crates" units
+Flow "Crates" from "Farm" to "Market" quantity 5
+Flow "Crates" from "Market" to "Farm" quantity 2
+"#;
+        let diff = diff_texts(base, proposed);
+
+        assert!(diff.flows.added.is_empty());
+        assert_eq!(
+            diff.flows.removed,
+            vec!["default::Farm -> default::Market (default::Crates)"]
+        );
+        assert_eq!(
+            diff.flows.changed,
+            vec!["default::Market -> default::Farm (default::Crates)"]
+        );
+        assert_eq!(diff_texts(base, base).flows, DiffSection::default());
+    }
+}
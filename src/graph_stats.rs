@@ -0,0 +1,103 @@
+//! Aggregate declaration counts for the `sea/graphStats` request.
+
+use std::collections::BTreeSet;
+
+use sea_core::Graph;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphStatsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphStats {
+    /// False when the document is not open or does not parse; every count is zero.
+    pub success: bool,
+    pub entities: usize,
+    pub resources: usize,
+    pub flows: usize,
+    pub roles: usize,
+    pub relations: usize,
+    pub patterns: usize,
+    pub policies: usize,
+    pub instances: usize,
+    /// Distinct namespaces across the graph's namespaced declarations.
+    pub namespaces: usize,
+}
+
+/// Count the declarations of a document's graph.
+pub fn graph_stats(graph: Option<&Graph>) -> GraphStats {
+    let Some(graph) = graph else {
+        return GraphStats::default();
+    };
+
+    let mut namespaces = BTreeSet::new();
+    namespaces.extend(graph.all_entities().iter().map(|e| e.namespace()));
+    namespaces.extend(graph.all_resources().iter().map(|r| r.namespace()));
+    namespaces.extend(graph.all_roles().iter().map(|r| r.namespace()));
+    namespaces.extend(graph.all_relations().iter().map(|r| r.namespace()));
+    namespaces.extend(graph.all_patterns().iter().map(|p| p.namespace()));
+    namespaces.extend(graph.all_entity_instances().iter().map(|i| i.namespace()));
+    namespaces.extend(graph.all_policies().iter().map(|p| p.namespace.as_str()));
+
+    GraphStats {
+        success: true,
+        entities: graph.all_entities().len(),
+        resources: graph.all_resources().len(),
+        flows: graph.all_flows().len(),
+        roles: graph.all_roles().len(),
+        relations: graph.all_relations().len(),
+        patterns: graph.all_patterns().len(),
+        policies: graph.all_policies().len(),
+        instances: graph.all_entity_instances().len(),
+        namespaces: namespaces.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_each_kind_of_declaration() {
+        let source = r#"Entity "Warehouse" in logistics
+Entity "Factory" in logistics
+Entity "Clerk" in staffing
+Resource "Camera" units
+Flow "Camera" from "Warehouse" to "Factory" quantity 10
+Role "Picker" in staffing
+Pattern "Email" matches ".*@.*"
+Instance depot of "Warehouse"
+Policy stocked as: true
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+
+        let stats = graph_stats(Some(&graph));
+
+        assert!(stats.success);
+        assert_eq!(
+            (stats.entities, stats.resources, stats.flows, stats.roles),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(
+            (
+                stats.patterns,
+                stats.instances,
+                stats.policies,
+                stats.relations
+            ),
+            (1, 1, 1, 0)
+        );
+        // logistics, staffing and the default namespace of the rest.
+        assert_eq!(stats.namespaces, 3);
+    }
+
+    #[test]
+    fn missing_graph_reports_failure() {
+        assert_eq!(graph_stats(None), GraphStats::default());
+        assert!(!graph_stats(None).success);
+    }
+}
@@ -1,5 +1,6 @@
 use super::HoverModel;
 
+#[derive(Debug, Clone)]
 pub struct MarkdownRenderResult {
     pub markdown: String,
     pub truncated_sections: Vec<String>,
@@ -54,22 +55,9 @@ pub fn render_markdown(model: &HoverModel) -> MarkdownRenderResult {
     }
 
     // Diagnostics
-    if model.symbol.resolution_confidence != "exact" || !model.limits.truncated_sections.is_empty()
-    {
-        lines.push("## Diagnostics".to_string());
-        if model.symbol.resolution_confidence != "exact" {
-            lines.push(format!(
-                "- **resolution**: {}",
-                model.symbol.resolution_confidence
-            ));
-        }
-        if !model.limits.truncated_sections.is_empty() {
-            let mut t = model.limits.truncated_sections.clone();
-            t.sort();
-            t.dedup();
-            lines.push(format!("- **limits**: {}", t.join(", ")));
-        }
-    }
+    let diagnostics_at = lines.len();
+    lines.extend(diagnostics_section(model, &truncated_sections));
+    let diagnostics_end = lines.len();
 
     // Resolution (progressive disclosure)
     lines.push("## Resolution".to_string());
@@ -101,11 +89,15 @@ pub fn render_markdown(model: &HoverModel) -> MarkdownRenderResult {
     if !model.related.is_empty() {
         lines.push("## Related".to_string());
         for rel in &model.related {
-            lines.push(format!(
-                "- {} ({})",
-                rel.qualified_name.trim(),
-                rel.kind.trim()
-            ));
+            let name = rel.qualified_name.trim();
+            let name = match &rel.range {
+                // Editors resolve `#L<line>` fragments of file links to the line
+                Some(range) => {
+                    format!("[{}]({}#L{})", name, model.symbol.uri, range.start.line + 1)
+                }
+                None => name.to_string(),
+            };
+            lines.push(format!("- {} ({})", name, rel.kind.trim()));
         }
     }
 
@@ -113,28 +105,86 @@ pub fn render_markdown(model: &HoverModel) -> MarkdownRenderResult {
 
     let max_bytes = model.limits.max_markdown_bytes;
     if markdown.len() > max_bytes {
+        truncated_sections.push("markdown".to_string());
+
+        // The Diagnostics block is always kept so clients can see what was clipped;
+        // reserve room for it and drop trailing lines instead.
+        let diagnostics = diagnostics_section(model, &truncated_sections);
+        let reserved: usize = diagnostics.iter().map(|l| l.len() + 1).sum();
+        let budget = max_bytes.saturating_sub(64).saturating_sub(reserved);
+
         let mut kept = String::with_capacity(max_bytes);
         let mut byte_count = 0usize;
-        for line in lines {
+        let mut diagnostics_emitted = false;
+        for (idx, line) in lines.iter().enumerate() {
+            if idx == diagnostics_at {
+                push_lines(&mut kept, &diagnostics);
+                diagnostics_emitted = true;
+            }
+            if (diagnostics_at..diagnostics_end).contains(&idx) {
+                continue;
+            }
             let line_bytes = line.len() + 1;
-            if byte_count + line_bytes > max_bytes.saturating_sub(64) {
-                truncated_sections.push("markdown".to_string());
+            if byte_count + line_bytes > budget {
                 break;
             }
-            kept.push_str(&line);
+            kept.push_str(line);
             kept.push('\n');
             byte_count += line_bytes;
         }
+        if !diagnostics_emitted {
+            push_lines(&mut kept, &diagnostics);
+        }
         kept.push_str("… truncated. Use hoverPlus for full detail.");
         markdown = kept;
     }
 
+    truncated_sections.sort();
+    truncated_sections.dedup();
     MarkdownRenderResult {
         markdown,
         truncated_sections,
     }
 }
 
+/// Lines of the Diagnostics section, or nothing when resolution was exact and no
+/// section was clipped by either the model builder or the renderer.
+fn diagnostics_section(model: &HoverModel, rendered_truncations: &[String]) -> Vec<String> {
+    let mut truncations: Vec<String> = model
+        .limits
+        .truncated_sections
+        .iter()
+        .chain(rendered_truncations)
+        .cloned()
+        .collect();
+    truncations.sort();
+    truncations.dedup();
+
+    let exact = model.symbol.resolution_confidence == "exact";
+    if exact && truncations.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = vec!["## Diagnostics".to_string()];
+    if !exact {
+        lines.push(format!(
+            "- **resolution**: {}",
+            model.symbol.resolution_confidence
+        ));
+    }
+    if !truncations.is_empty() {
+        lines.push(format!("- **limits**: {}", truncations.join(", ")));
+    }
+    lines
+}
+
+fn push_lines(out: &mut String, lines: &[String]) {
+    for line in lines {
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
 fn push_code_block(
     lines: &mut Vec<String>,
     language: &str,
@@ -242,11 +292,25 @@ mod tests {
         };
 
         let rendered = render_markdown(&model);
-        assert!(rendered.markdown.as_bytes().len() <= 256 + 64);
+        assert!(rendered.markdown.len() <= 256 + 64);
         assert!(
             rendered.markdown.contains("… truncated"),
             "should include truncation marker"
         );
+        assert!(rendered
+            .truncated_sections
+            .contains(&"markdown".to_string()));
+
+        let diagnostics = rendered
+            .markdown
+            .split("## Diagnostics")
+            .nth(1)
+            .expect("diagnostics section survives truncation");
+        let limits = diagnostics
+            .lines()
+            .find(|l| l.starts_with("- **limits**"))
+            .expect("limits line");
+        assert!(limits.contains("markdown"), "got: {limits}");
     }
 
     #[test]
@@ -300,6 +364,7 @@ mod tests {
                 qualified_name: "default::Y".to_string(),
                 kind: "Entity".to_string(),
                 relevance_score: 1,
+                range: None,
             }],
             limits: HoverLimits {
                 max_markdown_bytes: 4096,
@@ -1,4 +1,5 @@
 pub mod markdown_renderer;
+pub mod plaintext_renderer;
 pub mod symbol_resolver;
 
 use serde::{Deserialize, Serialize};
@@ -22,6 +23,25 @@ impl DetailLevel {
     }
 }
 
+pub const DEFAULT_MAX_MARKDOWN_BYTES: usize = 32 * 1024;
+pub const DEFAULT_MAX_JSON_BYTES: usize = 128 * 1024;
+
+/// Per-request byte budgets for hover payloads, surfaced in `HoverLimits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoverBudget {
+    pub max_markdown_bytes: usize,
+    pub max_json_bytes: usize,
+}
+
+impl Default for HoverBudget {
+    fn default() -> Self {
+        Self {
+            max_markdown_bytes: DEFAULT_MAX_MARKDOWN_BYTES,
+            max_json_bytes: DEFAULT_MAX_JSON_BYTES,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HoverModel {
     pub schema_version: String,
@@ -80,6 +100,9 @@ pub struct HoverRelated {
     pub qualified_name: String,
     pub kind: String,
     pub relevance_score: i32,
+    /// Where the related item is written in the hovered document, when it is there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<HoverRange>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,6 +135,58 @@ pub struct HoverPlusResponse {
     pub markdown: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonikerParams {
+    pub text_document: HoverTextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// Stable identity of a symbol, independent of document version or cursor position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolMoniker {
+    pub scheme: String,
+    /// `namespace::name` for graph-backed symbols, the bare name otherwise.
+    pub identifier: String,
+    /// Graph id of the resolved symbol, or a `<...>` placeholder when unresolved.
+    pub graph_id: String,
+    pub kind: String,
+    pub resolution_confidence: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolveParams {
+    pub text_document: HoverTextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// Identity and confidence of the symbol at a position, without the rest of the
+/// hover model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolResolution {
+    /// Same value as the hover model's `resolve_id`.
+    pub resolve_id: String,
+    pub qualified_name: String,
+    pub confidence: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickInfoParams {
+    pub text_document: HoverTextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// One-line summary of the symbol at a position, for status bars and clients that
+/// don't need the full hover model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickInfo {
+    pub kind: String,
+    pub qualified_name: String,
+    /// Same value as the hover model's `signature_or_shape`.
+    pub signature: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HoverRange {
     pub start: HoverPosition,
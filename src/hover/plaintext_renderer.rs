@@ -0,0 +1,53 @@
+//! Plain-text hover rendering for clients that don't display markdown.
+
+use super::HoverModel;
+
+/// Render `model` as plain text: header, signature, summary, then badges and facts
+/// one per line. Output past `limits.max_markdown_bytes` is cut at a line boundary.
+pub fn render_plaintext(model: &HoverModel) -> String {
+    let mut lines = vec![
+        format!(
+            "{} {}",
+            model.primary.header.kind_label, model.primary.header.qualified_path
+        ),
+        model.primary.signature_or_shape.clone(),
+    ];
+    if !model.primary.summary.is_empty() {
+        lines.push(String::new());
+        lines.extend(model.primary.summary.lines().map(str::to_string));
+    }
+
+    let mut details = Vec::new();
+    if !model.primary.badges.is_empty() {
+        let mut badges = model.primary.badges.clone();
+        badges.sort();
+        badges.dedup();
+        details.push(format!("badges: {}", badges.join(", ")));
+    }
+    let mut facts = model.primary.facts.clone();
+    facts.sort();
+    details.extend(facts.into_iter().map(|(k, v)| format!("{}: {}", k, v)));
+    if model.symbol.resolution_confidence != "exact" {
+        details.push(format!(
+            "resolution: {}",
+            model.symbol.resolution_confidence
+        ));
+    }
+    if !details.is_empty() {
+        lines.push(String::new());
+        lines.extend(details);
+    }
+
+    let max_bytes = model.limits.max_markdown_bytes;
+    let mut text = String::new();
+    for line in lines {
+        if text.len() + line.len() + 1 > max_bytes {
+            text.push_str("… truncated. Use hoverPlus for full detail.");
+            return text;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.truncate(text.trim_end().len());
+    text
+}
@@ -1,21 +1,28 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use blake3::Hasher;
 use sea_core::Graph;
 use tower_lsp::lsp_types::{Position, Url};
 
 use crate::line_index::LineIndex;
-use crate::semantic_index::{ByteRange, FlowDecl, Occurrence, SemanticIndex, SymbolKind};
+use crate::navigation::declaration_namespace;
+use crate::semantic_index::{
+    Annotation, ByteRange, FlowDecl, NamespaceRole, Occurrence, SemanticIndex, SymbolKind,
+};
 
 use super::{
-    DetailLevel, HoverContext, HoverHeader, HoverLimits, HoverModel, HoverPosition, HoverRange,
-    HoverRelated, HoverScopeSummary, HoverSymbol,
+    DetailLevel, HoverBudget, HoverContext, HoverHeader, HoverLimits, HoverModel, HoverPosition,
+    HoverRange, HoverRelated, HoverScopeSummary, HoverSymbol, QuickInfo, SymbolMoniker,
+    SymbolResolution,
 };
 
 const SCHEMA_VERSION: &str = "1.0";
-const MAX_MARKDOWN_BYTES: usize = 32 * 1024;
-const MAX_JSON_BYTES: usize = 128 * 1024;
+const MONIKER_SCHEME: &str = "domainforge";
 const MAX_FLOW_SCAN: usize = 2000;
+/// Instance field values listed at `DetailLevel::Deep` before the rest are dropped.
+const MAX_FIELD_FACTS: usize = 10;
+/// Patterns listed in an entity's `constrained_by` fact before the rest are dropped.
+const MAX_CONSTRAINING_PATTERNS: usize = 5;
 
 #[derive(Debug, Clone)]
 pub struct HoverBuildInput<'a> {
@@ -27,13 +34,44 @@ pub struct HoverBuildInput<'a> {
     pub line_index: &'a LineIndex,
     pub index: &'a SemanticIndex,
     pub graph: Option<&'a Graph>,
+    pub budget: HoverBudget,
 }
 
+/// Build the hover model for the symbol at `input.position`.
+///
+/// Returns `None` only when no symbol occurrence covers the position (whitespace,
+/// comments, keywords). Any recognized occurrence yields a model, falling back to
+/// `resolution_confidence: "error_fallback"` when the graph cannot resolve it.
 pub fn build_hover_model(input: HoverBuildInput<'_>) -> Option<HoverModel> {
     let offset = input.line_index.offset_of(input.position)?;
-    let occurrence = input.index.symbol_at_offset(offset)?;
-
-    let resolved = resolve_occurrence(occurrence, input.index, input.graph, input.detail_level);
+    let (resolved, symbol_range) = match input.index.symbol_at_offset(offset) {
+        Some(occurrence) => {
+            let mut resolved = resolve_occurrence(
+                occurrence,
+                input.index,
+                input.line_index,
+                input.graph,
+                input.detail_level,
+            );
+            if let Some(doc) = input.index.doc_comment(occurrence.kind, &occurrence.name) {
+                resolved.summary = doc.to_string();
+            }
+            if let Some(enclosing) = input.index.enclosing_occurrence(occurrence) {
+                resolved.facts.push((
+                    "enclosing".to_string(),
+                    describe_enclosing(input.index, enclosing),
+                ));
+            }
+            (resolved, occurrence.range)
+        }
+        None => {
+            let annotation = input.index.annotation_at_offset(offset)?;
+            (
+                resolve_annotation(annotation, input.index, input.graph),
+                annotation.range,
+            )
+        }
+    };
     let id = hover_id(
         input.uri,
         input.document_version,
@@ -43,7 +81,7 @@ pub fn build_hover_model(input: HoverBuildInput<'_>) -> Option<HoverModel> {
         input.detail_level,
     );
 
-    let range = byte_range_to_hover_range(input.line_index, occurrence.range);
+    let range = byte_range_to_hover_range(input.line_index, symbol_range);
 
     let mut related = resolved.related;
     related.sort_by(|a, b| {
@@ -89,16 +127,106 @@ pub fn build_hover_model(input: HoverBuildInput<'_>) -> Option<HoverModel> {
         },
         related,
         limits: HoverLimits {
-            max_markdown_bytes: MAX_MARKDOWN_BYTES,
-            max_json_bytes: MAX_JSON_BYTES,
+            max_markdown_bytes: input.budget.max_markdown_bytes,
+            max_json_bytes: input.budget.max_json_bytes,
             truncated_sections: resolved.truncated_sections,
         },
     };
+    if input.index.approximate {
+        model.primary.badges.push("approximate".to_string());
+    }
     model.limits.truncated_sections.sort();
     model.limits.truncated_sections.dedup();
     Some(model)
 }
 
+/// Short description of the occurrence around the hovered one, for the `enclosing`
+/// fact. Flows are named the way their own hovers name them.
+fn describe_enclosing(index: &SemanticIndex, enclosing: &Occurrence) -> String {
+    match index.flow_decl_for_range(enclosing.range) {
+        Some(flow) if enclosing.kind == SymbolKind::Flow => format!(
+            "Flow {} -> {} ({})",
+            flow.from_entity, flow.to_entity, flow.resource
+        ),
+        _ => format!("{:?} {}", enclosing.kind, enclosing.name),
+    }
+}
+
+/// Flag a model built against the last graph that parsed rather than the current
+/// text: resolved symbols get a `stale` badge, and exact ones drop to
+/// `resolution_confidence: "stale"`. Ambiguous symbols keep `ambiguous` and
+/// unresolved ones keep `error_fallback`.
+pub fn mark_stale(model: &mut HoverModel) {
+    if model.symbol.resolution_confidence == "error_fallback" {
+        return;
+    }
+    if model.symbol.resolution_confidence == "exact" {
+        model.symbol.resolution_confidence = "stale".to_string();
+    }
+    model.primary.badges.push("stale".to_string());
+}
+
+/// Resolve the symbol at `position` to its stable moniker.
+///
+/// Uses the same resolution as hover, so the moniker's `graph_id` always matches the
+/// hover model's `resolve_id`.
+pub fn build_moniker(
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> Option<SymbolMoniker> {
+    let offset = line_index.offset_of(position)?;
+    let occurrence = index.symbol_at_offset(offset)?;
+    let resolved = resolve_occurrence(occurrence, index, line_index, graph, DetailLevel::Core);
+
+    Some(SymbolMoniker {
+        scheme: MONIKER_SCHEME.to_string(),
+        identifier: resolved.qualified_name,
+        graph_id: resolved.resolve_id,
+        kind: resolved.kind_label.to_string(),
+        resolution_confidence: resolved.confidence,
+    })
+}
+
+/// Resolve the symbol at `position` to its `resolve_id`, qualified name and
+/// confidence, skipping everything else the hover model computes.
+pub fn build_resolution(
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> Option<SymbolResolution> {
+    let offset = line_index.offset_of(position)?;
+    let occurrence = index.symbol_at_offset(offset)?;
+    let resolved = resolve_occurrence(occurrence, index, line_index, graph, DetailLevel::Core);
+
+    Some(SymbolResolution {
+        resolve_id: resolved.resolve_id,
+        qualified_name: resolved.qualified_name,
+        confidence: resolved.confidence,
+    })
+}
+
+/// Resolve the symbol at `position` to its kind, qualified name and signature, at
+/// `DetailLevel::Core` and without building the rest of the hover model.
+pub fn build_quick_info(
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> Option<QuickInfo> {
+    let offset = line_index.offset_of(position)?;
+    let occurrence = index.symbol_at_offset(offset)?;
+    let resolved = resolve_occurrence(occurrence, index, line_index, graph, DetailLevel::Core);
+
+    Some(QuickInfo {
+        kind: resolved.kind_label.to_string(),
+        qualified_name: resolved.qualified_name,
+        signature: resolved.signature,
+    })
+}
+
 #[derive(Debug, Clone)]
 struct ResolvedSymbol {
     name: String,
@@ -117,22 +245,29 @@ struct ResolvedSymbol {
 fn resolve_occurrence(
     occ: &Occurrence,
     index: &SemanticIndex,
+    line_index: &LineIndex,
     graph: Option<&Graph>,
     detail_level: DetailLevel,
 ) -> ResolvedSymbol {
     match occ.kind {
-        SymbolKind::Entity => resolve_entity(&occ.name, graph, detail_level),
-        SymbolKind::Resource => resolve_resource(&occ.name, graph, detail_level),
+        SymbolKind::Entity => resolve_entity(&occ.name, index, line_index, graph, detail_level),
+        SymbolKind::Resource => resolve_resource(&occ.name, index, line_index, graph, detail_level),
         SymbolKind::Flow => resolve_flow(occ.range, index, graph),
-        SymbolKind::Role => resolve_role(&occ.name, graph),
-        SymbolKind::Relation => resolve_relation(&occ.name, graph),
-        SymbolKind::Pattern => resolve_pattern(&occ.name, graph),
-        SymbolKind::Instance => resolve_instance(&occ.name, graph, detail_level),
-        SymbolKind::Policy => resolve_policy(&occ.name, graph),
+        SymbolKind::Role => resolve_role(&occ.name, index, line_index, graph, detail_level),
+        SymbolKind::Relation => resolve_relation(&occ.name, index, line_index, graph, detail_level),
+        SymbolKind::Pattern => resolve_pattern(&occ.name, index, graph),
+        SymbolKind::Instance => resolve_instance(&occ.name, index, graph, detail_level),
+        SymbolKind::Policy => resolve_policy(&occ.name, index, line_index, graph, detail_level),
     }
 }
 
-fn resolve_entity(name: &str, graph: Option<&Graph>, detail_level: DetailLevel) -> ResolvedSymbol {
+fn resolve_entity(
+    name: &str,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+    graph: Option<&Graph>,
+    detail_level: DetailLevel,
+) -> ResolvedSymbol {
     let mut badges = Vec::new();
     let mut facts = Vec::new();
     let mut related = Vec::new();
@@ -191,14 +326,20 @@ fn resolve_entity(name: &str, graph: Option<&Graph>, detail_level: DetailLevel)
                 ),
             }
         }
-        None => (
-            "<no-graph>".to_string(),
-            name.to_string(),
-            "error_fallback".to_string(),
-            None,
-            None,
-            None,
-        ),
+        None => {
+            // No graph (the document failed to parse), but the recovered syntax
+            // index still knows which flows mention this entity.
+            let flows_from = index.flows.iter().filter(|f| f.from_entity == name).count();
+            let flows_to = index.flows.iter().filter(|f| f.to_entity == name).count();
+            (
+                "<no-graph>".to_string(),
+                name.to_string(),
+                "error_fallback".to_string(),
+                None,
+                Some((flows_from, flows_to)),
+                None,
+            )
+        }
     };
 
     if confidence == "ambiguous" {
@@ -207,6 +348,7 @@ fn resolve_entity(name: &str, graph: Option<&Graph>, detail_level: DetailLevel)
     if confidence == "error_fallback" {
         badges.push("unresolved".to_string());
     }
+    push_declared_without_graph(&mut facts, index, graph, SymbolKind::Entity, name);
 
     if let Some(ns) = namespace {
         facts.push(("namespace".to_string(), ns));
@@ -222,9 +364,37 @@ fn resolve_entity(name: &str, graph: Option<&Graph>, detail_level: DetailLevel)
             facts.push(("roles".to_string(), roles.join(", ")));
         }
     }
+    push_reference_count(&mut facts, index, SymbolKind::Entity, name, detail_level);
+    push_declared_at(
+        &mut facts,
+        index,
+        line_index,
+        SymbolKind::Entity,
+        name,
+        detail_level,
+    );
 
     if matches!(detail_level, DetailLevel::Standard | DetailLevel::Deep) {
+        related.extend(namespace_origin(
+            SymbolKind::Entity,
+            name,
+            index,
+            line_index,
+        ));
         if let Some(graph) = graph {
+            let patterns = constraining_patterns(graph, name);
+            if patterns.len() > MAX_CONSTRAINING_PATTERNS {
+                truncated_sections.push("constrained_by".to_string());
+            }
+            if !patterns.is_empty() {
+                let listed: Vec<&str> = patterns
+                    .iter()
+                    .take(MAX_CONSTRAINING_PATTERNS)
+                    .map(String::as_str)
+                    .collect();
+                facts.push(("constrained_by".to_string(), listed.join(", ")));
+            }
+
             let mut resources_by_count: BTreeMap<String, i32> = BTreeMap::new();
             let flows = graph.all_flows();
             if flows.len() > MAX_FLOW_SCAN {
@@ -251,6 +421,26 @@ fn resolve_entity(name: &str, graph: Option<&Graph>, detail_level: DetailLevel)
                     qualified_name: qname,
                     kind: "Resource".to_string(),
                     relevance_score: score,
+                    range: None,
+                });
+            }
+        } else {
+            let mut resources_by_count: BTreeMap<&str, i32> = BTreeMap::new();
+            for flow in index
+                .flows
+                .iter()
+                .filter(|f| f.from_entity == name || f.to_entity == name)
+            {
+                *resources_by_count
+                    .entry(flow.resource.as_str())
+                    .or_default() += 1;
+            }
+            for (resource, score) in resources_by_count {
+                related.push(HoverRelated {
+                    qualified_name: resource.to_string(),
+                    kind: "Resource".to_string(),
+                    relevance_score: score,
+                    range: None,
                 });
             }
         }
@@ -273,6 +463,8 @@ fn resolve_entity(name: &str, graph: Option<&Graph>, detail_level: DetailLevel)
 
 fn resolve_resource(
     name: &str,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
     graph: Option<&Graph>,
     detail_level: DetailLevel,
 ) -> ResolvedSymbol {
@@ -332,11 +524,37 @@ fn resolve_resource(
     if confidence == "error_fallback" {
         badges.push("unresolved".to_string());
     }
+    if graph.is_none() {
+        facts.push((
+            "declared".to_string(),
+            declared_fact(index, SymbolKind::Resource, name),
+        ));
+        let flow_count = index.flows.iter().filter(|f| f.resource == name).count();
+        facts.push(("flows".to_string(), flow_count.to_string()));
+    }
     if let Some(ns) = namespace {
         facts.push(("namespace".to_string(), ns));
     }
-    if let Some(unit) = unit_symbol {
-        facts.push(("unit".to_string(), unit));
+    if let Some(unit) = &unit_symbol {
+        facts.push(("unit".to_string(), unit.clone()));
+    }
+    push_reference_count(&mut facts, index, SymbolKind::Resource, name, detail_level);
+    push_declared_at(
+        &mut facts,
+        index,
+        line_index,
+        SymbolKind::Resource,
+        name,
+        detail_level,
+    );
+    if detail_level == DetailLevel::Deep {
+        push_total_quantity(
+            &mut facts,
+            &mut truncated_sections,
+            index,
+            name,
+            unit_symbol.as_deref(),
+        );
     }
 
     if matches!(detail_level, DetailLevel::Standard | DetailLevel::Deep) {
@@ -371,6 +589,25 @@ fn resolve_resource(
                     qualified_name: qname,
                     kind: "Entity".to_string(),
                     relevance_score: score,
+                    range: None,
+                });
+            }
+        } else {
+            let mut entities_by_count: BTreeMap<&str, i32> = BTreeMap::new();
+            for flow in index.flows.iter().filter(|f| f.resource == name) {
+                *entities_by_count
+                    .entry(flow.from_entity.as_str())
+                    .or_default() += 1;
+                *entities_by_count
+                    .entry(flow.to_entity.as_str())
+                    .or_default() += 1;
+            }
+            for (entity, score) in entities_by_count {
+                related.push(HoverRelated {
+                    qualified_name: entity.to_string(),
+                    kind: "Entity".to_string(),
+                    relevance_score: score,
+                    range: None,
                 });
             }
         }
@@ -391,15 +628,53 @@ fn resolve_resource(
     }
 }
 
+/// Sum of the quantities written on the resource's flows, in the resource's `unit`.
+/// Flows without a numeric quantity, or with an inline unit other than `unit`, are
+/// left out of the sum and mark it `partial`.
+fn push_total_quantity(
+    facts: &mut Vec<(String, String)>,
+    truncated_sections: &mut Vec<String>,
+    index: &SemanticIndex,
+    name: &str,
+    unit: Option<&str>,
+) {
+    let mut flows = index.flows.iter().filter(|f| f.resource == name).peekable();
+    if flows.peek().is_none() {
+        return;
+    }
+    let mut total = 0.0;
+    let mut partial = false;
+    for flow in flows {
+        let same_unit = flow
+            .unit
+            .as_ref()
+            .is_none_or(|(inline, _)| Some(inline.as_str()) == unit);
+        let quantity = flow
+            .quantity
+            .as_deref()
+            .and_then(|q| q.replace('_', "").parse::<f64>().ok())
+            .filter(|_| same_unit);
+        match quantity {
+            Some(quantity) => total += quantity,
+            None => partial = true,
+        }
+    }
+    facts.push(("total_quantity".to_string(), total.to_string()));
+    if partial {
+        truncated_sections.push("partial".to_string());
+    }
+}
+
 fn resolve_instance(
     name: &str,
+    index: &SemanticIndex,
     graph: Option<&Graph>,
     detail_level: DetailLevel,
 ) -> ResolvedSymbol {
     let mut badges = Vec::new();
     let mut facts = Vec::new();
     let mut related = Vec::new();
-    let truncated_sections = Vec::new();
+    let mut truncated_sections = Vec::new();
 
     let (resolve_id, qualified_name, confidence, entity_type, field_count) = match graph {
         Some(graph) => match graph.get_entity_instance(name) {
@@ -438,12 +713,26 @@ fn resolve_instance(
                 qualified_name: entity_type,
                 kind: "Entity".to_string(),
                 relevance_score: 10,
+                range: None,
             });
         }
     }
     if let Some(field_count) = field_count {
         facts.push(("fields".to_string(), field_count.to_string()));
     }
+    if detail_level == DetailLevel::Deep {
+        if let Some(instance) = graph.and_then(|g| g.get_entity_instance(name)) {
+            let fields = instance.fields();
+            for (field, value) in fields.iter().take(MAX_FIELD_FACTS) {
+                facts.push((format!("field.{}", field), value.to_string()));
+            }
+            if fields.len() > MAX_FIELD_FACTS {
+                truncated_sections.push("fields".to_string());
+            }
+        }
+    }
+
+    push_declared_without_graph(&mut facts, index, graph, SymbolKind::Instance, name);
 
     ResolvedSymbol {
         name: name.to_string(),
@@ -460,7 +749,13 @@ fn resolve_instance(
     }
 }
 
-fn resolve_role(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
+fn resolve_role(
+    name: &str,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+    graph: Option<&Graph>,
+    detail_level: DetailLevel,
+) -> ResolvedSymbol {
     let mut badges = Vec::new();
     let mut facts = Vec::new();
     let truncated_sections = Vec::new();
@@ -518,6 +813,18 @@ fn resolve_role(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
         facts.push(("namespace".to_string(), ns));
     }
 
+    push_reference_count(&mut facts, index, SymbolKind::Role, name, detail_level);
+    push_declared_at(
+        &mut facts,
+        index,
+        line_index,
+        SymbolKind::Role,
+        name,
+        detail_level,
+    );
+
+    push_declared_without_graph(&mut facts, index, graph, SymbolKind::Role, name);
+
     ResolvedSymbol {
         name: name.to_string(),
         kind_label: "Role",
@@ -533,7 +840,13 @@ fn resolve_role(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
     }
 }
 
-fn resolve_relation(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
+fn resolve_relation(
+    name: &str,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+    graph: Option<&Graph>,
+    detail_level: DetailLevel,
+) -> ResolvedSymbol {
     let mut badges = Vec::new();
     let mut facts = Vec::new();
     let truncated_sections = Vec::new();
@@ -591,6 +904,18 @@ fn resolve_relation(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
         facts.push(("namespace".to_string(), ns));
     }
 
+    push_reference_count(&mut facts, index, SymbolKind::Relation, name, detail_level);
+    push_declared_at(
+        &mut facts,
+        index,
+        line_index,
+        SymbolKind::Relation,
+        name,
+        detail_level,
+    );
+
+    push_declared_without_graph(&mut facts, index, graph, SymbolKind::Relation, name);
+
     ResolvedSymbol {
         name: name.to_string(),
         kind_label: "Relation",
@@ -606,8 +931,9 @@ fn resolve_relation(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
     }
 }
 
-fn resolve_pattern(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
+fn resolve_pattern(name: &str, index: &SemanticIndex, graph: Option<&Graph>) -> ResolvedSymbol {
     let mut badges = Vec::new();
+    let mut facts = Vec::new();
     let truncated_sections = Vec::new();
 
     let (resolve_id, qualified_name, confidence) = match graph {
@@ -655,6 +981,8 @@ fn resolve_pattern(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
         });
     }
 
+    push_declared_without_graph(&mut facts, index, graph, SymbolKind::Pattern, name);
+
     ResolvedSymbol {
         name: name.to_string(),
         kind_label: "Pattern",
@@ -664,13 +992,19 @@ fn resolve_pattern(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
         signature: format!("Pattern \"{}\"", name),
         summary: "DomainForge pattern".to_string(),
         badges,
-        facts: Vec::new(),
+        facts,
         related: Vec::new(),
         truncated_sections,
     }
 }
 
-fn resolve_policy(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
+fn resolve_policy(
+    name: &str,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+    graph: Option<&Graph>,
+    detail_level: DetailLevel,
+) -> ResolvedSymbol {
     let mut badges = Vec::new();
     let mut facts = Vec::new();
     let truncated_sections = Vec::new();
@@ -762,6 +1096,15 @@ fn resolve_policy(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
     if let Some(priority) = priority {
         facts.push(("priority".to_string(), priority.to_string()));
     }
+    push_reference_count(&mut facts, index, SymbolKind::Policy, name, detail_level);
+    push_declared_at(
+        &mut facts,
+        index,
+        line_index,
+        SymbolKind::Policy,
+        name,
+        detail_level,
+    );
 
     let signature = if let Some(expr) = expr_summary {
         format!("Policy {} as:\n    {}", name, expr)
@@ -769,6 +1112,8 @@ fn resolve_policy(name: &str, graph: Option<&Graph>) -> ResolvedSymbol {
         format!("Policy {} as: …", name)
     };
 
+    push_declared_without_graph(&mut facts, index, graph, SymbolKind::Policy, name);
+
     ResolvedSymbol {
         name: name.to_string(),
         kind_label: "Policy",
@@ -801,6 +1146,7 @@ fn resolve_flow(range: ByteRange, index: &SemanticIndex, graph: Option<&Graph>)
         from_entity: "<unknown>".to_string(),
         to_entity: "<unknown>".to_string(),
         quantity: None,
+        unit: None,
     });
 
     facts.push(("resource".to_string(), resource.clone()));
@@ -810,15 +1156,8 @@ fn resolve_flow(range: ByteRange, index: &SemanticIndex, graph: Option<&Graph>)
         facts.push(("quantity".to_string(), q));
     }
 
-    if let Some(graph) = graph {
-        let unit = graph
-            .all_resources()
-            .into_iter()
-            .find(|r| r.name() == resource)
-            .map(|r| r.unit().symbol().to_string());
-        if let Some(unit) = unit {
-            facts.push(("unit".to_string(), unit));
-        }
+    if let Some(unit) = graph.and_then(|graph| resource_unit(graph, &resource)) {
+        facts.push(("unit".to_string(), unit));
     }
 
     ResolvedSymbol {
@@ -850,6 +1189,214 @@ fn resolve_flow(range: ByteRange, index: &SemanticIndex, graph: Option<&Graph>)
     }
 }
 
+/// Patterns used by policies whose expression mentions `entity`, sorted.
+///
+/// Policy expressions name entities and patterns as string literals or bare
+/// identifiers, so both are matched as whole words in the expression text.
+fn constraining_patterns(graph: &Graph, entity: &str) -> Vec<String> {
+    let patterns = graph.all_patterns();
+    let mut used = BTreeSet::new();
+    for policy in graph.all_policies() {
+        let expression = policy.expression().to_string();
+        if !mentions(&expression, entity) {
+            continue;
+        }
+        used.extend(
+            patterns
+                .iter()
+                .filter(|pattern| mentions(&expression, pattern.name()))
+                .map(|pattern| pattern.name().to_string()),
+        );
+    }
+    used.into_iter().collect()
+}
+
+/// Whether `name` appears in `text` with no identifier character on either side.
+fn mentions(text: &str, name: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    !name.is_empty()
+        && text.match_indices(name).any(|(start, _)| {
+            let end = start + name.len();
+            !text[..start].chars().next_back().is_some_and(is_ident)
+                && !text[end..].chars().next().is_some_and(is_ident)
+        })
+}
+
+/// Related entry for the `@namespace` annotation, or failing that the wildcard import
+/// alias, that defines the namespace named in the declaration's `in` clause. Scored
+/// above any flow-derived entry so it lists first.
+fn namespace_origin(
+    kind: SymbolKind,
+    name: &str,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+) -> Option<HoverRelated> {
+    let declaration = index
+        .declarations
+        .iter()
+        .find(|d| d.kind == kind && d.name == name)?;
+    let in_clause = index.namespaces.iter().find(|occ| {
+        occ.role == NamespaceRole::InClause
+            && occ.range.start >= declaration.range.start
+            && occ.range.end <= declaration.range.end
+    })?;
+    let origin = [NamespaceRole::Declaration, NamespaceRole::ImportAlias]
+        .into_iter()
+        .find_map(|role| {
+            index
+                .namespaces
+                .iter()
+                .find(|occ| occ.role == role && occ.namespace == in_clause.namespace)
+        })?;
+
+    Some(HoverRelated {
+        qualified_name: origin.namespace.clone(),
+        kind: "Namespace".to_string(),
+        relevance_score: 100,
+        range: Some(byte_range_to_hover_range(line_index, origin.range)),
+    })
+}
+
+/// Describe a file-level annotation: `@namespace` counts the declarations placed in
+/// the namespace, `@version` breaks the value into its semantic version parts.
+fn resolve_annotation(
+    annotation: &Annotation,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> ResolvedSymbol {
+    let mut badges = Vec::new();
+    let mut facts = Vec::new();
+
+    let kind_label = match annotation.key.as_str() {
+        "namespace" => {
+            // Declarations the graph cannot place belong to the file namespace.
+            let declarations = index
+                .declarations
+                .iter()
+                .filter(|decl| {
+                    graph
+                        .and_then(|graph| declaration_namespace(graph, decl.kind, &decl.name))
+                        .is_none_or(|namespace| namespace == annotation.value)
+                })
+                .count();
+            facts.push(("declarations".to_string(), declarations.to_string()));
+            "Namespace"
+        }
+        "version" => {
+            match parse_semver(&annotation.value) {
+                Some((major, minor, patch, pre_release)) => {
+                    facts.push(("major".to_string(), major.to_string()));
+                    facts.push(("minor".to_string(), minor.to_string()));
+                    facts.push(("patch".to_string(), patch.to_string()));
+                    if let Some(pre_release) = pre_release {
+                        facts.push(("pre_release".to_string(), pre_release.to_string()));
+                    }
+                }
+                None => badges.push("invalid_version".to_string()),
+            }
+            "Version"
+        }
+        _ => "Annotation",
+    };
+
+    ResolvedSymbol {
+        name: annotation.value.clone(),
+        kind_label,
+        qualified_name: annotation.value.clone(),
+        resolve_id: format!("@{}:{}", annotation.key, annotation.value),
+        confidence: "exact".to_string(),
+        signature: format!("@{} \"{}\"", annotation.key, annotation.value),
+        summary: format!("File-level @{} annotation", annotation.key),
+        badges,
+        facts,
+        related: Vec::new(),
+        truncated_sections: Vec::new(),
+    }
+}
+
+/// Split `MAJOR.MINOR.PATCH[-PRE][+BUILD]` into its numeric parts and pre-release tag.
+fn parse_semver(value: &str) -> Option<(u64, u64, u64, Option<&str>)> {
+    let version = value.split('+').next()?;
+    let (core, pre_release) = match version.split_once('-') {
+        Some((core, pre)) if !pre.is_empty() => (core, Some(pre)),
+        Some(_) => return None,
+        None => (version, None),
+    };
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some((major, minor, patch, pre_release))
+}
+
+/// Add a `declared` fact from the syntax index when there is no graph to consult.
+fn push_declared_without_graph(
+    facts: &mut Vec<(String, String)>,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+    kind: SymbolKind,
+    name: &str,
+) {
+    if graph.is_none() {
+        facts.push(("declared".to_string(), declared_fact(index, kind, name)));
+    }
+}
+
+/// Add an in-file `references` count from Standard detail up.
+fn push_reference_count(
+    facts: &mut Vec<(String, String)>,
+    index: &SemanticIndex,
+    kind: SymbolKind,
+    name: &str,
+    detail_level: DetailLevel,
+) {
+    if matches!(detail_level, DetailLevel::Standard | DetailLevel::Deep) {
+        let count = index.reference_ranges(kind, name).len();
+        facts.push(("references".to_string(), count.to_string()));
+    }
+}
+
+/// Add the 1-based `line:column` of the in-file definition from Standard detail up.
+fn push_declared_at(
+    facts: &mut Vec<(String, String)>,
+    index: &SemanticIndex,
+    line_index: &LineIndex,
+    kind: SymbolKind,
+    name: &str,
+    detail_level: DetailLevel,
+) {
+    if !matches!(detail_level, DetailLevel::Standard | DetailLevel::Deep) {
+        return;
+    }
+    if let Some(def) = index.definition_range(kind, name) {
+        let position = line_index.position_of(def.start);
+        facts.push((
+            "declared_at".to_string(),
+            format!("{}:{}", position.line + 1, position.character + 1),
+        ));
+    }
+}
+
+/// Unit symbol of the resource named `resource`, if the graph declares it.
+pub(crate) fn resource_unit(graph: &Graph, resource: &str) -> Option<String> {
+    graph
+        .all_resources()
+        .into_iter()
+        .find(|r| r.name() == resource)
+        .map(|r| r.unit().symbol().to_string())
+}
+
+/// Whether the syntax index saw a declaration for `name`, used when the graph is missing.
+fn declared_fact(index: &SemanticIndex, kind: SymbolKind, name: &str) -> String {
+    if index.definition_range(kind, name).is_some() {
+        "in this file".to_string()
+    } else {
+        "not found in this file".to_string()
+    }
+}
+
 fn hover_id(
     uri: &Url,
     version: i32,
@@ -910,6 +1457,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         };
 
         let input2 = HoverBuildInput {
@@ -921,6 +1469,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         };
 
         let m1 = build_hover_model(input1).expect("hover model");
@@ -929,7 +1478,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
         let j1 = serde_json::to_string(&m1).unwrap();
         let j2 = serde_json::to_string(&m2).unwrap();
         assert_eq!(j1, j2);
-        assert!(j1.as_bytes().len() <= m1.limits.max_json_bytes);
+        assert!(j1.len() <= m1.limits.max_json_bytes);
     }
 
     #[test]
@@ -957,6 +1506,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
         let entity_md = render_markdown(&entity_model).markdown;
@@ -974,6 +1524,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
         let res_md = render_markdown(&res_model).markdown;
@@ -991,6 +1542,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
         let flow_md = render_markdown(&flow_model).markdown;
@@ -1025,6 +1577,7 @@ Flow "Widgets" from "Warehouse" to "Factory" quantity 5
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
 
@@ -1059,6 +1612,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
         assert!(core.related.is_empty());
@@ -1072,6 +1626,7 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
         assert!(!standard.related.is_empty());
@@ -1099,6 +1654,7 @@ Entity "Warehouse"
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         });
         assert!(model.is_none());
     }
@@ -1131,6 +1687,7 @@ Policy all_named per Constraint Obligation priority 5 as:
             line_index: &line_index,
             index: &index,
             graph: Some(&graph),
+            budget: HoverBudget::default(),
         })
         .unwrap();
 
@@ -1143,4 +1700,423 @@ Policy all_named per Constraint Obligation priority 5 as:
         assert!(model.primary.facts.iter().any(|(k, _)| k == "modality"));
         assert!(model.primary.facts.iter().any(|(k, _)| k == "kind"));
     }
+
+    #[test]
+    fn moniker_for_entity_uses_qualified_name_and_graph_id() {
+        let source = r#"
+Entity "Warehouse" in logistics
+Entity "Factory" in logistics
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let offset = source.find("\"Warehouse\"").unwrap() + 2;
+        let position = line_index.position_of(offset);
+        let moniker = build_moniker(&line_index, position, &index, Some(&graph)).expect("moniker");
+
+        let entity = graph
+            .all_entities()
+            .into_iter()
+            .find(|e| e.name() == "Warehouse")
+            .unwrap();
+        assert_eq!(moniker.scheme, "domainforge");
+        assert_eq!(moniker.identifier, "logistics::Warehouse");
+        assert_eq!(moniker.graph_id, entity.id().to_string());
+        assert_eq!(moniker.kind, "Entity");
+        assert_eq!(moniker.resolution_confidence, "exact");
+    }
+
+    #[test]
+    fn resolution_matches_graph_id_or_is_unresolved() {
+        let source = r#"
+Entity "Warehouse" in logistics
+Instance depot of "Ghost"
+"#;
+        let graph = sea_core::parse_to_graph("Entity \"Warehouse\" in logistics\n").unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+        let entity = graph.all_entities().into_iter().next().unwrap();
+
+        let at = |needle: &str| line_index.position_of(source.find(needle).unwrap() + 2);
+        let exact = build_resolution(&line_index, at("\"Warehouse\""), &index, Some(&graph))
+            .expect("resolution");
+        assert_eq!(
+            exact,
+            SymbolResolution {
+                resolve_id: entity.id().to_string(),
+                qualified_name: "logistics::Warehouse".to_string(),
+                confidence: "exact".to_string(),
+            }
+        );
+
+        let undefined = build_resolution(&line_index, at("\"Ghost\""), &index, Some(&graph))
+            .expect("resolution");
+        assert_eq!(undefined.resolve_id, "<unresolved>");
+        assert_eq!(undefined.qualified_name, "Ghost");
+    }
+
+    /// Hover model for `source` at byte `offset`, with the graph when it parses.
+    fn hover_at(source: &str, offset: usize, detail_level: DetailLevel) -> Option<HoverModel> {
+        let graph = sea_core::parse_to_graph(source).ok();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+        let uri = Url::parse("file:///test.sea").unwrap();
+        build_hover_model(HoverBuildInput {
+            uri: &uri,
+            document_version: 1,
+            position: line_index.position_of(offset),
+            config_hash: "cfg",
+            detail_level,
+            line_index: &line_index,
+            index: &index,
+            graph: graph.as_ref(),
+            budget: HoverBudget::default(),
+        })
+    }
+
+    /// Hover model just inside the first occurrence of `needle`.
+    fn model_at(source: &str, needle: &str, detail_level: DetailLevel) -> HoverModel {
+        let offset = source.find(needle).expect(needle) + 1;
+        hover_at(source, offset, detail_level).expect("hover model")
+    }
+
+    /// Value of the primary fact `key`.
+    fn fact(model: &HoverModel, key: &str) -> Option<String> {
+        model
+            .primary
+            .facts
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    }
+
+    #[test]
+    fn quick_info_matches_the_full_hover() {
+        let source = r#"
+Entity "Warehouse" in logistics
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Warehouse" quantity 5
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        for needle in ["\"Warehouse\"", "\"Cameras\" units", "Flow"] {
+            let position = line_index.position_of(source.find(needle).unwrap() + 1);
+            let quick =
+                build_quick_info(&line_index, position, &index, Some(&graph)).expect("quick info");
+            let model = model_at(source, needle, DetailLevel::Standard);
+
+            assert_eq!(
+                quick.signature, model.primary.signature_or_shape,
+                "{needle}"
+            );
+            assert_eq!(
+                quick.qualified_name, model.symbol.qualified_name,
+                "{needle}"
+            );
+            assert_eq!(quick.kind, model.symbol.kind, "{needle}");
+        }
+    }
+
+    #[test]
+    fn namespaced_entity_relates_to_its_namespace_declaration() {
+        let source = r#"@namespace "logistics"
+
+Entity "Warehouse" in logistics
+"#;
+        let model = model_at(source, "\"Warehouse\"", DetailLevel::Standard);
+        let namespace = &model.related[0];
+        assert_eq!(namespace.qualified_name, "logistics");
+        assert_eq!(namespace.kind, "Namespace");
+        let range = namespace
+            .range
+            .as_ref()
+            .expect("namespace declaration range");
+        assert_eq!((range.start.line, range.start.character), (0, 12));
+        assert!(render_markdown(&model)
+            .markdown
+            .contains("[logistics](file:///test.sea#L1) (Namespace)"));
+
+        let core = model_at(source, "\"Warehouse\"", DetailLevel::Core);
+        assert!(core.related.iter().all(|r| r.kind != "Namespace"));
+    }
+
+    #[test]
+    fn entity_hover_lists_patterns_its_policies_use() {
+        let source = r#"Entity "Vendor"
+Entity "Vendors"
+Pattern "Email" matches ".*@.*"
+Pattern "Phone" matches "[0-9]+"
+Policy vendor_email as: "Vendor" matches "Email"
+Policy others as: "Vendors" matches "Phone"
+"#;
+        let model = model_at(source, "\"Vendor\"", DetailLevel::Standard);
+        assert_eq!(fact(&model, "constrained_by").as_deref(), Some("Email"));
+        let core = model_at(source, "\"Vendor\"", DetailLevel::Core);
+        assert!(fact(&core, "constrained_by").is_none());
+    }
+
+    #[test]
+    fn hovering_annotations_describes_namespace_and_version() {
+        let source = r#"@namespace "logistics"
+@version "1.4.2-beta"
+
+Entity "Warehouse"
+Resource "Cameras" units
+Entity "Clerk" in staffing
+"#;
+        let namespace = model_at(source, "@namespace", DetailLevel::Standard);
+        assert_eq!(namespace.symbol.kind, "Namespace");
+        assert_eq!(namespace.symbol.qualified_name, "logistics");
+        assert_eq!(fact(&namespace, "declarations").as_deref(), Some("2"));
+
+        let version = model_at(source, "@version", DetailLevel::Standard);
+        assert_eq!(version.symbol.kind, "Version");
+        assert_eq!(fact(&version, "major").as_deref(), Some("1"));
+        assert_eq!(fact(&version, "minor").as_deref(), Some("4"));
+        assert_eq!(fact(&version, "patch").as_deref(), Some("2"));
+        assert_eq!(fact(&version, "pre_release").as_deref(), Some("beta"));
+        assert!(render_markdown(&version).markdown.contains("1.4.2-beta"));
+    }
+
+    #[test]
+    fn stale_models_keep_the_lower_confidence() {
+        let source = "Entity \"Depot\" in north\nEntity \"Depot\" in south\nEntity \"Yard\"\n";
+
+        let mut ambiguous = model_at(source, "\"Depot\"", DetailLevel::Standard);
+        assert_eq!(ambiguous.symbol.resolution_confidence, "ambiguous");
+        mark_stale(&mut ambiguous);
+        assert_eq!(ambiguous.symbol.resolution_confidence, "ambiguous");
+        assert!(ambiguous.primary.badges.contains(&"stale".to_string()));
+
+        let mut exact = model_at(source, "\"Yard\"", DetailLevel::Standard);
+        assert_eq!(exact.symbol.resolution_confidence, "exact");
+        mark_stale(&mut exact);
+        assert_eq!(exact.symbol.resolution_confidence, "stale");
+    }
+
+    #[test]
+    fn semver_needs_three_numeric_parts() {
+        assert_eq!(parse_semver("2.0.11"), Some((2, 0, 11, None)));
+        assert_eq!(
+            parse_semver("1.0.0-rc.1+build"),
+            Some((1, 0, 0, Some("rc.1")))
+        );
+        assert_eq!(parse_semver("1.0"), None);
+        assert_eq!(parse_semver("1.0.0.0"), None);
+        assert_eq!(parse_semver("v1.0.0"), None);
+    }
+
+    #[test]
+    fn hover_without_graph_uses_recovered_index_facts() {
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+Entity Broken"
+"#;
+        assert!(sea_core::parse_to_graph(source).is_err());
+        let model = model_at(source, "\"Warehouse\"", DetailLevel::Standard);
+
+        assert_eq!(model.symbol.kind, "Entity");
+        assert_eq!(model.symbol.name, "Warehouse");
+        assert_eq!(model.symbol.resolution_confidence, "error_fallback");
+        assert_eq!(fact(&model, "declared").as_deref(), Some("in this file"));
+        assert_eq!(fact(&model, "flows_from").as_deref(), Some("1"));
+        assert_eq!(fact(&model, "flows_to").as_deref(), Some("0"));
+        assert!(model
+            .related
+            .iter()
+            .any(|r| r.qualified_name == "Cameras" && r.kind == "Resource"));
+    }
+
+    #[test]
+    fn standard_hover_counts_in_file_references() {
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory"
+Instance depot of "Warehouse"
+"#;
+        let standard = model_at(source, "\"Warehouse\"", DetailLevel::Standard);
+        assert_eq!(fact(&standard, "references").as_deref(), Some("2"));
+        let core = model_at(source, "\"Warehouse\"", DetailLevel::Core);
+        assert!(fact(&core, "references").is_none());
+    }
+
+    #[test]
+    fn deep_instance_hover_lists_field_values() {
+        let fields: String = (0..12).map(|i| format!("  f{:02}: {}\n", i, i)).collect();
+        let source = format!(
+            "Entity \"Vendor\"\nInstance acme of \"Vendor\" {{\n  name: \"Acme\"\n}}\nInstance big of \"Vendor\" {{\n{fields}}}\n"
+        );
+
+        let deep = model_at(&source, "acme", DetailLevel::Deep);
+        assert!(fact(&deep, "field.name").is_some_and(|v| v.contains("Acme")));
+        assert!(deep.limits.truncated_sections.is_empty());
+        let standard = model_at(&source, "acme", DetailLevel::Standard);
+        assert!(!standard
+            .primary
+            .facts
+            .iter()
+            .any(|(k, _)| k.starts_with("field.")));
+
+        let big = model_at(&source, "big", DetailLevel::Deep);
+        let listed = big
+            .primary
+            .facts
+            .iter()
+            .filter(|(k, _)| k.starts_with("field."))
+            .count();
+        assert_eq!(listed, MAX_FIELD_FACTS);
+        assert!(big
+            .limits
+            .truncated_sections
+            .contains(&"fields".to_string()));
+    }
+
+    #[test]
+    fn deep_resource_hover_sums_flow_quantities() {
+        let source = r#"Entity "Farm"
+Entity "Mill"
+Resource "Grain" kg
+Flow "Grain" from "Farm" to "Mill" quantity 40
+Flow "Grain" from "Farm" to "Mill" quantity 2.5
+"#;
+        let total = |source: &str, detail_level| {
+            let model = model_at(source, "\"Grain\"", detail_level);
+            let partial = model
+                .limits
+                .truncated_sections
+                .contains(&"partial".to_string());
+            (fact(&model, "total_quantity"), partial)
+        };
+
+        assert_eq!(
+            total(source, DetailLevel::Deep),
+            (Some("42.5".to_string()), false)
+        );
+        assert_eq!(total(source, DetailLevel::Standard).0, None);
+
+        let unquantified = format!("{}Flow \"Grain\" from \"Mill\" to \"Farm\"\n", source);
+        assert_eq!(
+            total(&unquantified, DetailLevel::Deep),
+            (Some("42.5".to_string()), true)
+        );
+
+        let separated = format!(
+            "{}Flow \"Grain\" from \"Mill\" to \"Farm\" quantity 1_000 kg\n",
+            source
+        );
+        assert_eq!(
+            total(&separated, DetailLevel::Deep),
+            (Some("1042.5".to_string()), false)
+        );
+
+        let mixed = format!(
+            "{}Flow \"Grain\" from \"Mill\" to \"Farm\" quantity 5 g\n",
+            source
+        );
+        assert_eq!(
+            total(&mixed, DetailLevel::Deep),
+            (Some("42.5".to_string()), true)
+        );
+    }
+
+    #[test]
+    fn doc_comment_replaces_the_generic_summary() {
+        let source = r#"// Regional distribution hub
+Entity "Warehouse"
+Entity "Factory"
+"#;
+        let summary_at = |needle| {
+            model_at(source, needle, DetailLevel::Standard)
+                .primary
+                .summary
+        };
+
+        assert!(summary_at("\"Warehouse\"").contains("Regional distribution hub"));
+        assert_eq!(summary_at("\"Factory\""), "DomainForge entity");
+    }
+
+    #[test]
+    fn flow_endpoint_hover_notes_the_enclosing_flow() {
+        let source = r#"Entity "Supplier"
+Entity "Warehouse"
+Resource "Cameras" units
+Flow "Cameras" from "Supplier" to "Warehouse"
+"#;
+        let endpoint = model_at(source, "\"Supplier\" to", DetailLevel::Standard);
+        assert_eq!(endpoint.symbol.kind, "Entity");
+        assert_eq!(
+            fact(&endpoint, "enclosing").as_deref(),
+            Some("Flow Supplier -> Warehouse (Cameras)")
+        );
+
+        let declaration = model_at(source, "\"Supplier\"", DetailLevel::Standard);
+        assert!(fact(&declaration, "enclosing").is_none());
+    }
+
+    #[test]
+    fn declared_at_points_at_the_definition_from_a_reference() {
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory"
+"#;
+        let declared_at = |detail_level| {
+            fact(
+                &model_at(source, "\"Cameras\" from", detail_level),
+                "declared_at",
+            )
+        };
+
+        assert_eq!(declared_at(DetailLevel::Standard).as_deref(), Some("4:10"));
+        assert_eq!(declared_at(DetailLevel::Core), None);
+    }
+
+    #[test]
+    fn entity_hover_facts_with_graph_are_unchanged_by_index_threading() {
+        let source = r#"
+@namespace "logistics"
+Entity "Warehouse"
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory"
+"#;
+        let model = model_at(source, "\"Warehouse\"", DetailLevel::Core);
+
+        let keys: Vec<&str> = model
+            .primary
+            .facts
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(keys, vec!["namespace", "flows_from", "flows_to"]);
+        assert_eq!(model.primary.facts[0].1, "logistics");
+    }
+
+    #[test]
+    fn unresolved_reference_yields_fallback_model_and_whitespace_yields_none() {
+        let source = r#"
+Entity "Warehouse"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Ghost"
+"#;
+        let model = model_at(source, "\"Ghost\"", DetailLevel::Standard);
+        assert_eq!(model.symbol.kind, "Entity");
+        assert_eq!(model.symbol.name, "Ghost");
+        assert_eq!(model.symbol.resolution_confidence, "error_fallback");
+        assert!(model.primary.badges.contains(&"unresolved".to_string()));
+
+        assert!(
+            hover_at(source, 0, DetailLevel::Standard).is_none(),
+            "leading newline is whitespace"
+        );
+    }
 }
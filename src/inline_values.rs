@@ -0,0 +1,115 @@
+//! Inline values for `textDocument/inlineValue`: the field values of instances
+//! referenced from policy expressions.
+
+use sea_core::Graph;
+use tower_lsp::lsp_types::{InlineValue, InlineValueText, Range};
+
+use crate::line_index::LineIndex;
+use crate::semantic_index::{SemanticIndex, SymbolKind};
+
+/// Inline values for `@instance` references inside policies overlapping `range`.
+///
+/// An instance with a single field shows just its value (`@vendor_123 ⟹ "Acme"`);
+/// one with several lists them as `field: value`. Instances without fields, or that
+/// the graph doesn't know, get no value.
+pub fn inline_values(
+    line_index: &LineIndex,
+    index: &SemanticIndex,
+    graph: &Graph,
+    range: Range,
+) -> Vec<InlineValue> {
+    let start = line_index.offset_of(range.start).unwrap_or(0);
+    let end = line_index.offset_of(range.end).unwrap_or(usize::MAX);
+
+    let policies: Vec<_> = index
+        .declarations
+        .iter()
+        .filter(|decl| decl.kind == SymbolKind::Policy)
+        .filter(|decl| decl.range.start <= end && start <= decl.range.end)
+        .map(|decl| decl.range)
+        .collect();
+
+    index
+        .occurrences
+        .iter()
+        .filter(|occ| occ.kind == SymbolKind::Instance && !occ.is_definition)
+        .filter(|occ| occ.range.start <= end && start <= occ.range.end)
+        .filter(|occ| {
+            policies
+                .iter()
+                .any(|p| p.start <= occ.range.start && occ.range.end <= p.end)
+        })
+        .filter_map(|occ| {
+            let instance = graph.get_entity_instance(&occ.name)?;
+            let fields = instance.fields();
+            let text = match fields.len() {
+                0 => return None,
+                1 => fields.values().next()?.to_string(),
+                _ => fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            Some(InlineValue::Text(InlineValueText {
+                range: Range {
+                    start: line_index.position_of(occ.range.start),
+                    end: line_index.position_of(occ.range.end),
+                },
+                text: format!("⟹ {}", text),
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn instance_reference_in_a_policy_shows_its_field() {
+        let source = r#"Entity "Vendor"
+Instance vendor_123 of "Vendor" {
+  name: "Acme"
+}
+Policy known_vendor as: @vendor_123 = @vendor_123
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+        let whole = Range {
+            start: Position::new(0, 0),
+            end: line_index.position_of(source.len()),
+        };
+
+        let values = inline_values(&line_index, &index, &graph, whole);
+
+        assert_eq!(values.len(), 2);
+        let InlineValue::Text(first) = &values[0] else {
+            panic!("expected text inline value");
+        };
+        assert_eq!(first.text, "⟹ \"Acme\"");
+        assert_eq!(first.range.start, Position::new(4, 24));
+        assert_eq!(first.range.end, Position::new(4, 35));
+    }
+
+    #[test]
+    fn references_outside_the_requested_range_are_skipped() {
+        let source = r#"Entity "Vendor"
+Instance vendor_123 of "Vendor" {
+  name: "Acme"
+}
+Policy known_vendor as: @vendor_123 = @vendor_123
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+        let header = Range {
+            start: Position::new(0, 0),
+            end: Position::new(1, 0),
+        };
+
+        assert!(inline_values(&line_index, &index, &graph, header).is_empty());
+    }
+}
@@ -3,10 +3,19 @@ pub mod capabilities;
 pub mod code_actions;
 pub mod completion;
 pub mod diagnostics;
+pub mod entity_fields;
+pub mod flow_graph;
+pub mod folding;
 pub mod formatting;
+pub mod graph_diff;
+pub mod graph_stats;
 pub mod hover;
+pub mod inline_values;
 pub mod line_index;
+pub mod logging;
 pub mod navigation;
+pub mod outline;
 pub mod semantic_index;
+pub mod workspace;
 // MCP module is NOT part of the library, it's a separate binary.
 // But we might want to share MCP types if we were doing in-process, but here we are doing separate bin.
@@ -1,6 +1,6 @@
-use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::{Position, Range};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LineIndex {
     line_starts: Vec<usize>,
     text_len: usize,
@@ -21,6 +21,40 @@ impl LineIndex {
         }
     }
 
+    /// Update the index for `range` of the indexed text being replaced by `new_text`,
+    /// as an incremental `didChange` does, without rescanning the whole document.
+    ///
+    /// Returns `false`, leaving the index untouched, when `range` is not within the
+    /// text.
+    pub fn apply_edit(&mut self, range: Range, new_text: &str) -> bool {
+        let (Some(start), Some(end)) = (self.offset_of(range.start), self.offset_of(range.end))
+        else {
+            return false;
+        };
+        if start > end {
+            return false;
+        }
+
+        // Line i starts after the newline at line_starts[i] - 1, so the starts in
+        // (start, end] belong to newlines that are deleted.
+        let first_removed = self.line_starts.partition_point(|&s| s <= start);
+        let first_kept = self.line_starts.partition_point(|&s| s <= end);
+        let inserted = new_text
+            .bytes()
+            .enumerate()
+            .filter(|(_, b)| *b == b'\n')
+            .map(|(idx, _)| start + idx + 1);
+        let shifted: Vec<usize> = self.line_starts[first_kept..]
+            .iter()
+            .map(|&s| s - end + start + new_text.len())
+            .collect();
+        self.line_starts.truncate(first_removed);
+        self.line_starts.extend(inserted);
+        self.line_starts.extend(shifted);
+        self.text_len = self.text_len - (end - start) + new_text.len();
+        true
+    }
+
     pub fn offset_of(&self, position: Position) -> Option<usize> {
         let line = usize::try_from(position.line).ok()?;
         let character = usize::try_from(position.character).ok()?;
@@ -48,3 +82,69 @@ impl LineIndex {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// xorshift64, so the edits are random but reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        fn text(&mut self, max_len: usize) -> String {
+            let len = self.below(max_len + 1);
+            (0..len).map(|_| ['a', 'b', '\n'][self.below(3)]).collect()
+        }
+    }
+
+    #[test]
+    fn apply_edit_matches_a_fresh_index_for_random_edits() {
+        let mut rng = Rng(0x5eed_cafe_f00d_d00d);
+        for _ in 0..2000 {
+            let text = rng.text(24);
+            let mut a = rng.below(text.len() + 1);
+            let mut b = rng.below(text.len() + 1);
+            if a > b {
+                std::mem::swap(&mut a, &mut b);
+            }
+            let new_text = rng.text(6);
+
+            let mut index = LineIndex::new(&text);
+            let range = Range {
+                start: index.position_of(a),
+                end: index.position_of(b),
+            };
+            assert!(index.apply_edit(range, &new_text));
+
+            let edited = format!("{}{}{}", &text[..a], new_text, &text[b..]);
+            assert_eq!(
+                index,
+                LineIndex::new(&edited),
+                "replacing {a}..{b} of {text:?} with {new_text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn apply_edit_rejects_ranges_outside_the_text() {
+        let mut index = LineIndex::new("one\ntwo\n");
+        let before = index.clone();
+        let past_end = Position {
+            line: 7,
+            character: 0,
+        };
+        let range = Range {
+            start: past_end,
+            end: past_end,
+        };
+        assert!(!index.apply_edit(range, "x"));
+        assert_eq!(index, before);
+    }
+}
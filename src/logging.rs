@@ -0,0 +1,92 @@
+//! Logger for the language server binary.
+//!
+//! RUST_LOG is parsed by env_logger as usual. The `logLevel` initialization option
+//! then replaces only its global level, so module directives such as
+//! `tower_lsp=off` keep applying.
+
+use std::sync::{OnceLock, RwLock};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+static LOGGER: OnceLock<ReloadableLogger> = OnceLock::new();
+
+/// An env_logger whose filter can be rebuilt after startup.
+struct ReloadableLogger {
+    inner: RwLock<env_logger::Logger>,
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner
+            .read()
+            .is_ok_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Ok(logger) = self.inner.read() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(logger) = self.inner.read() {
+            logger.flush();
+        }
+    }
+}
+
+/// Install the logger configured from RUST_LOG.
+pub fn init() {
+    let logger = LOGGER.get_or_init(|| ReloadableLogger {
+        inner: RwLock::new(build(env_logger::Builder::from_default_env(), None)),
+    });
+    if log::set_logger(logger).is_ok() {
+        if let Ok(inner) = logger.inner.read() {
+            log::set_max_level(inner.filter());
+        }
+    }
+}
+
+/// Replace the global level RUST_LOG set, keeping its module directives. Without
+/// an installed logger (as in tests) only `log::max_level` changes.
+pub fn set_level(level: LevelFilter) {
+    let Some(logger) = LOGGER.get() else {
+        log::set_max_level(level);
+        return;
+    };
+    let rebuilt = build(env_logger::Builder::from_default_env(), Some(level));
+    log::set_max_level(rebuilt.filter());
+    if let Ok(mut inner) = logger.inner.write() {
+        *inner = rebuilt;
+    }
+}
+
+fn build(mut builder: env_logger::Builder, level: Option<LevelFilter>) -> env_logger::Logger {
+    if let Some(level) = level {
+        builder.filter_level(level);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn enabled(logger: &env_logger::Logger, target: &str, level: Level) -> bool {
+        logger.enabled(&Metadata::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn global_level_keeps_module_directives() {
+        let mut builder = env_logger::Builder::new();
+        builder.parse_filters("warn,tower_lsp=off,sea_core=trace");
+        let logger = build(builder, Some(LevelFilter::Debug));
+
+        assert!(enabled(&logger, "domainforge_lsp::backend", Level::Debug));
+        assert!(!enabled(&logger, "domainforge_lsp::backend", Level::Trace));
+        assert!(!enabled(&logger, "tower_lsp::service", Level::Error));
+        assert!(enabled(&logger, "sea_core::parser", Level::Trace));
+        assert_eq!(logger.filter(), LevelFilter::Trace);
+    }
+}
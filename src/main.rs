@@ -3,19 +3,98 @@
 //! This is a thin wrapper around `sea-core` that provides Language Server Protocol support
 //! for the SEA DSL. It handles JSON-RPC communication and delegates all actual work to sea-core.
 
+use clap::Parser;
 use tower_lsp::{LspService, Server};
 
 use domainforge_lsp::backend::Backend;
 
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Talk LSP over stdin/stdout (the default).
+    #[arg(long, conflicts_with_all = ["socket", "pipe"])]
+    stdio: bool,
+
+    /// Listen on 127.0.0.1:<PORT> and serve the first client that connects.
+    #[arg(long, value_name = "PORT", conflicts_with = "pipe")]
+    socket: Option<u16>,
+
+    /// Listen on a Unix domain socket at <PATH> and serve the first client that connects.
+    #[arg(long, value_name = "PATH")]
+    pipe: Option<std::path::PathBuf>,
+}
+
 #[tokio::main]
-async fn main() {
-    env_logger::init();
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
 
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
+    domainforge_lsp::logging::init();
 
     let (service, socket) = LspService::build(Backend::new)
         .custom_method("textDocument/hoverPlus", Backend::hover_plus)
+        .custom_method("sea/moniker", Backend::moniker)
+        .custom_method("sea/resolve", Backend::resolve)
+        .custom_method("sea/quickInfo", Backend::quick_info)
+        .custom_method("sea/breadcrumb", Backend::breadcrumb)
+        .custom_method("sea/flows", Backend::flows)
+        .custom_method("sea/neighborhood", Backend::neighborhood)
+        .custom_method("sea/whatProvides", Backend::what_provides)
+        .custom_method("sea/outline", Backend::outline)
+        .custom_method("sea/dot", Backend::dot)
+        .custom_method("sea/graphStats", Backend::graph_stats)
+        .custom_method("sea/graphDiff", Backend::graph_diff)
+        .custom_method("sea/diagnosticsAll", Backend::diagnostics_all)
+        .custom_method("sea/formatCheck", Backend::format_check)
+        .custom_method("sea/references", Backend::graph_references)
+        .custom_method("sea/rename/validate", Backend::validate_rename)
+        .custom_method("sea/workspaceReferences", Backend::workspace_references)
+        .custom_method("$/setTrace", Backend::set_trace)
         .finish();
-    Server::new(stdin, stdout, socket).serve(service).await;
+
+    if let Some(port) = args.socket {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+        log::info!("Listening on {}", listener.local_addr()?);
+        let (stream, _) = listener.accept().await?;
+        let (read, write) = tokio::io::split(stream);
+        Server::new(read, write, socket).serve(service).await;
+    } else if let Some(path) = args.pipe {
+        serve_pipe(&path, service, socket).await?;
+    } else {
+        Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+            .serve(service)
+            .await;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn serve_pipe(
+    path: &std::path::Path,
+    service: LspService<Backend>,
+    socket: tower_lsp::ClientSocket,
+) -> anyhow::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // A socket left behind by an earlier run makes bind fail; remove it unless a
+    // server still answers on it.
+    let stale = std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket())
+        && std::os::unix::net::UnixStream::connect(path).is_err();
+    if stale {
+        std::fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    log::info!("Listening on {}", path.display());
+    let (stream, _) = listener.accept().await?;
+    let (read, write) = tokio::io::split(stream);
+    Server::new(read, write, socket).serve(service).await;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn serve_pipe(
+    _path: &std::path::Path,
+    _service: LspService<Backend>,
+    _socket: tower_lsp::ClientSocket,
+) -> anyhow::Result<()> {
+    anyhow::bail!("--pipe needs Unix domain sockets, which this platform lacks")
 }
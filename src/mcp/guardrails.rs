@@ -5,12 +5,18 @@ use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
+/// Largest file, in bytes, the tools will hand to an agent; matches the LSP's default
+/// `maxDocumentBytes`.
+pub const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
 /// Security and Stability Guardrails for the MCP Server
 pub struct Guard {
     /// Allowed workspace roots. Access to files outside these roots is denied.
     allowed_roots: Vec<PathBuf>,
     /// Rate limiters per tool type
     rate_limiters: Arc<Mutex<HashMap<String, RateLimiter>>>,
+    /// Extensions of the model files tools list, without the dot
+    file_extensions: Vec<String>,
 }
 
 impl Guard {
@@ -24,7 +30,26 @@ impl Guard {
         Self {
             allowed_roots,
             rate_limiters: Arc::new(Mutex::new(HashMap::new())),
+            file_extensions: vec![domainforge_lsp::workspace::DEFAULT_FILE_EXTENSION.to_string()],
+        }
+    }
+
+    /// List files with these extensions instead of `.sea`; an empty list keeps `.sea`.
+    pub fn with_file_extensions(mut self, extensions: Vec<String>) -> Self {
+        if !extensions.is_empty() {
+            self.file_extensions = extensions;
         }
+        self
+    }
+
+    /// Extensions of the model files tools list.
+    pub fn file_extensions(&self) -> &[String] {
+        &self.file_extensions
+    }
+
+    /// The canonicalized workspace roots files may be read from.
+    pub fn allowed_roots(&self) -> &[PathBuf] {
+        &self.allowed_roots
     }
 
     /// Verifies that a path is within the allowed workspace roots.
@@ -60,8 +85,11 @@ impl Guard {
                 "domainforge/diagnostics" => RateLimiter::new(10, Duration::from_secs(1)),
                 "domainforge/definition" => RateLimiter::new(10, Duration::from_secs(1)),
                 "domainforge/references" => RateLimiter::new(5, Duration::from_secs(1)),
+                "domainforge/workspace-references" => RateLimiter::new(2, Duration::from_secs(1)),
                 "domainforge/code-actions" => RateLimiter::new(5, Duration::from_secs(1)),
+                "domainforge/workspace-symbols" => RateLimiter::new(5, Duration::from_secs(1)),
                 "domainforge/rename-preview" => RateLimiter::new(2, Duration::from_secs(1)),
+                "domainforge/list-files" => RateLimiter::new(2, Duration::from_secs(1)),
                 _ => RateLimiter::new(10, Duration::from_secs(1)), // Default for unknown tools
             }
         });
@@ -138,6 +166,28 @@ mod tests {
         assert!(guard.check_path(&outside_file).is_err());
     }
 
+    #[test]
+    fn test_path_verification_across_roots() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let in_first = first.path().join("a.sea");
+        let in_second = second.path().join("b.sea");
+        let in_outside = outside.path().join("c.sea");
+        for path in [&in_first, &in_second, &in_outside] {
+            File::create(path).unwrap();
+        }
+
+        let guard = Guard::new(vec![
+            first.path().to_path_buf(),
+            second.path().to_path_buf(),
+        ]);
+
+        assert!(guard.check_path(&in_first).is_ok());
+        assert!(guard.check_path(&in_second).is_ok());
+        assert!(guard.check_path(&in_outside).is_err());
+    }
+
     #[test]
     fn test_rate_limiting() {
         // Create a guard with dummy root
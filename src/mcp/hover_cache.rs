@@ -0,0 +1,144 @@
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use lru::LruCache;
+use serde_json::Value;
+use tower_lsp::lsp_types::Url;
+
+/// Identifies a hover result: the same position in an unchanged file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HoverKey {
+    uri: String,
+    mtime: SystemTime,
+    line: u64,
+    character: u64,
+}
+
+impl HoverKey {
+    /// Build a key from the file's current mtime. Returns `None` when the URI is not a
+    /// readable local file, in which case hovers are never cached.
+    pub fn for_file(uri: &str, line: u64, character: u64) -> Option<Self> {
+        let path = Url::parse(uri).ok()?.to_file_path().ok()?;
+        let mtime = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        Some(Self {
+            uri: uri.to_string(),
+            mtime,
+            line,
+            character,
+        })
+    }
+}
+
+/// Bounded cache of proxied `textDocument/hover` responses.
+pub struct HoverCache {
+    entries: Mutex<LruCache<HoverKey, Value>>,
+}
+
+impl HoverCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Return the cached response for `key`, or run `fetch` and cache its result.
+    ///
+    /// A `None` key bypasses the cache entirely.
+    pub async fn get_or_fetch<F, Fut>(
+        &self,
+        key: Option<HoverKey>,
+        fetch: F,
+    ) -> anyhow::Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Value>>,
+    {
+        let Some(key) = key else {
+            return fetch().await;
+        };
+        if let Some(hit) = self.entries.lock().unwrap().get(&key).cloned() {
+            return Ok(hit);
+        }
+
+        let value = fetch().await?;
+        let mut entries = self.entries.lock().unwrap();
+        // Entries for an older mtime of the same file can never hit again.
+        let stale: Vec<HoverKey> = entries
+            .iter()
+            .filter(|(k, _)| k.uri == key.uri && k.mtime != key.mtime)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for k in stale {
+            entries.pop(&k);
+        }
+        entries.put(key, value.clone());
+        Ok(value)
+    }
+
+    /// Drop every cached hover for `uri`, e.g. when the server republishes diagnostics.
+    pub fn invalidate(&self, uri: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        let keys: Vec<HoverKey> = entries
+            .iter()
+            .filter(|(k, _)| k.uri == uri)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for k in keys {
+            entries.pop(&k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_identical_hovers_on_unchanged_file_fetch_once() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "Entity \"Warehouse\"\n").unwrap();
+        let uri = format!("file://{}", file.path().display());
+
+        let cache = HoverCache::new(NonZeroUsize::new(8).unwrap());
+        let downstream = AtomicUsize::new(0);
+        let fetch = || async {
+            downstream.fetch_add(1, Ordering::SeqCst);
+            Ok(json!({ "contents": "Warehouse" }))
+        };
+
+        let first = cache
+            .get_or_fetch(HoverKey::for_file(&uri, 0, 8), fetch)
+            .await
+            .unwrap();
+        let second = cache
+            .get_or_fetch(HoverKey::for_file(&uri, 0, 8), fetch)
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(downstream.load(Ordering::SeqCst), 1);
+
+        cache.invalidate(&uri);
+        cache
+            .get_or_fetch(HoverKey::for_file(&uri, 0, 8), fetch)
+            .await
+            .unwrap();
+        assert_eq!(downstream.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_percent_encoded_file_uris_are_keyed() {
+        let file = tempfile::Builder::new()
+            .prefix("hover cache ")
+            .tempfile()
+            .unwrap();
+        let uri = Url::from_file_path(file.path()).unwrap();
+        assert!(uri.as_str().contains("%20"));
+
+        assert!(HoverKey::for_file(uri.as_str(), 0, 0).is_some());
+        assert!(HoverKey::for_file("untitled:Untitled-1", 0, 0).is_none());
+    }
+}
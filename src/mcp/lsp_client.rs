@@ -1,11 +1,15 @@
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
+use tower_lsp::lsp_types::Url;
+
+use crate::hover_cache::{HoverCache, HoverKey};
 
 /// Type alias for pending request map to reduce type complexity
 type PendingRequests = Arc<Mutex<HashMap<i64, oneshot::Sender<anyhow::Result<Value>>>>>;
@@ -16,6 +20,7 @@ pub struct LspClient {
     sender: mpsc::Sender<Value>,
     pending_requests: PendingRequests,
     pub diagnostics_cache: Arc<RwLock<HashMap<String, Vec<Value>>>>, // URI -> Diagnostics list
+    hover_cache: Arc<HoverCache>,
 }
 
 impl LspClient {
@@ -39,6 +44,9 @@ impl LspClient {
         let pending_requests: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
 
         let diagnostics_cache = Arc::new(RwLock::new(HashMap::new()));
+        let hover_cache = Arc::new(HoverCache::new(
+            NonZeroUsize::new(256).expect("non-zero hover cache size"),
+        ));
 
         // Writer task
         let mut stdin = stdin;
@@ -65,6 +73,7 @@ impl LspClient {
         // Reader task
         let pending_requests_clone = pending_requests.clone();
         let diagnostics_cache_clone = diagnostics_cache.clone();
+        let hover_cache_clone = hover_cache.clone();
         tokio::spawn(async move {
             let mut reader = BufReader::new(stdout);
             loop {
@@ -111,6 +120,8 @@ impl LspClient {
                                                 let mut cache =
                                                     diagnostics_cache_clone.write().await;
                                                 cache.insert(uri.to_string(), diags.clone());
+                                                // New diagnostics mean the document changed.
+                                                hover_cache_clone.invalidate(uri);
                                             }
                                         }
                                     }
@@ -154,12 +165,25 @@ impl LspClient {
             sender: tx,
             pending_requests,
             diagnostics_cache,
+            hover_cache,
         })
     }
 
-    pub async fn initialize(&self, root_path: Option<String>) -> anyhow::Result<()> {
+    /// Initialize the LSP with `root_paths` as workspace folders; the first is also
+    /// sent as `rootUri`.
+    pub async fn initialize(&self, root_paths: &[String]) -> anyhow::Result<()> {
         let id = self.request_id.fetch_add(1, Ordering::SeqCst);
-        let root_uri = root_path.map(|p| format!("file://{}", p));
+        let root_uris: Vec<String> = root_paths
+            .iter()
+            .map(|p| match Url::from_file_path(p) {
+                Ok(url) => url.to_string(),
+                Err(()) => format!("file://{}", p),
+            })
+            .collect();
+        let workspace_folders: Vec<Value> = root_uris
+            .iter()
+            .map(|uri| json!({ "uri": uri, "name": uri.rsplit('/').next().unwrap_or(uri) }))
+            .collect();
 
         let req = json!({
             "jsonrpc": "2.0",
@@ -167,7 +191,8 @@ impl LspClient {
             "method": "initialize",
             "params": {
                 "processId": std::process::id(),
-                "rootUri": root_uri,
+                "rootUri": root_uris.first(),
+                "workspaceFolders": workspace_folders,
                 "capabilities": {}
             }
         });
@@ -189,17 +214,39 @@ impl LspClient {
     }
 
     pub async fn hover(&self, uri: &str, line: u64, character: u64) -> anyhow::Result<Value> {
+        let key = HoverKey::for_file(uri, line, character);
+        self.hover_cache
+            .get_or_fetch(key, || async {
+                let id = self.request_id.fetch_add(1, Ordering::SeqCst);
+                let req = json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "textDocument/hover",
+                    "params": {
+                        "textDocument": { "uri": uri },
+                        "position": { "line": line, "character": character }
+                    }
+                });
+
+                self.send_request(id, req).await
+            })
+            .await
+    }
+
+    /// Structured hover through the server's `textDocument/hoverPlus` extension.
+    /// Responses are not cached.
+    pub async fn hover_plus(&self, uri: &str, line: u64, character: u64) -> anyhow::Result<Value> {
         let id = self.request_id.fetch_add(1, Ordering::SeqCst);
         let req = json!({
             "jsonrpc": "2.0",
             "id": id,
-            "method": "textDocument/hover",
+            "method": "textDocument/hoverPlus",
             "params": {
-                "textDocument": { "uri": uri },
-                "position": { "line": line, "character": character }
+                "text_document": { "uri": uri },
+                "position": { "line": line, "character": character },
+                "include_markdown": false
             }
         });
-
         self.send_request(id, req).await
     }
 
@@ -238,6 +285,27 @@ impl LspClient {
         self.send_request(id, req).await
     }
 
+    pub async fn workspace_references(
+        &self,
+        uri: &str,
+        line: u64,
+        character: u64,
+        include_decl: bool,
+    ) -> anyhow::Result<Value> {
+        let id = self.request_id.fetch_add(1, Ordering::SeqCst);
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "sea/workspaceReferences",
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": line, "character": character },
+                "context": { "includeDeclaration": include_decl }
+            }
+        });
+        self.send_request(id, req).await
+    }
+
     pub async fn rename(
         &self,
         uri: &str,
@@ -259,6 +327,17 @@ impl LspClient {
         self.send_request(id, req).await
     }
 
+    pub async fn workspace_symbols(&self, query: &str) -> anyhow::Result<Value> {
+        let id = self.request_id.fetch_add(1, Ordering::SeqCst);
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "workspace/symbol",
+            "params": { "query": query }
+        });
+        self.send_request(id, req).await
+    }
+
     pub async fn code_action(&self, uri: &str, range: Value) -> anyhow::Result<Value> {
         let id = self.request_id.fetch_add(1, Ordering::SeqCst);
         let req = json!({
@@ -5,6 +5,7 @@
 //! to the LSP server/logic.
 
 mod guardrails;
+mod hover_cache;
 mod lsp_client;
 mod tools;
 mod transport;
@@ -18,9 +19,14 @@ struct Args {
     #[arg(long)]
     lsp_path: Option<String>,
 
-    /// Root path of the workspace to analyze.
+    /// Root path of the workspace to analyze. Repeat to allow several roots.
     #[arg(long)]
-    workspace_root: Option<String>,
+    workspace_root: Vec<String>,
+
+    /// Extension of the model files to list, without the dot. Repeat for several;
+    /// defaults to `sea`.
+    #[arg(long)]
+    file_extension: Vec<String>,
 }
 
 #[tokio::main]
@@ -34,13 +40,16 @@ async fn main() -> anyhow::Result<()> {
         .lsp_path
         .unwrap_or_else(|| "domainforge-lsp".to_string());
     let client = lsp_client::LspClient::new(&lsp_path).await?;
-    client.initialize(args.workspace_root.clone()).await?;
+    client.initialize(&args.workspace_root).await?;
 
     log::info!("LSP Client initialized, entering loop...");
 
     // Initialize Guard
-    let root_paths = if let Some(root) = &args.workspace_root {
-        vec![std::path::PathBuf::from(root)]
+    let root_paths = if !args.workspace_root.is_empty() {
+        args.workspace_root
+            .iter()
+            .map(std::path::PathBuf::from)
+            .collect()
     } else {
         // Default to CWD if no root provided
         match std::env::current_dir() {
@@ -54,7 +63,8 @@ async fn main() -> anyhow::Result<()> {
             }
         }
     };
-    let guard = std::sync::Arc::new(crate::guardrails::Guard::new(root_paths));
+    let guard = crate::guardrails::Guard::new(root_paths).with_file_extensions(args.file_extension);
+    let guard = std::sync::Arc::new(guard);
 
     // Basic stdio loop
     crate::transport::run_stdio_loop(&client, guard).await?;
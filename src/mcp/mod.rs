@@ -1,4 +1,5 @@
 pub mod guardrails;
+pub mod hover_cache;
 pub mod main;
 pub mod tools;
 pub mod transport;
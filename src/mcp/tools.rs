@@ -1,8 +1,16 @@
+use std::path::PathBuf;
+
+use domainforge_lsp::workspace::discover_sea_files;
+use tower_lsp::lsp_types::Url;
+
 use crate::lsp_client::LspClient;
 
 use serde_json::{json, Value};
 
-use crate::guardrails::Guard;
+use crate::guardrails::{Guard, MAX_FILE_BYTES};
+
+/// Most files `domainforge/list-files` returns in one call.
+const MAX_LISTED_FILES: usize = 1000;
 
 pub async fn handle_tool_call(
     name: &str,
@@ -18,9 +26,12 @@ pub async fn handle_tool_call(
         "domainforge/hover" => hover_tool(args, client, guard).await,
         "domainforge/definition" => definition_tool(args, client, guard).await,
         "domainforge/references" => references_tool(args, client, guard).await,
+        "domainforge/workspace-references" => workspace_references_tool(args, client, guard).await,
         "domainforge/diagnostics" => diagnostics_tool(args, client, guard).await,
         "domainforge/rename-preview" => rename_preview_tool(args, client, guard).await,
         "domainforge/code-actions" => code_action_tool(args, client, guard).await,
+        "domainforge/workspace-symbols" => workspace_symbols_tool(args, client).await,
+        "domainforge/list-files" => list_files_tool(args, guard),
         _ => Err(anyhow::anyhow!("Unknown tool: {}", name)),
     }
 }
@@ -28,7 +39,19 @@ pub async fn handle_tool_call(
 async fn hover_tool(args: Value, client: &LspClient, guard: &Guard) -> anyhow::Result<Value> {
     let uri = extract_uri(&args, guard)?;
     let (line, char) = extract_pos(&args)?;
-    client.hover(&uri, line, char).await
+    let format = args
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("markdown");
+    match format {
+        "markdown" => client.hover(&uri, line, char).await,
+        // The hoverPlus response wraps the model; agents asking for JSON want only it
+        "json" => {
+            let response = client.hover_plus(&uri, line, char).await?;
+            Ok(response.get("model").cloned().unwrap_or(Value::Null))
+        }
+        other => Err(anyhow::anyhow!("Unknown hover format: {}", other)),
+    }
 }
 
 async fn definition_tool(args: Value, client: &LspClient, guard: &Guard) -> anyhow::Result<Value> {
@@ -47,11 +70,80 @@ async fn references_tool(args: Value, client: &LspClient, guard: &Guard) -> anyh
     client.references(&uri, line, char, include_decl).await
 }
 
+async fn workspace_references_tool(
+    args: Value,
+    client: &LspClient,
+    guard: &Guard,
+) -> anyhow::Result<Value> {
+    let uri = extract_uri(&args, guard)?;
+    let (line, char) = extract_pos(&args)?;
+    let include_decl = args
+        .get("includeDeclaration")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let locations = client
+        .workspace_references(&uri, line, char, include_decl)
+        .await?;
+    Ok(group_by_file(&locations))
+}
+
+/// Groups LSP locations into `{ files: [{ uri, ranges }] }`, preserving the order in
+/// which files first appear.
+fn group_by_file(locations: &Value) -> Value {
+    let mut files: Vec<(String, Vec<Value>)> = Vec::new();
+    for location in locations.as_array().into_iter().flatten() {
+        let Some(uri) = location.get("uri").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let range = location.get("range").cloned().unwrap_or(Value::Null);
+        match files.iter_mut().find(|(u, _)| u == uri) {
+            Some((_, ranges)) => ranges.push(range),
+            None => files.push((uri.to_string(), vec![range])),
+        }
+    }
+    json!({
+        "files": files
+            .into_iter()
+            .map(|(uri, ranges)| json!({ "uri": uri, "ranges": ranges }))
+            .collect::<Vec<_>>()
+    })
+}
+
 async fn diagnostics_tool(args: Value, client: &LspClient, guard: &Guard) -> anyhow::Result<Value> {
     let uri = extract_uri(&args, guard)?;
+    let format = args.get("format").and_then(|v| v.as_str()).unwrap_or("lsp");
     let cache = client.diagnostics_cache.read().await;
     let diags = cache.get(&uri).cloned().unwrap_or_else(Vec::new);
-    Ok(json!(diags))
+    match format {
+        "lsp" => Ok(json!(diags)),
+        "compact" => Ok(compact_diagnostics(&diags)),
+        other => Err(anyhow::anyhow!("Unknown diagnostics format: {}", other)),
+    }
+}
+
+/// Reduce LSP diagnostics to `{ code, severity, line, message }` to save agent tokens.
+///
+/// `line` is the zero-based start line of the diagnostic's range.
+fn compact_diagnostics(diags: &[Value]) -> Value {
+    let compact: Vec<Value> = diags
+        .iter()
+        .map(|d| {
+            let severity = match d.get("severity").and_then(|s| s.as_u64()) {
+                Some(1) => "error",
+                Some(2) => "warning",
+                Some(3) => "information",
+                Some(4) => "hint",
+                _ => "error",
+            };
+            json!({
+                "code": d.get("code").cloned().unwrap_or(Value::Null),
+                "severity": severity,
+                "line": d.pointer("/range/start/line").cloned().unwrap_or(Value::Null),
+                "message": d.get("message").cloned().unwrap_or(Value::Null),
+            })
+        })
+        .collect();
+    json!(compact)
 }
 
 async fn rename_preview_tool(
@@ -86,14 +178,70 @@ async fn code_action_tool(args: Value, client: &LspClient, guard: &Guard) -> any
     client.code_action(&uri, range).await
 }
 
+async fn workspace_symbols_tool(args: Value, client: &LspClient) -> anyhow::Result<Value> {
+    let query = extract_query(&args)?;
+    client.workspace_symbols(query).await
+}
+
+/// Lists model files (`.sea` unless the guard names other extensions) under each
+/// allowed root (or `subdir` of it) as
+/// `{ files: [{ uri, path }], truncated }`, with `path` relative to its root.
+///
+/// Hidden directories and `target` are skipped, as are files the guard rejects or that
+/// exceed `MAX_FILE_BYTES`. At most `MAX_LISTED_FILES` are returned.
+fn list_files_tool(args: Value, guard: &Guard) -> anyhow::Result<Value> {
+    let subdir = args.get("subdir").and_then(|v| v.as_str());
+    let mut files = Vec::new();
+    let mut truncated = false;
+
+    for root in guard.allowed_roots() {
+        let base = match subdir {
+            Some(subdir) => guard.check_path(root.join(subdir))?,
+            None => root.clone(),
+        };
+        for path in discover_sea_files(&base, guard.file_extensions()) {
+            let Ok(path) = guard.check_path(&path) else {
+                continue;
+            };
+            if path.metadata().map_or(true, |m| m.len() > MAX_FILE_BYTES) {
+                continue;
+            }
+            if files.len() == MAX_LISTED_FILES {
+                truncated = true;
+                break;
+            }
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.push(json!({
+                "uri": uri,
+                "path": relative.to_string_lossy(),
+            }));
+        }
+    }
+
+    Ok(json!({ "files": files, "truncated": truncated }))
+}
+
 // Helpers
+fn extract_query(args: &Value) -> anyhow::Result<&str> {
+    args.get("query")
+        .and_then(|v| v.as_str())
+        .ok_or(anyhow::anyhow!("Missing query"))
+}
+
 fn extract_uri(args: &Value, guard: &Guard) -> anyhow::Result<String> {
     let uri = args
         .get("uri")
         .and_then(|v| v.as_str())
         .ok_or(anyhow::anyhow!("Missing uri"))?;
-    let path_str = uri.strip_prefix("file://").unwrap_or(uri);
-    guard.check_path(path_str)?;
+    // URIs list-files returns are percent-encoded; bare paths are accepted as is.
+    let path = Url::parse(uri)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .unwrap_or_else(|| PathBuf::from(uri.strip_prefix("file://").unwrap_or(uri)));
+    guard.check_path(&path)?;
     Ok(uri.to_string())
 }
 
@@ -113,13 +261,14 @@ pub fn list_tools() -> Value {
     json!([
         {
             "name": "domainforge/hover",
-            "description": "Get hover information for a symbol",
+            "description": "Get hover information for a symbol, as markdown or as the structured hover model",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "uri": { "type": "string" },
                     "line": { "type": "integer" },
-                    "character": { "type": "integer" }
+                    "character": { "type": "integer" },
+                    "format": { "type": "string", "enum": ["markdown", "json"] }
                 },
                 "required": ["uri", "line", "character"]
             }
@@ -139,7 +288,21 @@ pub fn list_tools() -> Value {
         },
         {
             "name": "domainforge/references",
-            "description": "Get references for a symbol",
+            "description": "Get references for a symbol within its own file",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "uri": { "type": "string" },
+                    "line": { "type": "integer" },
+                    "character": { "type": "integer" },
+                    "includeDeclaration": { "type": "boolean" }
+                },
+                "required": ["uri", "line", "character"]
+            }
+        },
+        {
+            "name": "domainforge/workspace-references",
+            "description": "Get references for a symbol across all workspace files, grouped by file",
             "inputSchema": {
                 "type": "object",
                 "properties": {
@@ -157,7 +320,8 @@ pub fn list_tools() -> Value {
             "inputSchema": {
                 "type": "object",
                 "properties": {
-                    "uri": { "type": "string" }
+                    "uri": { "type": "string" },
+                    "format": { "type": "string", "enum": ["lsp", "compact"], "default": "lsp" }
                 },
                 "required": ["uri"]
             }
@@ -187,6 +351,27 @@ pub fn list_tools() -> Value {
                 },
                 "required": ["uri", "range"]
             }
+        },
+        {
+            "name": "domainforge/list-files",
+            "description": "List the .sea files in the workspace, with paths relative to the workspace root",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "subdir": { "type": "string", "description": "Only list files under this directory, relative to the workspace root" }
+                }
+            }
+        },
+        {
+            "name": "domainforge/workspace-symbols",
+            "description": "Search declarations by name across all workspace files",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" }
+                },
+                "required": ["query"]
+            }
         }
     ])
 }
@@ -212,5 +397,186 @@ mod tests {
         assert!(tool_names.contains(&"domainforge/diagnostics"));
         assert!(tool_names.contains(&"domainforge/rename-preview"));
         assert!(tool_names.contains(&"domainforge/code-actions"));
+        assert!(tool_names.contains(&"domainforge/workspace-symbols"));
+        assert!(tool_names.contains(&"domainforge/workspace-references"));
+        assert!(tool_names.contains(&"domainforge/list-files"));
+    }
+
+    #[test]
+    fn test_list_files_lists_sea_files_under_the_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("models/shared")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("main.sea"), "Entity \"A\"\n").unwrap();
+        std::fs::write(root.join("models/shared/depots.sea"), "Entity \"B\"\n").unwrap();
+        std::fs::write(root.join("models/notes.txt"), "not sea").unwrap();
+        std::fs::write(root.join(".git/stray.sea"), "Entity \"C\"\n").unwrap();
+        let guard = Guard::new(vec![root.to_path_buf()]);
+
+        let paths = |listing: Value| -> Vec<String> {
+            listing["files"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| f["path"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let all = list_files_tool(json!({}), &guard).unwrap();
+        assert_eq!(all["truncated"], json!(false));
+        assert_eq!(paths(all), vec!["main.sea", "models/shared/depots.sea"]);
+
+        let models = list_files_tool(json!({ "subdir": "models" }), &guard).unwrap();
+        assert_eq!(paths(models), vec!["models/shared/depots.sea"]);
+
+        let outside = temp_dir.path().parent().unwrap();
+        let escape = list_files_tool(json!({ "subdir": outside.to_str().unwrap() }), &guard);
+        assert!(escape.is_err());
+    }
+
+    #[test]
+    fn test_list_files_uses_the_configured_extensions_and_escapes_uris() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("my models")).unwrap();
+        std::fs::write(root.join("my models/depot #1.dsea"), "Entity \"A\"\n").unwrap();
+        std::fs::write(root.join("main.sea"), "Entity \"B\"\n").unwrap();
+        let guard =
+            Guard::new(vec![root.to_path_buf()]).with_file_extensions(vec!["dsea".to_string()]);
+
+        let listing = list_files_tool(json!({}), &guard).unwrap();
+        let files = listing["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "my models/depot #1.dsea");
+        let uri = Url::parse(files[0]["uri"].as_str().unwrap()).unwrap();
+        assert!(uri.as_str().ends_with("/my%20models/depot%20%231.dsea"));
+        assert_eq!(
+            uri.to_file_path().unwrap(),
+            root.canonicalize().unwrap().join("my models/depot #1.dsea")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_references_is_dispatched() {
+        let client = LspClient::new("true").await.unwrap();
+        let guard = Guard::new(vec![]);
+
+        let unknown = handle_tool_call("domainforge/nope", json!({}), &client, &guard).await;
+        assert_eq!(
+            unknown.unwrap_err().to_string(),
+            "Unknown tool: domainforge/nope"
+        );
+
+        // Argument validation runs before the LSP is contacted.
+        let missing = handle_tool_call(
+            "domainforge/workspace-references",
+            json!({ "line": 0, "character": 0 }),
+            &client,
+            &guard,
+        )
+        .await;
+        assert_eq!(missing.unwrap_err().to_string(), "Missing uri");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hover_json_format_returns_the_hover_plus_model() {
+        // A stand-in server that answers one request: hoverPlus with a model, anything
+        // else with null.
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let server = temp_dir.path().join("fake-lsp.sh");
+        std::fs::write(
+            &server,
+            r#"#!/bin/sh
+read -r header
+read -r blank
+body=$(head -c "$(echo "$header" | tr -dc 0-9)")
+case "$body" in
+  *textDocument/hoverPlus*) result='{"model":{"schema_version":"1.0"}}' ;;
+  *) result='null' ;;
+esac
+response="{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":$result}"
+printf 'Content-Length: %d\r\n\r\n%s' "${#response}" "$response"
+cat > /dev/null
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&server, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+        std::fs::write(temp_dir.path().join("model.sea"), "Entity \"A\"\n").unwrap();
+        let uri = format!("file://{}", temp_dir.path().join("model.sea").display());
+        let guard = Guard::new(vec![temp_dir.path().to_path_buf()]);
+        let args =
+            |format: &str| json!({ "uri": uri, "line": 0, "character": 8, "format": format });
+
+        let client = LspClient::new(server.to_str().unwrap()).await.unwrap();
+        let model = handle_tool_call("domainforge/hover", args("json"), &client, &guard)
+            .await
+            .unwrap();
+        assert_eq!(model["schema_version"], "1.0");
+
+        let unknown = handle_tool_call("domainforge/hover", args("yaml"), &client, &guard).await;
+        assert_eq!(
+            unknown.unwrap_err().to_string(),
+            "Unknown hover format: yaml"
+        );
+    }
+
+    #[test]
+    fn test_workspace_references_group_by_file() {
+        let range = |line: u64| {
+            json!({
+                "start": { "line": line, "character": 0 },
+                "end": { "line": line, "character": 5 }
+            })
+        };
+        let locations = json!([
+            { "uri": "file:///ws/a.sea", "range": range(1) },
+            { "uri": "file:///ws/a.sea", "range": range(3) },
+            { "uri": "file:///ws/b.sea", "range": range(2) }
+        ]);
+
+        assert_eq!(
+            group_by_file(&locations),
+            json!({
+                "files": [
+                    { "uri": "file:///ws/a.sea", "ranges": [range(1), range(3)] },
+                    { "uri": "file:///ws/b.sea", "ranges": [range(2)] }
+                ]
+            })
+        );
+        assert_eq!(group_by_file(&Value::Null), json!({ "files": [] }));
+    }
+
+    #[test]
+    fn test_workspace_symbols_requires_query() {
+        assert_eq!(extract_query(&json!({ "query": "Ware" })).unwrap(), "Ware");
+        assert!(extract_query(&json!({ "uri": "file:///x.sea" })).is_err());
+    }
+
+    #[test]
+    fn test_compact_diagnostics_strips_ranges_to_line() {
+        let diags = vec![json!({
+            "range": {
+                "start": { "line": 4, "character": 2 },
+                "end": { "line": 6, "character": 0 }
+            },
+            "severity": 2,
+            "code": "E001",
+            "source": "domainforge",
+            "message": "Undefined entity: Vendor"
+        })];
+
+        let compact = compact_diagnostics(&diags);
+        assert_eq!(
+            compact,
+            json!([{
+                "code": "E001",
+                "severity": "warning",
+                "line": 4,
+                "message": "Undefined entity: Vendor"
+            }])
+        );
     }
 }
@@ -1,7 +1,245 @@
-use tower_lsp::lsp_types::{Location, Position, Url};
+use sea_core::Graph;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    LinkedEditingRanges, Location, Position, Range, TextDocumentIdentifier, TypeHierarchyItem, Url,
+};
 
 use crate::line_index::LineIndex;
-use crate::semantic_index::SemanticIndex;
+use crate::outline::DEFAULT_NAMESPACE;
+use crate::semantic_index::{ByteRange, NamespaceRole, Occurrence, SemanticIndex, SymbolKind};
+use crate::workspace::lsp_symbol_kind;
+
+const QUOTED_NAME_PATTERN: &str = r#"[^"\r\n]+"#;
+const IDENTIFIER_PATTERN: &str = r"[A-Za-z_][A-Za-z0-9_]*";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreadcrumbParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreadcrumbItem {
+    /// `Namespace`, or the symbol kind of a declaration or reference.
+    pub kind: String,
+    /// Display text, e.g. `Policy p` or `@vendor_123`.
+    pub label: String,
+    pub range: Option<Range>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphReferencesParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// References to an entity or resource grouped by the role they play in the model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphReferences {
+    /// `namespace::Name` when the graph resolves the symbol, else the bare name.
+    pub qualified_name: String,
+    /// Entity named after `from` in a flow.
+    pub flow_source: Vec<Location>,
+    /// Entity named after `to` in a flow.
+    pub flow_target: Vec<Location>,
+    /// Resource a flow moves.
+    pub flow_resource: Vec<Location>,
+    /// Entity named as the type of an `Instance x of "..."` declaration.
+    pub instance_type: Vec<Location>,
+    /// Any other reference, such as one inside a policy expression.
+    pub other: Vec<Location>,
+}
+
+/// Categorize the references to the entity or resource at `position` by how the
+/// model uses them. Other symbol kinds have no usage roles and return `None`.
+pub fn graph_references(
+    uri: &Url,
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> Option<GraphReferences> {
+    let offset = line_index.offset_of(position)?;
+    let occ = index.symbol_at_offset(offset)?;
+    if !matches!(occ.kind, SymbolKind::Entity | SymbolKind::Resource) {
+        return None;
+    }
+
+    let mut refs = GraphReferences {
+        qualified_name: graph
+            .and_then(|graph| declaration_namespace(graph, occ.kind, &occ.name))
+            .map(|namespace| format!("{}::{}", namespace, occ.name))
+            .unwrap_or_else(|| occ.name.clone()),
+        ..Default::default()
+    };
+    for range in index.reference_ranges(occ.kind, &occ.name) {
+        let location = SemanticIndex::lsp_location(uri, line_index, range);
+        let bucket = if let Some(flow) = index
+            .flows
+            .iter()
+            .find(|flow| flow.range.start <= range.start && range.end <= flow.range.end)
+        {
+            if occ.kind == SymbolKind::Resource {
+                &mut refs.flow_resource
+            } else if is_first_entity_in(index, flow.range, range) {
+                // Endpoints are written `from "A" to "B"`, so the first entity is the source
+                &mut refs.flow_source
+            } else {
+                &mut refs.flow_target
+            }
+        } else if index.declarations.iter().any(|decl| {
+            decl.kind == SymbolKind::Instance
+                && decl.range.start <= range.start
+                && range.end <= decl.range.end
+        }) {
+            &mut refs.instance_type
+        } else {
+            &mut refs.other
+        };
+        bucket.push(location);
+    }
+
+    Some(refs)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameValidateParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+    pub new_name: String,
+}
+
+/// An existing declaration a rename would collide with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameConflict {
+    /// Symbol kind, e.g. `Entity`.
+    pub kind: String,
+    /// `namespace::Name` of the existing declaration.
+    pub qualified_name: String,
+    /// Its definition, when it is declared in this document.
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameValidation {
+    pub valid: bool,
+    pub conflicts: Vec<RenameConflict>,
+}
+
+/// Check whether renaming the symbol at `position` to `new_name` would collide with
+/// a declaration of the same kind in the same namespace.
+///
+/// Returns `None` unless the cursor is on a symbol the graph resolves.
+pub fn validate_rename(
+    uri: &Url,
+    line_index: &LineIndex,
+    position: Position,
+    new_name: &str,
+    index: &SemanticIndex,
+    graph: &Graph,
+) -> Option<RenameValidation> {
+    let offset = line_index.offset_of(position)?;
+    let occ = index.symbol_at_offset(offset)?;
+    let (namespace, name) = occurrence_namespace(index, graph, occ)?;
+
+    let conflicts: Vec<RenameConflict> = if new_name == name {
+        Vec::new()
+    } else {
+        declaration_namespaces(graph, occ.kind, new_name)
+            .into_iter()
+            .filter(|existing| *existing == namespace)
+            .map(|existing| RenameConflict {
+                kind: format!("{:?}", occ.kind),
+                qualified_name: format!("{}::{}", existing, new_name),
+                location: index
+                    .occurrences
+                    .iter()
+                    .find(|def| {
+                        def.is_definition
+                            && def.kind == occ.kind
+                            && def.name == new_name
+                            && local_namespace(index, def.range) == existing
+                    })
+                    .map(|def| SemanticIndex::lsp_location(uri, line_index, def.range)),
+            })
+            .collect()
+    };
+
+    Some(RenameValidation {
+        valid: conflicts.is_empty(),
+        conflicts,
+    })
+}
+
+/// Namespace and bare name of the declaration `occ` stands for, as the graph knows it.
+///
+/// A qualified `"ns::Name"` names its namespace and a definition takes the one it is
+/// declared in. An unqualified reference resolves to a same-named declaration in the
+/// document's namespace when there is one, else to the first the graph holds.
+fn occurrence_namespace<'a>(
+    index: &SemanticIndex,
+    graph: &Graph,
+    occ: &'a Occurrence,
+) -> Option<(String, &'a str)> {
+    let (namespace, name) = match occ.name.split_once("::") {
+        Some((namespace, name)) => (namespace.to_string(), name),
+        None if occ.is_definition => (local_namespace(index, occ.range), occ.name.as_str()),
+        None => {
+            let candidates = declaration_namespaces(graph, occ.kind, &occ.name);
+            let local = local_namespace(index, occ.range);
+            let namespace = if candidates.contains(&local) {
+                local
+            } else {
+                candidates.into_iter().next()?
+            };
+            (namespace, occ.name.as_str())
+        }
+    };
+    declaration_namespaces(graph, occ.kind, name)
+        .contains(&namespace)
+        .then_some((namespace, name))
+}
+
+/// Namespace that applies at `range`: the `in` clause of the declaration spanning
+/// it, else the file's `@namespace`, else the default namespace.
+fn local_namespace(index: &SemanticIndex, range: ByteRange) -> String {
+    let in_clause = index
+        .declarations
+        .iter()
+        .find(|decl| decl.range.start <= range.start && range.end <= decl.range.end)
+        .and_then(|decl| {
+            index.namespaces.iter().find(|ns| {
+                ns.role == NamespaceRole::InClause
+                    && decl.range.start <= ns.range.start
+                    && ns.range.end <= decl.range.end
+            })
+        });
+    in_clause
+        .or_else(|| {
+            index
+                .namespaces
+                .iter()
+                .find(|ns| ns.role == NamespaceRole::Declaration)
+        })
+        .map_or_else(|| DEFAULT_NAMESPACE.to_string(), |ns| ns.namespace.clone())
+}
+
+/// Whether `range` is the first entity occurrence inside the flow spanning `flow`.
+fn is_first_entity_in(index: &SemanticIndex, flow: ByteRange, range: ByteRange) -> bool {
+    index
+        .occurrences
+        .iter()
+        .filter(|occ| {
+            occ.kind == SymbolKind::Entity
+                && flow.start <= occ.range.start
+                && occ.range.end <= flow.end
+        })
+        .min_by_key(|occ| occ.range.start)
+        .is_some_and(|first| first.range == range)
+}
 
 pub fn goto_definition(
     uri: &Url,
@@ -55,6 +293,282 @@ pub fn find_references(
     locations
 }
 
+/// Ranges that should be edited together with the symbol at `position`: its
+/// definition and every reference in the document.
+///
+/// Ranges cover only the name itself (no quotes or `@` sigil) so that every range
+/// holds identical text, as linked editing requires.
+pub fn linked_editing_ranges(
+    source: &str,
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+) -> Option<LinkedEditingRanges> {
+    let offset = line_index.offset_of(position)?;
+    let occ = index.symbol_at_offset(offset)?;
+    if occ.kind == SymbolKind::Flow {
+        return None;
+    }
+
+    let mut ranges: Vec<ByteRange> = index.reference_ranges(occ.kind, &occ.name);
+    ranges.extend(index.definition_range(occ.kind, &occ.name));
+    ranges.sort_by_key(|r| (r.start, r.end));
+    ranges.dedup();
+
+    // Quoted names may contain spaces; bare identifiers (instances, policies) may not.
+    let quoted = source
+        .get(occ.range.start..occ.range.end)
+        .is_some_and(|text| text.starts_with('"'));
+    let word_pattern = if quoted {
+        QUOTED_NAME_PATTERN
+    } else {
+        IDENTIFIER_PATTERN
+    };
+
+    let ranges: Vec<Range> = ranges
+        .into_iter()
+        .map(|r| name_range(source, r))
+        .map(|r| Range {
+            start: line_index.position_of(r.start),
+            end: line_index.position_of(r.end),
+        })
+        .collect();
+    if ranges.len() < 2 {
+        return None;
+    }
+
+    Some(LinkedEditingRanges {
+        ranges,
+        word_pattern: Some(word_pattern.to_string()),
+    })
+}
+
+/// Shrink an occurrence range to its bare name.
+fn name_range(source: &str, range: ByteRange) -> ByteRange {
+    let Some(text) = source.get(range.start..range.end) else {
+        return range;
+    };
+    let (lead, trail) = if text.starts_with("\"\"\"") && text.len() >= 6 {
+        (3, 3)
+    } else if text.starts_with('"') && text.len() >= 2 {
+        (1, 1)
+    } else if text.starts_with('@') {
+        (1, 0)
+    } else {
+        (0, 0)
+    };
+    ByteRange {
+        start: range.start + lead,
+        end: range.end - trail,
+    }
+}
+
+/// Chain of enclosing declarations from the file root down to the symbol at `position`,
+/// e.g. `logistics › Policy p › @vendor_123`.
+///
+/// The namespace crumb is only present when the graph resolves the outermost declaration.
+pub fn breadcrumb(
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: Option<&Graph>,
+) -> Vec<BreadcrumbItem> {
+    let Some(offset) = line_index.offset_of(position) else {
+        return Vec::new();
+    };
+    let to_range = |start, end| Range {
+        start: line_index.position_of(start),
+        end: line_index.position_of(end),
+    };
+
+    let declarations = index.declarations_at_offset(offset);
+    let mut items = Vec::new();
+
+    if let Some(namespace) = declarations
+        .first()
+        .and_then(|decl| declaration_namespace(graph?, decl.kind, &decl.name))
+    {
+        items.push(BreadcrumbItem {
+            kind: "Namespace".to_string(),
+            label: namespace,
+            range: None,
+        });
+    }
+
+    for decl in &declarations {
+        items.push(BreadcrumbItem {
+            kind: format!("{:?}", decl.kind),
+            label: crumb_label(decl.kind, &decl.name),
+            range: Some(to_range(decl.range.start, decl.range.end)),
+        });
+    }
+
+    // The innermost symbol, unless it is the name of the innermost declaration.
+    if let Some(occ) = index.symbol_at_offset(offset) {
+        let is_decl_name = occ.is_definition
+            && declarations
+                .last()
+                .is_some_and(|d| d.kind == occ.kind && d.name == occ.name);
+        if !is_decl_name {
+            let label = if occ.kind == SymbolKind::Instance {
+                format!("@{}", occ.name)
+            } else {
+                crumb_label(occ.kind, &occ.name)
+            };
+            items.push(BreadcrumbItem {
+                kind: format!("{:?}", occ.kind),
+                label,
+                range: Some(to_range(occ.range.start, occ.range.end)),
+            });
+        }
+    }
+
+    items
+}
+
+fn crumb_label(kind: SymbolKind, name: &str) -> String {
+    match kind {
+        // Flow occurrences carry a synthetic `flow@start..end` name.
+        SymbolKind::Flow => "Flow".to_string(),
+        _ => format!("{:?} {}", kind, name),
+    }
+}
+
+/// Type hierarchy item for the entity at `position`. Entities form a version lineage
+/// through `@replaces`: the replaced entity is the supertype, replacements subtypes.
+pub fn prepare_type_hierarchy(
+    uri: &Url,
+    line_index: &LineIndex,
+    position: Position,
+    index: &SemanticIndex,
+    graph: &Graph,
+) -> Option<TypeHierarchyItem> {
+    let occ = index.symbol_at_offset(line_index.offset_of(position)?)?;
+    if occ.kind != SymbolKind::Entity {
+        return None;
+    }
+    graph
+        .all_entities()
+        .into_iter()
+        .find(|e| e.name() == occ.name)?;
+    entity_hierarchy_item(uri, line_index, index, &occ.name)
+}
+
+/// The entity `entity` replaces, when it is declared in this document.
+pub fn type_hierarchy_supertypes(
+    uri: &Url,
+    line_index: &LineIndex,
+    index: &SemanticIndex,
+    graph: &Graph,
+    entity: &str,
+) -> Vec<TypeHierarchyItem> {
+    graph
+        .all_entities()
+        .into_iter()
+        .filter(|e| e.name() == entity)
+        .filter_map(|e| e.replaces())
+        .filter_map(|replaced| entity_hierarchy_item(uri, line_index, index, replaced))
+        .collect()
+}
+
+/// Entities declared in this document that replace `entity`.
+pub fn type_hierarchy_subtypes(
+    uri: &Url,
+    line_index: &LineIndex,
+    index: &SemanticIndex,
+    graph: &Graph,
+    entity: &str,
+) -> Vec<TypeHierarchyItem> {
+    graph
+        .all_entities()
+        .into_iter()
+        .filter(|e| e.replaces() == Some(entity))
+        .filter_map(|e| entity_hierarchy_item(uri, line_index, index, e.name()))
+        .collect()
+}
+
+fn entity_hierarchy_item(
+    uri: &Url,
+    line_index: &LineIndex,
+    index: &SemanticIndex,
+    name: &str,
+) -> Option<TypeHierarchyItem> {
+    let to_range = |range: ByteRange| Range {
+        start: line_index.position_of(range.start),
+        end: line_index.position_of(range.end),
+    };
+    let selection = index.definition_range(SymbolKind::Entity, name)?;
+    let declaration = index
+        .declarations
+        .iter()
+        .find(|d| d.kind == SymbolKind::Entity && d.name == name)
+        .map_or(selection, |d| d.range);
+
+    Some(TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp_symbol_kind(SymbolKind::Entity),
+        tags: None,
+        detail: Some("Entity".to_string()),
+        uri: uri.clone(),
+        range: to_range(declaration),
+        selection_range: to_range(selection),
+        data: None,
+    })
+}
+
+/// Namespace the graph assigns to the declaration of `name`, if it resolves.
+pub(crate) fn declaration_namespace(graph: &Graph, kind: SymbolKind, name: &str) -> Option<String> {
+    declaration_namespaces(graph, kind, name).into_iter().next()
+}
+
+/// Namespaces of every `kind` declaration named `name`, in graph order.
+fn declaration_namespaces(graph: &Graph, kind: SymbolKind, name: &str) -> Vec<String> {
+    match kind {
+        SymbolKind::Entity => graph
+            .all_entities()
+            .into_iter()
+            .filter(|e| e.name() == name)
+            .map(|e| e.namespace().to_string())
+            .collect(),
+        SymbolKind::Resource => graph
+            .all_resources()
+            .into_iter()
+            .filter(|r| r.name() == name)
+            .map(|r| r.namespace().to_string())
+            .collect(),
+        SymbolKind::Pattern => graph
+            .all_patterns()
+            .into_iter()
+            .filter(|p| p.name() == name)
+            .map(|p| p.namespace().to_string())
+            .collect(),
+        SymbolKind::Role => graph
+            .all_roles()
+            .into_iter()
+            .filter(|r| r.name() == name)
+            .map(|r| r.namespace().to_string())
+            .collect(),
+        SymbolKind::Relation => graph
+            .all_relations()
+            .into_iter()
+            .filter(|r| r.name() == name)
+            .map(|r| r.namespace().to_string())
+            .collect(),
+        SymbolKind::Instance => graph
+            .get_entity_instance(name)
+            .map(|i| i.namespace().to_string())
+            .into_iter()
+            .collect(),
+        SymbolKind::Policy => graph
+            .all_policies()
+            .into_iter()
+            .filter(|p| p.name == name)
+            .map(|p| p.namespace.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
 fn position_key(loc: &Location) -> (u32, u32, u32, u32) {
     (
         loc.range.start.line,
@@ -129,6 +643,137 @@ Instance vendor_123 of "Warehouse" {}
         );
     }
 
+    #[test]
+    fn graph_references_are_grouped_by_usage_role() {
+        let source = r#"
+Entity "Warehouse" in logistics
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+Flow "Cameras" from "Factory" to "Warehouse" quantity 2
+Instance depot of "Warehouse" {}
+"#;
+        let uri = Url::parse("file:///test.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let line_of =
+            |needle: &str| source.lines().position(|l| l.contains(needle)).unwrap() as u32;
+        let lines = |locations: &[Location]| -> Vec<u32> {
+            locations.iter().map(|l| l.range.start.line).collect()
+        };
+
+        let pos = line_index.position_of(source.find("\"Warehouse\"").unwrap() + 2);
+        let refs = graph_references(&uri, &line_index, pos, &index, Some(&graph)).unwrap();
+        assert_eq!(refs.qualified_name, "logistics::Warehouse");
+        assert_eq!(lines(&refs.flow_source), vec![line_of("quantity 10")]);
+        assert_eq!(lines(&refs.flow_target), vec![line_of("quantity 2")]);
+        assert_eq!(lines(&refs.instance_type), vec![line_of("Instance")]);
+        assert!(refs.flow_resource.is_empty() && refs.other.is_empty());
+
+        let pos = line_index.position_of(source.find("\"Cameras\"").unwrap() + 2);
+        let refs = graph_references(&uri, &line_index, pos, &index, Some(&graph)).unwrap();
+        assert_eq!(refs.flow_resource.len(), 2);
+    }
+
+    #[test]
+    fn rename_onto_an_existing_entity_reports_the_conflict() {
+        let source = r#"Entity "Warehouse" in logistics
+Entity "Depot" in logistics
+Entity "Store" in retail
+"#;
+        let uri = Url::parse("file:///rename.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let pos = line_index.position_of(source.find("\"Warehouse\"").unwrap() + 2);
+
+        let taken = validate_rename(&uri, &line_index, pos, "Depot", &index, &graph).unwrap();
+        assert!(!taken.valid);
+        assert_eq!(taken.conflicts.len(), 1);
+        assert_eq!(taken.conflicts[0].kind, "Entity");
+        assert_eq!(taken.conflicts[0].qualified_name, "logistics::Depot");
+        assert_eq!(
+            taken.conflicts[0]
+                .location
+                .as_ref()
+                .unwrap()
+                .range
+                .start
+                .line,
+            1
+        );
+
+        // The same name in another namespace does not collide.
+        let free = validate_rename(&uri, &line_index, pos, "Store", &index, &graph).unwrap();
+        assert!(free.valid && free.conflicts.is_empty());
+    }
+
+    #[test]
+    fn rename_checks_the_namespace_of_the_symbol_under_the_cursor() {
+        let source = r#"Entity "Depot" in logistics
+Entity "Store" in logistics
+Entity "Depot" in retail
+Entity "Store" in retail
+Entity "Shop" in retail
+"#;
+        let uri = Url::parse("file:///rename.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let retail_depot = source.find("\"Depot\" in retail").unwrap() + 2;
+        let pos = line_index.position_of(retail_depot);
+
+        // "Shop" only exists in retail, which the first graph match would miss
+        let taken = validate_rename(&uri, &line_index, pos, "Shop", &index, &graph).unwrap();
+        assert_eq!(taken.conflicts.len(), 1);
+        assert_eq!(taken.conflicts[0].qualified_name, "retail::Shop");
+
+        // The conflict points at retail's "Store", not the first one in the file
+        let taken = validate_rename(&uri, &line_index, pos, "Store", &index, &graph).unwrap();
+        assert_eq!(taken.conflicts.len(), 1);
+        assert_eq!(taken.conflicts[0].qualified_name, "retail::Store");
+        assert_eq!(
+            taken.conflicts[0]
+                .location
+                .as_ref()
+                .unwrap()
+                .range
+                .start
+                .line,
+            3
+        );
+    }
+
+    #[test]
+    fn type_hierarchy_follows_replaces_chains() {
+        let source = r#"Entity "Depot"
+Entity "Warehouse" @replaces "Depot"
+Entity "Hub" @replaces "Warehouse"
+"#;
+        let uri = Url::parse("file:///lineage.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let names = |items: Vec<TypeHierarchyItem>| -> Vec<String> {
+            items.into_iter().map(|item| item.name).collect()
+        };
+
+        let pos = line_index.position_of(source.find("\"Warehouse\"").unwrap() + 2);
+        let item = prepare_type_hierarchy(&uri, &line_index, pos, &index, &graph).unwrap();
+        assert_eq!(item.name, "Warehouse");
+        assert_eq!(item.selection_range.start.line, 1);
+
+        let supertypes = |name| type_hierarchy_supertypes(&uri, &line_index, &index, &graph, name);
+        let subtypes = |name| type_hierarchy_subtypes(&uri, &line_index, &index, &graph, name);
+        assert_eq!(names(supertypes("Warehouse")), vec!["Depot"]);
+        assert_eq!(names(subtypes("Warehouse")), vec!["Hub"]);
+        assert!(supertypes("Depot").is_empty());
+        assert_eq!(names(subtypes("Depot")), vec!["Warehouse"]);
+        assert_eq!(names(supertypes("Hub")), vec!["Warehouse"]);
+        assert!(subtypes("Hub").is_empty());
+    }
+
     #[test]
     fn goto_definition_from_flow_endpoint_to_entity_decl() {
         let source = r#"
@@ -151,4 +796,87 @@ Flow "Cameras" from "Warehouse" to "Factory" quantity 10
         let expected = SemanticIndex::lsp_location(&uri, &line_index, def_range);
         assert_eq!(loc.range, expected.range);
     }
+
+    #[test]
+    fn breadcrumb_from_policy_expression_ends_with_instance_reference() {
+        let source = r#"
+Entity "Vendor" in domain
+
+Instance vendor_123 of "Vendor" {
+  name: "Acme"
+}
+
+Policy p as: @vendor_123 = @vendor_123
+"#;
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+        let graph = sea_core::parse_to_graph(source).ok();
+
+        let offset = source.find("@vendor_123").unwrap() + 3;
+        let pos = line_index.position_of(offset);
+        let crumbs = breadcrumb(&line_index, pos, &index, graph.as_ref());
+
+        let labels: Vec<&str> = crumbs
+            .iter()
+            .filter(|c| c.kind != "Namespace")
+            .map(|c| c.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Policy p", "@vendor_123"]);
+    }
+
+    #[test]
+    fn linked_editing_ranges_cover_definition_and_references() {
+        let source = r#"
+Entity "Warehouse"
+Entity "Factory"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to "Factory" quantity 10
+"#;
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+
+        let offset = source.find("\"Warehouse\"").unwrap() + 2;
+        let pos = line_index.position_of(offset);
+        let linked =
+            linked_editing_ranges(source, &line_index, pos, &index).expect("linked ranges");
+
+        assert_eq!(linked.ranges.len(), 2);
+        for range in &linked.ranges {
+            let start = line_index.offset_of(range.start).unwrap();
+            let end = line_index.offset_of(range.end).unwrap();
+            assert_eq!(&source[start..end], "Warehouse");
+        }
+    }
+
+    #[test]
+    fn linked_editing_ranges_match_entity_occurrence_spans() {
+        let source = r#"
+Entity "Vendor" in domain
+
+Instance vendor_123 of "Vendor" {
+  name: "Acme"
+}
+Instance vendor_456 of "Vendor" {}
+"#;
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+
+        let offset = source.find("\"Vendor\"").unwrap() + 2;
+        let pos = line_index.position_of(offset);
+        let linked =
+            linked_editing_ranges(source, &line_index, pos, &index).expect("linked ranges");
+
+        let mut expected: Vec<Range> = index
+            .occurrences
+            .iter()
+            .filter(|o| o.kind == SymbolKind::Entity && o.name == "Vendor")
+            .map(|o| Range {
+                start: line_index.position_of(o.range.start + 1),
+                end: line_index.position_of(o.range.end - 1),
+            })
+            .collect();
+        expected.sort_by_key(|r| (r.start.line, r.start.character));
+        assert_eq!(linked.ranges, expected);
+        assert_eq!(linked.word_pattern.as_deref(), Some(QUOTED_NAME_PATTERN));
+    }
 }
@@ -0,0 +1,171 @@
+//! Namespace-grouped document outline for the `sea/outline` request.
+//!
+//! Unlike `textDocument/documentSymbol`, declarations are nested under synthetic
+//! namespace nodes so multi-namespace files read as one tree per namespace.
+
+use std::collections::BTreeMap;
+
+use sea_core::Graph;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    DocumentSymbol, Range, SymbolKind as LspSymbolKind, TextDocumentIdentifier,
+};
+
+use crate::line_index::LineIndex;
+use crate::navigation::declaration_namespace;
+use crate::semantic_index::{ByteRange, SemanticIndex, SymbolKind};
+use crate::workspace::lsp_symbol_kind;
+
+/// Group name for declarations with no namespace in the graph or the file header.
+pub(crate) const DEFAULT_NAMESPACE: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outline {
+    /// File-level annotations (`@namespace`, `@version`, ...) by key.
+    pub metadata: BTreeMap<String, String>,
+    /// One `NAMESPACE` symbol per namespace, in order of first declaration.
+    pub namespaces: Vec<DocumentSymbol>,
+}
+
+/// Build the outline of a document.
+///
+/// Declarations take their namespace from the graph; those it cannot place (flows,
+/// or every declaration when the document does not parse) fall back to the file's
+/// `@namespace` annotation.
+pub fn outline(line_index: &LineIndex, index: &SemanticIndex, graph: Option<&Graph>) -> Outline {
+    let to_range = |range: ByteRange| Range {
+        start: line_index.position_of(range.start),
+        end: line_index.position_of(range.end),
+    };
+    let metadata: BTreeMap<String, String> = index
+        .annotations
+        .iter()
+        .map(|a| (a.key.clone(), a.value.clone()))
+        .collect();
+    let file_namespace = metadata
+        .get("namespace")
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+
+    let mut groups: Vec<(String, Vec<DocumentSymbol>, ByteRange)> = Vec::new();
+    for decl in &index.declarations {
+        let namespace = graph
+            .and_then(|graph| declaration_namespace(graph, decl.kind, &decl.name))
+            .unwrap_or_else(|| file_namespace.clone());
+        let name_range = index
+            .definition_range(decl.kind, &decl.name)
+            .unwrap_or(decl.range);
+
+        #[allow(deprecated)]
+        let symbol = DocumentSymbol {
+            name: symbol_name(index, decl.kind, &decl.name, decl.range),
+            detail: Some(format!("{:?}", decl.kind)),
+            kind: lsp_symbol_kind(decl.kind),
+            tags: None,
+            deprecated: None,
+            range: to_range(decl.range),
+            selection_range: to_range(name_range),
+            children: None,
+        };
+
+        match groups.iter_mut().find(|(ns, _, _)| *ns == namespace) {
+            Some((_, symbols, span)) => {
+                symbols.push(symbol);
+                span.end = span.end.max(decl.range.end);
+            }
+            None => groups.push((namespace, vec![symbol], decl.range)),
+        }
+    }
+
+    let namespaces = groups
+        .into_iter()
+        .map(|(namespace, symbols, span)| {
+            #[allow(deprecated)]
+            DocumentSymbol {
+                name: namespace,
+                detail: None,
+                kind: LspSymbolKind::NAMESPACE,
+                tags: None,
+                deprecated: None,
+                range: to_range(span),
+                selection_range: to_range(ByteRange {
+                    start: span.start,
+                    end: span.start,
+                }),
+                children: Some(symbols),
+            }
+        })
+        .collect();
+
+    Outline {
+        metadata,
+        namespaces,
+    }
+}
+
+/// Display name of a declaration; flows are named after what they move.
+fn symbol_name(index: &SemanticIndex, kind: SymbolKind, name: &str, range: ByteRange) -> String {
+    if kind != SymbolKind::Flow {
+        return name.to_string();
+    }
+    match index.flow_decl_for_range(range) {
+        Some(flow) => format!(
+            "{}: {} -> {}",
+            flow.resource, flow.from_entity, flow.to_entity
+        ),
+        None => "Flow".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_declarations_under_their_namespaces() {
+        let source = r#"@namespace "logistics"
+@version "1.2"
+
+Entity "Warehouse"
+Entity "Factory"
+Entity "Clerk" in staffing
+Role "Picker" in staffing
+"#;
+        let graph = sea_core::parse_to_graph(source).unwrap();
+        let index = SemanticIndex::build(source);
+        let line_index = LineIndex::new(source);
+
+        let outline = outline(&line_index, &index, Some(&graph));
+
+        assert_eq!(outline.metadata.get("namespace").unwrap(), "logistics");
+        assert_eq!(outline.metadata.get("version").unwrap(), "1.2");
+
+        let groups: Vec<(&str, Vec<&str>)> = outline
+            .namespaces
+            .iter()
+            .map(|ns| {
+                let children = ns.children.as_ref().unwrap();
+                (
+                    ns.name.as_str(),
+                    children.iter().map(|c| c.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("logistics", vec!["Warehouse", "Factory"]),
+                ("staffing", vec!["Clerk", "Picker"]),
+            ]
+        );
+        assert!(outline
+            .namespaces
+            .iter()
+            .all(|ns| ns.kind == LspSymbolKind::NAMESPACE));
+    }
+}
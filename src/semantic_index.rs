@@ -1,5 +1,7 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+use pest::error::InputLocation;
 use pest::iterators::Pair;
 use pest::Parser;
 use sea_core::parser::{unescape_string, Rule, SeaParser};
@@ -8,6 +10,9 @@ use tower_lsp::lsp_types::{Location, Range, Url};
 
 use crate::line_index::LineIndex;
 
+/// Upper bound on lines blanked while recovering from parse errors.
+const MAX_RECOVERY_ATTEMPTS: usize = 16;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SymbolKind {
     Entity,
@@ -47,6 +52,59 @@ pub struct FlowDecl {
     pub from_entity: String,
     pub to_entity: String,
     pub quantity: Option<String>,
+    /// Unit written inline after the quantity (`quantity 10 kg`), with its span.
+    pub unit: Option<(String, ByteRange)>,
+}
+
+/// Inclusive, zero-based line span of a run of adjacent declarations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSpan {
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+/// The path literal of an `import ... from "path"` statement.
+#[derive(Debug, Clone)]
+pub struct ImportPath {
+    pub path: String,
+    /// Span of the string literal, quotes included.
+    pub range: ByteRange,
+}
+
+/// A place where a namespace name is written, spanning the bare name (no quotes).
+#[derive(Debug, Clone)]
+pub struct NamespaceOccurrence {
+    pub namespace: String,
+    pub range: ByteRange,
+    pub role: NamespaceRole,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamespaceRole {
+    /// The value of a `@namespace "..."` annotation.
+    Declaration,
+    /// The identifier of an `in <namespace>` clause.
+    InClause,
+    /// The alias bound by `import * as <alias> from ...`.
+    ImportAlias,
+    /// The `ns` prefix of a qualified `"ns::Name"` reference.
+    Qualifier,
+}
+
+/// A file-level `@key "value"` annotation such as `@namespace` or `@version`.
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub key: String,
+    pub value: String,
+    pub range: ByteRange,
+}
+
+/// Full source span of a top-level declaration, keyed by the symbol it defines.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub kind: SymbolKind,
+    pub name: String,
+    pub range: ByteRange,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -55,24 +113,55 @@ pub struct SemanticIndex {
     definitions: HashMap<(SymbolKind, String), ByteRange>,
     references: HashMap<(SymbolKind, String), Vec<ByteRange>>,
     pub import_prefixes: Vec<String>,
+    pub import_paths: Vec<ImportPath>,
+    pub namespaces: Vec<NamespaceOccurrence>,
+    pub annotations: Vec<Annotation>,
     pub flows: Vec<FlowDecl>,
+    /// Declarations in source order, spanning their whole text (not just the name).
+    pub declarations: Vec<Declaration>,
+    /// Runs of `import` statements on consecutive lines, in source order.
+    pub import_blocks: Vec<LineSpan>,
+    /// Whether the source could not be recovered into a parse and the occurrences
+    /// come from a line scan for `Entity`, `Resource` and `Flow` declarations.
+    pub approximate: bool,
+    import_ranges: Vec<ByteRange>,
+    /// Comment lines directly above a declaration, keyed by the symbol it defines.
+    doc_comments: HashMap<(SymbolKind, String), String>,
 }
 
 impl SemanticIndex {
     pub fn build(source: &str) -> Self {
         let mut index = Self::default();
 
-        let Ok(mut pairs) = SeaParser::parse(Rule::program, source) else {
+        let text = match recover(source) {
+            Recovered::Source(text) => text,
+            Recovered::Scanned(symbols) => {
+                index.approximate = true;
+                for (kind, name, range, is_definition) in symbols {
+                    index.record(kind, name, range, is_definition);
+                }
+                return index;
+            }
+        };
+        let Ok(mut pairs) = SeaParser::parse(Rule::program, &text) else {
             return index;
         };
 
         if let Some(program) = pairs.next() {
             index.walk(program);
         }
+        for decl in &index.declarations {
+            if let Some(doc) = leading_comment(source, decl.range.start) {
+                index
+                    .doc_comments
+                    .insert((decl.kind, decl.name.clone()), doc);
+            }
+        }
 
         index.import_prefixes.sort();
         index.import_prefixes.dedup();
         index.flows.sort_by_key(|f| (f.range.start, f.range.end));
+        index.import_blocks = group_adjacent_lines(&LineIndex::new(source), &index.import_ranges);
         index
     }
 
@@ -83,6 +172,24 @@ impl SemanticIndex {
             .min_by_key(|occ| occ.range.end.saturating_sub(occ.range.start))
     }
 
+    /// The smallest occurrence strictly enclosing `inner`, such as the Flow around one
+    /// of its endpoints.
+    pub fn enclosing_occurrence(&self, inner: &Occurrence) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .filter(|occ| {
+                occ.range.start <= inner.range.start
+                    && inner.range.end <= occ.range.end
+                    && occ.range != inner.range
+            })
+            .min_by_key(|occ| occ.range.end.saturating_sub(occ.range.start))
+    }
+
+    /// The file-level annotation whose span contains `offset`.
+    pub fn annotation_at_offset(&self, offset: usize) -> Option<&Annotation> {
+        self.annotations.iter().find(|a| a.range.contains(offset))
+    }
+
     pub fn definition_range(&self, kind: SymbolKind, name: &str) -> Option<ByteRange> {
         self.definitions
             .get(&(kind, name.to_string()))
@@ -99,6 +206,13 @@ impl SemanticIndex {
             })
     }
 
+    /// Text of the comment block written directly above the declaration of `name`.
+    pub fn doc_comment(&self, kind: SymbolKind, name: &str) -> Option<&str> {
+        self.doc_comments
+            .get(&(kind, name.to_string()))
+            .map(String::as_str)
+    }
+
     pub fn reference_ranges(&self, kind: SymbolKind, name: &str) -> Vec<ByteRange> {
         self.references
             .get(&(kind, name.to_string()))
@@ -106,6 +220,17 @@ impl SemanticIndex {
             .unwrap_or_default()
     }
 
+    /// Declarations whose span contains `offset`, outermost first.
+    pub fn declarations_at_offset(&self, offset: usize) -> Vec<&Declaration> {
+        let mut enclosing: Vec<&Declaration> = self
+            .declarations
+            .iter()
+            .filter(|decl| decl.range.contains(offset))
+            .collect();
+        enclosing.sort_by_key(|decl| (decl.range.start, std::cmp::Reverse(decl.range.end)));
+        enclosing
+    }
+
     pub fn flow_decl_for_range(&self, range: ByteRange) -> Option<&FlowDecl> {
         self.flows.iter().find(|f| f.range == range)
     }
@@ -121,31 +246,123 @@ impl SemanticIndex {
     }
 
     fn walk(&mut self, pair: Pair<'_, Rule>) {
-        match pair.as_rule() {
-            Rule::import_decl => self.parse_import_decl(pair),
-            Rule::entity_decl => self.parse_entity_decl(pair),
-            Rule::resource_decl => self.parse_resource_decl(pair),
-            Rule::flow_decl => self.parse_flow_decl(pair),
-            Rule::pattern_decl => self.parse_pattern_decl(pair),
-            Rule::role_decl => self.parse_role_decl(pair),
-            Rule::relation_decl => self.parse_relation_decl(pair),
-            Rule::instance_decl => self.parse_instance_decl(pair),
-            Rule::instance_reference => self.parse_instance_reference(pair),
-            Rule::policy_decl => self.parse_policy_decl(pair),
+        let parse: fn(&mut Self, Pair<'_, Rule>) = match pair.as_rule() {
+            Rule::annotation => return self.parse_annotation(pair),
+            Rule::import_decl => return self.parse_import_decl(pair),
+            Rule::instance_reference => return self.parse_instance_reference(pair),
+            Rule::entity_decl => Self::parse_entity_decl,
+            Rule::resource_decl => Self::parse_resource_decl,
+            Rule::flow_decl => Self::parse_flow_decl,
+            Rule::pattern_decl => Self::parse_pattern_decl,
+            Rule::role_decl => Self::parse_role_decl,
+            Rule::relation_decl => Self::parse_relation_decl,
+            Rule::instance_decl => Self::parse_instance_decl,
+            Rule::policy_decl => Self::parse_policy_decl,
             _ => {
                 for inner in pair.into_inner() {
                     self.walk(inner);
                 }
+                return;
             }
+        };
+
+        let span = pair.as_span();
+        let first = self.occurrences.len();
+        parse(self, pair);
+        if let Some(def) = self.occurrences[first..].iter().find(|o| o.is_definition) {
+            self.declarations.push(Declaration {
+                kind: def.kind,
+                name: def.name.clone(),
+                range: ByteRange {
+                    start: span.start(),
+                    end: span.end(),
+                },
+            });
+        }
+    }
+
+    fn parse_annotation(&mut self, pair: Pair<'_, Rule>) {
+        // annotation = { "@" ~ identifier ~ string_literal }
+        let span = pair.as_span();
+        let mut inner = pair.into_inner();
+        let (Some(key), Some(value)) = (inner.next(), inner.next()) else {
+            return;
+        };
+        let Some(text) = extract_string_literal_value(value.as_str()) else {
+            return;
+        };
+        self.annotations.push(Annotation {
+            key: key.as_str().to_string(),
+            value: text.clone(),
+            range: ByteRange {
+                start: span.start(),
+                end: span.end(),
+            },
+        });
+
+        if key.as_str() == "namespace" {
+            let span = value.as_span();
+            self.namespaces.push(NamespaceOccurrence {
+                namespace: text,
+                range: ByteRange {
+                    start: span.start() + 1,
+                    end: span.end() - 1,
+                },
+                role: NamespaceRole::Declaration,
+            });
+        }
+    }
+
+    fn record_namespace_identifier(&mut self, pair: Pair<'_, Rule>, role: NamespaceRole) {
+        let span = pair.as_span();
+        self.namespaces.push(NamespaceOccurrence {
+            namespace: pair.as_str().to_string(),
+            range: ByteRange {
+                start: span.start(),
+                end: span.end(),
+            },
+            role,
+        });
+    }
+
+    /// Record the namespace of an optional `in <namespace>` clause among `pairs`.
+    fn record_in_clause<'a>(&mut self, pairs: impl IntoIterator<Item = Pair<'a, Rule>>) {
+        let ident = pairs
+            .into_iter()
+            .find(|p| p.as_rule() == Rule::in_clause)
+            .and_then(|clause| {
+                clause
+                    .into_inner()
+                    .find(|p| p.as_rule() == Rule::identifier)
+            });
+        if let Some(ident) = ident {
+            self.record_namespace_identifier(ident, NamespaceRole::InClause);
         }
     }
 
     fn parse_import_decl(&mut self, pair: Pair<'_, Rule>) {
         // import_decl = { ^"import" ~ import_specifier ~ ^"from" ~ string_literal }
+        let span = pair.as_span();
+        self.import_ranges.push(ByteRange {
+            start: span.start(),
+            end: span.end(),
+        });
         for inner in pair.into_inner() {
             match inner.as_rule() {
                 Rule::import_named => self.parse_import_named(inner),
                 Rule::import_wildcard => self.parse_import_wildcard(inner),
+                Rule::string_literal => {
+                    let span = inner.as_span();
+                    if let Some(path) = extract_string_literal_value(inner.as_str()) {
+                        self.import_paths.push(ImportPath {
+                            path,
+                            range: ByteRange {
+                                start: span.start(),
+                                end: span.end(),
+                            },
+                        });
+                    }
+                }
                 Rule::import_specifier => {
                     for spec in inner.into_inner() {
                         match spec.as_rule() {
@@ -185,32 +402,41 @@ impl SemanticIndex {
         // import_wildcard = { "*" ~ ^"as" ~ identifier }
         if let Some(ident) = pair.into_inner().find(|p| p.as_rule() == Rule::identifier) {
             self.import_prefixes.push(ident.as_str().to_string());
+            self.record_namespace_identifier(ident, NamespaceRole::ImportAlias);
         }
     }
 
     fn parse_entity_decl(&mut self, pair: Pair<'_, Rule>) {
-        // entity_decl = { ^"entity" ~ name ~ ... }
-        if let Some(name_pair) = pair.into_inner().find(|p| p.as_rule() == Rule::name) {
-            self.record_name(SymbolKind::Entity, name_pair, true);
+        // entity_decl = { ^"entity" ~ name ~ ... ~ in_clause? }
+        let inner_pairs: Vec<Pair<'_, Rule>> = pair.into_inner().collect();
+        if let Some(name_pair) = inner_pairs.iter().find(|p| p.as_rule() == Rule::name) {
+            self.record_name(SymbolKind::Entity, name_pair.clone(), true);
         }
+        self.record_in_clause(inner_pairs);
     }
 
     fn parse_resource_decl(&mut self, pair: Pair<'_, Rule>) {
-        if let Some(name_pair) = pair.into_inner().find(|p| p.as_rule() == Rule::name) {
-            self.record_name(SymbolKind::Resource, name_pair, true);
+        let inner_pairs: Vec<Pair<'_, Rule>> = pair.into_inner().collect();
+        if let Some(name_pair) = inner_pairs.iter().find(|p| p.as_rule() == Rule::name) {
+            self.record_name(SymbolKind::Resource, name_pair.clone(), true);
         }
+        self.record_in_clause(inner_pairs);
     }
 
     fn parse_pattern_decl(&mut self, pair: Pair<'_, Rule>) {
-        if let Some(name_pair) = pair.into_inner().find(|p| p.as_rule() == Rule::name) {
-            self.record_name(SymbolKind::Pattern, name_pair, true);
+        let inner_pairs: Vec<Pair<'_, Rule>> = pair.into_inner().collect();
+        if let Some(name_pair) = inner_pairs.iter().find(|p| p.as_rule() == Rule::name) {
+            self.record_name(SymbolKind::Pattern, name_pair.clone(), true);
         }
+        self.record_in_clause(inner_pairs);
     }
 
     fn parse_role_decl(&mut self, pair: Pair<'_, Rule>) {
-        if let Some(name_pair) = pair.into_inner().find(|p| p.as_rule() == Rule::name) {
-            self.record_name(SymbolKind::Role, name_pair, true);
+        let inner_pairs: Vec<Pair<'_, Rule>> = pair.into_inner().collect();
+        if let Some(name_pair) = inner_pairs.iter().find(|p| p.as_rule() == Rule::name) {
+            self.record_name(SymbolKind::Role, name_pair.clone(), true);
         }
+        self.record_in_clause(inner_pairs);
     }
 
     fn parse_relation_decl(&mut self, pair: Pair<'_, Rule>) {
@@ -271,10 +497,9 @@ impl SemanticIndex {
             self.record_string_literal(SymbolKind::Entity, to_entity, false);
         }
 
-        let quantity = inner_pairs
-            .iter()
-            .find(|p| p.as_rule() == Rule::number)
-            .map(|p| p.as_str().to_string());
+        let number = inner_pairs.iter().find(|p| p.as_rule() == Rule::number);
+        let quantity = number.map(|p| p.as_str().to_string());
+        let unit = number.and_then(|p| inline_unit(span.as_str(), span.start(), p.as_span().end()));
 
         let resource = resource_name
             .as_ref()
@@ -295,6 +520,7 @@ impl SemanticIndex {
             from_entity: from,
             to_entity: to,
             quantity,
+            unit,
         });
 
         // Record a coarse Flow occurrence so hovering the "flow" keyword yields a Flow hover.
@@ -390,6 +616,18 @@ impl SemanticIndex {
             start: span.start(),
             end: span.end(),
         };
+        if let Some((namespace, _)) = name.split_once("::") {
+            if raw[1..].starts_with(namespace) {
+                self.namespaces.push(NamespaceOccurrence {
+                    namespace: namespace.to_string(),
+                    range: ByteRange {
+                        start: span.start() + 1,
+                        end: span.start() + 1 + namespace.len(),
+                    },
+                    role: NamespaceRole::Qualifier,
+                });
+            }
+        }
         self.record(kind, name, range, is_definition);
     }
 
@@ -428,6 +666,175 @@ impl SemanticIndex {
     }
 }
 
+/// What [`recover`] makes of a source.
+enum Recovered<'a> {
+    /// Text that parses as a program: `source` itself, or a copy with the lines that
+    /// fail to parse blanked out.
+    Source(Cow<'a, str>),
+    /// `(kind, name, range, is_definition)` of the literals on `Entity`, `Resource`
+    /// and `Flow` lines, for sources no amount of blanking makes parse.
+    Scanned(Vec<(SymbolKind, String, ByteRange, bool)>),
+}
+
+/// Recover as much of `source` as can be indexed. Lines that fail to parse are
+/// blanked with [`blank_line`] until the text parses; when that gives up, the
+/// declaration lines are scanned for their literals instead, matching keywords in
+/// any case as the grammar does.
+fn recover(source: &str) -> Recovered<'_> {
+    let mut patched = Cow::Borrowed(source);
+    for _ in 0..MAX_RECOVERY_ATTEMPTS {
+        let err = match SeaParser::parse(Rule::program, &patched) {
+            Ok(_) => return Recovered::Source(patched),
+            Err(err) => err,
+        };
+        let pos = match err.location {
+            InputLocation::Pos(pos) => pos,
+            InputLocation::Span((start, _)) => start,
+        };
+        let line = patched[..pos].matches('\n').count();
+        if !blank_line(patched.to_mut(), line) {
+            break;
+        }
+    }
+    Recovered::Scanned(scan_declarations(source))
+}
+
+fn scan_declarations(source: &str) -> Vec<(SymbolKind, String, ByteRange, bool)> {
+    let mut symbols = Vec::new();
+    let mut line_start = 0;
+    for line in source.split_inclusive('\n') {
+        let indent = line.len() - line.trim_start().len();
+        let literals = string_literals(&line[indent..], line_start + indent);
+        let keyword = line[indent..]
+            .split_whitespace()
+            .next()
+            .map(str::to_ascii_lowercase);
+        let kinds: &[(SymbolKind, bool)] = match keyword.as_deref() {
+            Some("entity") => &[(SymbolKind::Entity, true)],
+            Some("resource") => &[(SymbolKind::Resource, true)],
+            Some("flow") => &[
+                (SymbolKind::Resource, false),
+                (SymbolKind::Entity, false),
+                (SymbolKind::Entity, false),
+            ],
+            _ => &[],
+        };
+        for (&(kind, is_definition), (name, range)) in kinds.iter().zip(literals) {
+            symbols.push((kind, name, range, is_definition));
+        }
+        line_start += line.len();
+    }
+    symbols
+}
+
+/// Overwrite the 0-based `line` with spaces, keeping every byte offset valid for the
+/// original text. Returns `false` if there was nothing to blank.
+pub(crate) fn blank_line(text: &mut String, line: usize) -> bool {
+    let Some(start) = (if line == 0 {
+        Some(0)
+    } else {
+        text.match_indices('\n').nth(line - 1).map(|(i, _)| i + 1)
+    }) else {
+        return false;
+    };
+    let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+    if text[start..end].trim().is_empty() {
+        return false;
+    }
+    let blank = " ".repeat(end - start);
+    text.replace_range(start..end, &blank);
+    true
+}
+
+/// The `//` or `#` comment lines immediately above the line containing `start`, with
+/// their markers stripped. A blank line ends the block.
+fn leading_comment(source: &str, start: usize) -> Option<String> {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let mut lines: Vec<&str> = source[..line_start]
+        .lines()
+        .rev()
+        .map_while(|line| {
+            let line = line.trim();
+            line.strip_prefix("//")
+                .or_else(|| line.strip_prefix('#'))
+                .map(str::trim)
+        })
+        .collect();
+    lines.reverse();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Merge declaration ranges whose lines touch into contiguous line spans.
+fn group_adjacent_lines(line_index: &LineIndex, ranges: &[ByteRange]) -> Vec<LineSpan> {
+    let mut spans: Vec<LineSpan> = Vec::new();
+    for range in ranges {
+        let start_line = line_index.position_of(range.start).line;
+        let end_line = line_index.position_of(range.end).line;
+        match spans.last_mut() {
+            Some(last) if start_line <= last.end_line + 1 => {
+                last.end_line = last.end_line.max(end_line);
+            }
+            _ => spans.push(LineSpan {
+                start_line,
+                end_line,
+            }),
+        }
+    }
+    spans
+}
+
+/// The identifier directly following a flow quantity, if any.
+///
+/// `decl` is the flow declaration text starting at byte `decl_start` of the source;
+/// `after` is the source offset just past the quantity number.
+fn inline_unit(decl: &str, decl_start: usize, after: usize) -> Option<(String, ByteRange)> {
+    let rest = decl.get(after - decl_start..)?;
+    let skipped = rest.len() - rest.trim_start().len();
+    let unit: String = rest[skipped..]
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    if unit.is_empty() {
+        return None;
+    }
+    let start = after + skipped;
+    Some((
+        unit.clone(),
+        ByteRange {
+            start,
+            end: start + unit.len(),
+        },
+    ))
+}
+
+/// Closed `"..."` literals in `line` (which starts at byte `offset` of the source),
+/// with their values and spans.
+fn string_literals(line: &str, offset: usize) -> Vec<(String, ByteRange)> {
+    let mut literals = Vec::new();
+    let mut open = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (open, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(start), '"') => {
+                let raw = &line[start..=i];
+                let name = extract_string_literal_value(raw).unwrap_or_else(|| raw.to_string());
+                let range = ByteRange {
+                    start: offset + start,
+                    end: offset + i + 1,
+                };
+                literals.push((name, range));
+                open = None;
+            }
+            (None, '"') => open = Some(i),
+            _ => {}
+        }
+    }
+    literals
+}
+
 fn extract_string_literal_value(raw: &str) -> Option<String> {
     let unquoted = raw.strip_prefix('"').and_then(|s| s.strip_suffix('"'))?;
     Some(
@@ -490,4 +897,113 @@ Policy p as: @vendor_123 = @vendor_123
         let pos = line_index.position_of(offset);
         assert!(pos.line > 0);
     }
+
+    #[test]
+    fn unrecoverable_source_falls_back_to_a_line_scan() {
+        let source = r#"Entity "Warehouse"
+Resource "Cameras" units
+Flow "Cameras" from "Warehouse" to
+"#;
+        let index = SemanticIndex::build(source);
+
+        assert!(index.approximate);
+        assert!(index
+            .definition_range(SymbolKind::Entity, "Warehouse")
+            .is_some());
+        assert!(index
+            .definition_range(SymbolKind::Resource, "Cameras")
+            .is_some());
+        assert_eq!(
+            index
+                .reference_ranges(SymbolKind::Entity, "Warehouse")
+                .len(),
+            1
+        );
+
+        let offset = source.rfind("\"Warehouse\"").unwrap() + 2;
+        let occ = index.symbol_at_offset(offset).expect("scanned occurrence");
+        assert_eq!(occ.kind, SymbolKind::Entity);
+        assert_eq!(&source[occ.range.start..occ.range.end], "\"Warehouse\"");
+
+        assert!(!SemanticIndex::build("Entity \"Warehouse\"\n").approximate);
+    }
+
+    #[test]
+    fn line_scan_matches_keywords_in_any_case() {
+        let source = "ENTITY \"Warehouse\"\nresource \"Cameras\" units\nflow \"Cameras\" from \"Warehouse\" to\n";
+        let index = SemanticIndex::build(source);
+
+        assert!(index.approximate);
+        assert!(index
+            .definition_range(SymbolKind::Entity, "Warehouse")
+            .is_some());
+        assert_eq!(
+            index
+                .reference_ranges(SymbolKind::Resource, "Cameras")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn records_the_namespace_of_qualified_references() {
+        let source = "Instance depot of \"logistics::Warehouse\"\n";
+        let index = SemanticIndex::build(source);
+
+        let qualifiers: Vec<(&str, &str)> = index
+            .namespaces
+            .iter()
+            .filter(|n| n.role == NamespaceRole::Qualifier)
+            .map(|n| (n.namespace.as_str(), &source[n.range.start..n.range.end]))
+            .collect();
+        assert_eq!(qualifiers, vec![("logistics", "logistics")]);
+    }
+
+    #[test]
+    fn records_namespace_declaration_in_clauses_and_aliases() {
+        let source = r#"@namespace "logistics"
+import * as shared from "shared.sea"
+
+Entity "Warehouse" in logistics
+Role "Clerk" in shared
+"#;
+        let index = SemanticIndex::build(source);
+        let found: Vec<(&str, &str, NamespaceRole)> = index
+            .namespaces
+            .iter()
+            .map(|n| {
+                (
+                    n.namespace.as_str(),
+                    &source[n.range.start..n.range.end],
+                    n.role,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("logistics", "logistics", NamespaceRole::Declaration),
+                ("shared", "shared", NamespaceRole::ImportAlias),
+                ("logistics", "logistics", NamespaceRole::InClause),
+                ("shared", "shared", NamespaceRole::InClause),
+            ]
+        );
+    }
+
+    #[test]
+    fn captures_the_comment_block_above_a_declaration() {
+        let source = r#"// Not attached: a blank line follows
+
+// Where stock is kept
+# between shipments
+Entity "Warehouse"
+Entity "Factory"
+"#;
+        let index = SemanticIndex::build(source);
+        assert_eq!(
+            index.doc_comment(SymbolKind::Entity, "Warehouse"),
+            Some("Where stock is kept\nbetween shipments")
+        );
+        assert_eq!(index.doc_comment(SymbolKind::Entity, "Factory"), None);
+    }
 }
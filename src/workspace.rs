@@ -0,0 +1,1024 @@
+//! Workspace-wide indexing of `.sea` files that are not open in the editor,
+//! cross-file fixups such as rewriting imports when a file or namespace is renamed,
+//! and the template for new files.
+//!
+//! The scan is a plain iterator so the backend can interleave progress reporting
+//! with indexing, one file at a time.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use tower_lsp::lsp_types::{
+    CreateFile, DocumentChangeOperation, DocumentChanges, Location, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, SymbolInformation,
+    TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::line_index::LineIndex;
+use crate::semantic_index::{ByteRange, SemanticIndex, SymbolKind};
+
+/// Extension of SEA files when `fileExtensions` is not configured.
+pub const DEFAULT_FILE_EXTENSION: &str = "sea";
+
+/// `workspace/executeCommand` name for renaming a namespace; arguments are the old
+/// and new namespace names.
+pub const RENAME_NAMESPACE_COMMAND: &str = "domainforge.renameNamespace";
+
+/// `workspace/executeCommand` name returning the text of a new SEA file; the argument
+/// is the namespace the file declares.
+pub const NEW_FILE_COMMAND: &str = "domainforge.newFile";
+
+/// `workspace/executeCommand` name moving the declarations a selection touches into a
+/// new file; arguments are the document URI, the selected range and the namespace of
+/// the new file.
+pub const EXTRACT_TO_FILE_COMMAND: &str = "domainforge.extractToFile";
+
+/// A workspace file indexed from disk.
+#[derive(Debug, Clone)]
+pub struct IndexedFile {
+    pub line_index: LineIndex,
+    pub index: SemanticIndex,
+}
+
+/// Recursively collect files with one of `extensions` under `root`, sorted for
+/// deterministic scans.
+///
+/// Hidden directories (`.git`, `.vscode`, ...) and `target` are skipped.
+pub fn discover_sea_files(root: &Path, extensions: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let skip = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.') || n == "target");
+                if !skip {
+                    pending.push(path);
+                }
+            } else if has_extension(&path, extensions) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Resolve the path literal of an import in `importer` to an existing file.
+///
+/// Paths are relative to the importing document. A path with one of `extensions`
+/// is taken as is; otherwise each extension is tried in order.
+pub fn resolve_import(importer: &Url, path: &str, extensions: &[String]) -> Option<Url> {
+    let dir = importer.to_file_path().ok()?.parent().map(normalize)?;
+    import_candidates(&dir, path, extensions)
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| Url::from_file_path(candidate).ok())
+}
+
+/// Files an import path literal written in `dir` may name, in the order
+/// [`resolve_import`] tries them.
+fn import_candidates(dir: &Path, path: &str, extensions: &[String]) -> Vec<PathBuf> {
+    let target = normalize(&dir.join(path));
+    if has_extension(&target, extensions) {
+        return vec![target];
+    }
+    extensions
+        .iter()
+        .map(|ext| {
+            let mut name = target.clone().into_os_string();
+            name.push(format!(".{}", ext.trim_start_matches('.')));
+            PathBuf::from(name)
+        })
+        .collect()
+}
+
+/// The file an import path literal in `importer` names, whether or not it exists.
+///
+/// A path without an extension gets `DEFAULT_FILE_EXTENSION`, the file a quick fix
+/// would create for it.
+pub fn import_target(importer: &Url, path: &str) -> Option<Url> {
+    let dir = importer.to_file_path().ok()?.parent().map(normalize)?;
+    let mut target = normalize(&dir.join(path));
+    if target.extension().is_none() {
+        target.set_extension(DEFAULT_FILE_EXTENSION);
+    }
+    Url::from_file_path(target).ok()
+}
+
+/// Whether an import path literal names a file rather than a namespace: it contains
+/// a `/` or ends in one of `extensions`.
+pub fn is_file_import(path: &str, extensions: &[String]) -> bool {
+    path.contains('/') || has_extension(Path::new(path), extensions)
+}
+
+/// Whether `path` ends in one of `extensions` (given with or without the dot).
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        extensions
+            .iter()
+            .any(|ext| ext.trim_start_matches('.') == e)
+    })
+}
+
+/// One indexed file, together with overall scan progress after it.
+#[derive(Debug)]
+pub struct ScanStep {
+    pub uri: Url,
+    /// `None` when the file could not be read.
+    pub file: Option<IndexedFile>,
+    /// Percentage of the scan completed, in `1..=100`.
+    pub percentage: u32,
+}
+
+/// Iterator that indexes a fixed list of files in order.
+pub struct WorkspaceScan {
+    files: std::vec::IntoIter<PathBuf>,
+    total: usize,
+    done: usize,
+}
+
+impl WorkspaceScan {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self {
+            total: files.len(),
+            files: files.into_iter(),
+            done: 0,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+impl Iterator for WorkspaceScan {
+    type Item = ScanStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let path = self.files.next()?;
+            self.done += 1;
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let file = fs::read_to_string(&path).ok().map(|text| IndexedFile {
+                line_index: LineIndex::new(&text),
+                index: SemanticIndex::build(&text),
+            });
+            return Some(ScanStep {
+                uri,
+                file,
+                percentage: percentage(self.done, self.total),
+            });
+        }
+    }
+}
+
+/// Edits that repoint import path literals at `old_path` to `new_path`.
+///
+/// Import paths are resolved relative to the importing document the way
+/// [`resolve_import`] does, so paths without an extension match too and stay without
+/// one. Rewritten paths are relative to the importing document as well. When the
+/// renamed file itself changes directory, its own relative imports are rewritten to
+/// keep naming the same files; those edits are keyed by its new URI.
+pub fn import_rename_edits<'a>(
+    documents: impl IntoIterator<Item = (&'a Url, &'a LineIndex, &'a SemanticIndex)>,
+    old_path: &Path,
+    new_path: &Path,
+    extensions: &[String],
+) -> HashMap<Url, Vec<TextEdit>> {
+    let old_path = normalize(old_path);
+    let new_path = normalize(new_path);
+    let mut changes = HashMap::new();
+
+    for (uri, line_index, index) in documents {
+        let Some(path) = uri.to_file_path().ok().map(|p| normalize(&p)) else {
+            continue;
+        };
+        // The renamed file may still be known under either path
+        let moved = path == old_path || path == new_path;
+        let (Some(import_dir), Some(dir)) = (
+            if moved {
+                old_path.parent()
+            } else {
+                path.parent()
+            },
+            if moved {
+                new_path.parent()
+            } else {
+                path.parent()
+            },
+        ) else {
+            continue;
+        };
+        let Some(edit_uri) = (if moved {
+            Url::from_file_path(&new_path).ok()
+        } else {
+            Some(uri.clone())
+        }) else {
+            continue;
+        };
+
+        let edits: Vec<TextEdit> = index
+            .import_paths
+            .iter()
+            .filter(|import| is_file_import(&import.path, extensions))
+            .filter_map(|import| {
+                let new_literal = if import_candidates(import_dir, &import.path, extensions)
+                    .contains(&old_path)
+                {
+                    let keep_extension = has_extension(Path::new(&import.path), extensions)
+                        || !has_extension(&new_path, extensions);
+                    let target = if keep_extension {
+                        new_path.clone()
+                    } else {
+                        new_path.with_extension("")
+                    };
+                    relative_path(dir, &target)
+                } else if import_dir != dir {
+                    relative_path(dir, &normalize(&import_dir.join(&import.path)))
+                } else {
+                    return None;
+                };
+                // Keep a leading `./` the author wrote
+                let new_literal = if import.path.starts_with("./") && !new_literal.starts_with("..")
+                {
+                    format!("./{}", new_literal)
+                } else {
+                    new_literal
+                };
+                Some(TextEdit {
+                    range: Range {
+                        start: line_index.position_of(import.range.start),
+                        end: line_index.position_of(import.range.end),
+                    },
+                    new_text: format!("\"{}\"", new_literal),
+                })
+            })
+            .collect();
+        if !edits.is_empty() {
+            changes.insert(edit_uri, edits);
+        }
+    }
+
+    changes
+}
+
+/// Edits that rename namespace `old` to `new` in every document.
+///
+/// Covers `@namespace` values, `in` clauses, wildcard import aliases, the prefix of
+/// qualified `"ns::Name"` references and import paths naming the namespace, all
+/// rewritten to the same `new`. `in` clauses and aliases only take identifiers, so
+/// `None` is returned when `new` is not one.
+pub fn namespace_rename_edits<'a>(
+    documents: impl IntoIterator<Item = (&'a Url, &'a LineIndex, &'a SemanticIndex)>,
+    old: &str,
+    new: &str,
+) -> Option<HashMap<Url, Vec<TextEdit>>> {
+    if !is_identifier(new) {
+        return None;
+    }
+    let mut changes = HashMap::new();
+
+    for (uri, line_index, index) in documents {
+        let to_range = |start, end| Range {
+            start: line_index.position_of(start),
+            end: line_index.position_of(end),
+        };
+        let mut edits: Vec<TextEdit> = index
+            .namespaces
+            .iter()
+            .filter(|occ| occ.namespace == old)
+            .map(|occ| TextEdit {
+                range: to_range(occ.range.start, occ.range.end),
+                new_text: new.to_string(),
+            })
+            .collect();
+        edits.extend(
+            index
+                .import_paths
+                .iter()
+                .filter(|import| import.path == old)
+                .map(|import| TextEdit {
+                    range: to_range(import.range.start, import.range.end),
+                    new_text: format!("\"{}\"", new),
+                }),
+        );
+        if !edits.is_empty() {
+            edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+            changes.insert(uri.clone(), edits);
+        }
+    }
+
+    Some(changes)
+}
+
+/// Upper bound on `workspace/symbol` results, so a short query stays cheap to send.
+const MAX_WORKSPACE_SYMBOLS: usize = 200;
+
+/// Declarations whose name contains `query` (case-insensitively), sorted by name.
+///
+/// An empty query matches every declaration. Flows are skipped because they have
+/// no name of their own.
+pub fn workspace_symbols<'a>(
+    documents: impl IntoIterator<Item = (&'a Url, &'a LineIndex, &'a SemanticIndex)>,
+    query: &str,
+) -> Vec<SymbolInformation> {
+    let query = query.to_lowercase();
+    let mut symbols = Vec::new();
+
+    for (uri, line_index, index) in documents {
+        for occ in index.occurrences.iter().filter(|o| o.is_definition) {
+            if occ.kind == SymbolKind::Flow || !occ.name.to_lowercase().contains(&query) {
+                continue;
+            }
+            #[allow(deprecated)]
+            symbols.push(SymbolInformation {
+                name: occ.name.clone(),
+                kind: lsp_symbol_kind(occ.kind),
+                tags: None,
+                deprecated: None,
+                location: SemanticIndex::lsp_location(uri, line_index, occ.range),
+                container_name: None,
+            });
+        }
+    }
+
+    symbols.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.location.uri.as_str().cmp(b.location.uri.as_str()))
+    });
+    symbols.truncate(MAX_WORKSPACE_SYMBOLS);
+    symbols
+}
+
+/// References to the `kind` named `name` in every document, sorted by file and
+/// position, optionally including its declaration.
+pub fn workspace_references<'a>(
+    documents: impl IntoIterator<Item = (&'a Url, &'a LineIndex, &'a SemanticIndex)>,
+    kind: SymbolKind,
+    name: &str,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let mut locations = Vec::new();
+
+    for (uri, line_index, index) in documents {
+        let mut ranges = index.reference_ranges(kind, name);
+        if include_declaration {
+            ranges.extend(index.definition_range(kind, name));
+        }
+        locations.extend(
+            ranges
+                .into_iter()
+                .map(|r| SemanticIndex::lsp_location(uri, line_index, r)),
+        );
+    }
+
+    locations.sort_by(|a, b| {
+        a.uri.as_str().cmp(b.uri.as_str()).then_with(|| {
+            (a.range.start.line, a.range.start.character)
+                .cmp(&(b.range.start.line, b.range.start.character))
+        })
+    });
+    locations.dedup_by(|a, b| a.uri == b.uri && a.range == b.range);
+    locations
+}
+
+/// Boilerplate for a new file declaring `namespace`: `@namespace`, `@version` and a
+/// sample entity.
+///
+/// Returns `None` unless `namespace` is one or more identifiers joined by `.`.
+pub fn new_file_template(namespace: &str) -> Option<String> {
+    if !is_namespace_name(namespace) {
+        return None;
+    }
+
+    Some(format!(
+        "@namespace \"{}\"\n@version \"0.1.0\"\n\nEntity \"Example\"\n",
+        namespace
+    ))
+}
+
+/// Edit moving the declarations and flows overlapping `selection` out of `uri` into
+/// a sibling `<namespace>.sea` that declares `namespace`, and importing them back by
+/// name. Entities and resources the moved text refers to but that stay behind are
+/// imported into the new file from `uri`.
+///
+/// Returns `None` when the selection touches no declaration or flow, when
+/// `namespace` is not a valid namespace name, or when an imported name cannot be
+/// written as an import item.
+pub fn extract_to_file_edit(
+    uri: &Url,
+    text: &str,
+    line_index: &LineIndex,
+    index: &SemanticIndex,
+    selection: Range,
+    namespace: &str,
+) -> Option<WorkspaceEdit> {
+    if !is_namespace_name(namespace) {
+        return None;
+    }
+    let start = line_index.offset_of(selection.start)?;
+    // A bare cursor selects the declaration it is in
+    let end = line_index.offset_of(selection.end)?.max(start + 1);
+    let moved: Vec<_> = index
+        .declarations
+        .iter()
+        .filter(|decl| decl.range.start < end && start < decl.range.end)
+        .collect();
+    // Flows are named after their span, so they move without being imported back
+    let names: Vec<&str> = moved
+        .iter()
+        .filter(|decl| decl.kind != SymbolKind::Flow)
+        .map(|decl| decl.name.as_str())
+        .collect();
+    if moved.is_empty() || !names.iter().all(|name| is_identifier(name)) {
+        return None;
+    }
+    let is_moved = |range: ByteRange| {
+        moved
+            .iter()
+            .any(|decl| decl.range.start <= range.start && range.end <= decl.range.end)
+    };
+    let mut left_behind: Vec<&str> = Vec::new();
+    for occ in &index.occurrences {
+        let stays = matches!(occ.kind, SymbolKind::Entity | SymbolKind::Resource)
+            && !occ.is_definition
+            && is_moved(occ.range)
+            && index
+                .definition_range(occ.kind, &occ.name)
+                .is_some_and(|def| !is_moved(def));
+        if stays && !left_behind.contains(&occ.name.as_str()) {
+            left_behind.push(&occ.name);
+        }
+    }
+    if !left_behind.iter().all(|name| is_identifier(name)) {
+        return None;
+    }
+    let file_name = format!("{}.{}", namespace, DEFAULT_FILE_EXTENSION);
+    let new_uri = uri.join(&file_name).ok()?;
+
+    let mut content = format!("@namespace \"{}\"\n", namespace);
+    if !left_behind.is_empty() {
+        let original_name = uri.path_segments()?.next_back()?;
+        content.push_str(&format!(
+            "import {{ {} }} from \"{}\"\n",
+            left_behind.join(", "),
+            original_name
+        ));
+    }
+    for decl in &moved {
+        content.push('\n');
+        content.push_str(text[decl.range.start..decl.range.end].trim_end());
+        content.push('\n');
+    }
+
+    // Imports follow the file's annotations and any existing imports
+    let import_line = index
+        .import_blocks
+        .iter()
+        .map(|block| block.end_line + 1)
+        .chain(
+            index
+                .annotations
+                .iter()
+                .map(|a| line_index.position_of(a.range.end).line + 1),
+        )
+        .max()
+        .unwrap_or(0);
+    let at_import = Position::new(import_line, 0);
+    let mut original_edits = Vec::new();
+    if !names.is_empty() {
+        original_edits.push(TextEdit {
+            range: Range::new(at_import, at_import),
+            new_text: format!("import {{ {} }} from \"{}\"\n", names.join(", "), file_name),
+        });
+    }
+    for decl in &moved {
+        // Take the line break after the declaration with it
+        let end = if text[decl.range.end..].starts_with('\n') {
+            decl.range.end + 1
+        } else {
+            decl.range.end
+        };
+        original_edits.push(TextEdit {
+            range: Range::new(
+                line_index.position_of(decl.range.start),
+                line_index.position_of(end),
+            ),
+            new_text: String::new(),
+        });
+    }
+
+    let text_edit = |uri: &Url, edits: Vec<TextEdit>| {
+        DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: None,
+            },
+            edits: edits.into_iter().map(OneOf::Left).collect(),
+        })
+    };
+    let origin = Position::new(0, 0);
+    Some(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(vec![
+            DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                uri: new_uri.clone(),
+                options: None,
+                annotation_id: None,
+            })),
+            text_edit(
+                &new_uri,
+                vec![TextEdit {
+                    range: Range::new(origin, origin),
+                    new_text: content,
+                }],
+            ),
+            text_edit(uri, original_edits),
+        ])),
+        ..Default::default()
+    })
+}
+
+/// One or more identifiers joined by `.`.
+fn is_namespace_name(namespace: &str) -> bool {
+    namespace.split('.').all(is_identifier)
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// LSP symbol kind used when listing declarations of `kind`.
+pub(crate) fn lsp_symbol_kind(kind: SymbolKind) -> tower_lsp::lsp_types::SymbolKind {
+    use tower_lsp::lsp_types::SymbolKind as Lsp;
+    match kind {
+        SymbolKind::Entity => Lsp::CLASS,
+        SymbolKind::Resource => Lsp::CONSTANT,
+        SymbolKind::Flow => Lsp::EVENT,
+        SymbolKind::Pattern => Lsp::STRING,
+        SymbolKind::Role => Lsp::INTERFACE,
+        SymbolKind::Relation => Lsp::PROPERTY,
+        SymbolKind::Instance => Lsp::VARIABLE,
+        SymbolKind::Policy => Lsp::FUNCTION,
+    }
+}
+
+/// Lexically resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Path from directory `from` to `to`, using `/` separators as import literals do.
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+fn percentage(done: usize, total: usize) -> u32 {
+    if total == 0 {
+        return 100;
+    }
+    ((done.min(total) * 100) / total) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edits_for(op: &DocumentChangeOperation) -> (Url, Vec<TextEdit>) {
+        match op {
+            DocumentChangeOperation::Edit(edit) => (
+                edit.text_document.uri.clone(),
+                edit.edits
+                    .iter()
+                    .map(|e| match e {
+                        OneOf::Left(e) => e.clone(),
+                        OneOf::Right(e) => e.text_edit.clone(),
+                    })
+                    .collect(),
+            ),
+            other => panic!("expected a text edit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn extract_to_file_creates_the_file_and_imports_the_moved_entity() {
+        let text = "@namespace \"logistics\"\n\nEntity \"Warehouse\"\nEntity \"Factory\"\n";
+        let uri = Url::parse("file:///ws/main.sea").unwrap();
+        let line_index = LineIndex::new(text);
+        let index = SemanticIndex::build(text);
+        let selection = Range::new(Position::new(2, 0), Position::new(2, 18));
+
+        let edit =
+            extract_to_file_edit(&uri, text, &line_index, &index, selection, "storage").unwrap();
+        let Some(DocumentChanges::Operations(ops)) = edit.document_changes else {
+            panic!("expected document change operations");
+        };
+        let new_uri = Url::parse("file:///ws/storage.sea").unwrap();
+
+        assert_eq!(ops.len(), 3);
+        assert!(matches!(
+            &ops[0],
+            DocumentChangeOperation::Op(ResourceOp::Create(create)) if create.uri == new_uri
+        ));
+        let (uri_1, created) = edits_for(&ops[1]);
+        assert_eq!(uri_1, new_uri);
+        assert_eq!(
+            created[0].new_text,
+            "@namespace \"storage\"\n\nEntity \"Warehouse\"\n"
+        );
+        let (uri_2, original) = edits_for(&ops[2]);
+        assert_eq!(uri_2, uri);
+        assert_eq!(
+            original[0],
+            TextEdit {
+                range: Range::new(Position::new(1, 0), Position::new(1, 0)),
+                new_text: "import { Warehouse } from \"storage.sea\"\n".to_string(),
+            }
+        );
+        assert_eq!(
+            original[1].range,
+            Range::new(Position::new(2, 0), Position::new(3, 0))
+        );
+        assert!(original[1].new_text.is_empty());
+
+        let nothing = Range::new(Position::new(1, 0), Position::new(1, 0));
+        assert!(
+            extract_to_file_edit(&uri, text, &line_index, &index, nothing, "storage").is_none()
+        );
+    }
+
+    #[test]
+    fn extract_to_file_moves_flows_and_imports_what_stays_behind() {
+        let text = "Entity \"Warehouse\"\nEntity \"Factory\"\nResource \"Cameras\" units\n\
+                    Flow \"Cameras\" from \"Warehouse\" to \"Factory\" quantity 5\n";
+        let uri = Url::parse("file:///ws/main.sea").unwrap();
+        let line_index = LineIndex::new(text);
+        let index = SemanticIndex::build(text);
+        let selection = Range::new(Position::new(1, 0), Position::new(3, 4));
+
+        let edit =
+            extract_to_file_edit(&uri, text, &line_index, &index, selection, "storage").unwrap();
+        let Some(DocumentChanges::Operations(ops)) = edit.document_changes else {
+            panic!("expected document change operations");
+        };
+
+        let (_, created) = edits_for(&ops[1]);
+        assert_eq!(
+            created[0].new_text,
+            "@namespace \"storage\"\n\
+             import { Warehouse } from \"main.sea\"\n\
+             \nEntity \"Factory\"\n\
+             \nResource \"Cameras\" units\n\
+             \nFlow \"Cameras\" from \"Warehouse\" to \"Factory\" quantity 5\n"
+        );
+        let (_, original) = edits_for(&ops[2]);
+        assert_eq!(
+            original[0].new_text,
+            "import { Factory, Cameras } from \"storage.sea\"\n"
+        );
+        let removed: Vec<_> = original[1..].iter().map(|e| e.range.start.line).collect();
+        assert_eq!(removed, vec![1, 2, 3]);
+
+        // A lone flow imports everything it names and leaves nothing to import back
+        let flow_only = Range::new(Position::new(3, 0), Position::new(3, 4));
+        let edit =
+            extract_to_file_edit(&uri, text, &line_index, &index, flow_only, "storage").unwrap();
+        let Some(DocumentChanges::Operations(ops)) = edit.document_changes else {
+            panic!("expected document change operations");
+        };
+        let (_, created) = edits_for(&ops[1]);
+        assert!(created[0]
+            .new_text
+            .contains("import { Cameras, Warehouse, Factory } from \"main.sea\"\n"));
+        let (_, original) = edits_for(&ops[2]);
+        assert_eq!(original.len(), 1);
+        assert!(original[0].new_text.is_empty());
+    }
+
+    #[test]
+    fn scan_reports_increasing_percentages_for_each_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        fs::create_dir(&nested).unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("a.sea"), "Entity \"A\"\n").unwrap();
+        fs::write(dir.path().join("b.sea"), "Entity \"B\"\n").unwrap();
+        fs::write(nested.join("c.sea"), "Entity \"C\"\n").unwrap();
+        fs::write(dir.path().join(".git").join("ignored.sea"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not sea").unwrap();
+
+        let files = discover_sea_files(dir.path(), &[DEFAULT_FILE_EXTENSION.to_string()]);
+        assert_eq!(files.len(), 3, "unexpected files: {files:?}");
+
+        let steps: Vec<ScanStep> = WorkspaceScan::new(files).collect();
+        let percentages: Vec<u32> = steps.iter().map(|s| s.percentage).collect();
+        assert_eq!(percentages, vec![33, 66, 100]);
+        assert!(steps.iter().all(|s| s.file.is_some()));
+    }
+
+    #[test]
+    fn renaming_an_imported_file_rewrites_the_import_literal() {
+        let source = "import * as logistics from \"logistics.sea\"\n\nEntity \"Depot\"\n";
+        let uri = Url::parse("file:///ws/main.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+
+        let changes = import_rename_edits(
+            [(&uri, &line_index, &index)],
+            Path::new("/ws/logistics.sea"),
+            Path::new("/ws/shared/freight.sea"),
+            &[DEFAULT_FILE_EXTENSION.to_string()],
+        );
+
+        let edits = changes.get(&uri).expect("importing document is edited");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "\"shared/freight.sea\"");
+        let start = line_index.offset_of(edits[0].range.start).unwrap();
+        let end = line_index.offset_of(edits[0].range.end).unwrap();
+        assert_eq!(&source[start..end], "\"logistics.sea\"");
+    }
+
+    #[test]
+    fn renaming_a_file_rewrites_imports_without_an_extension() {
+        let source =
+            "import * as logistics from \"./logistics\"\nimport * as ns from \"logistics\"\n";
+        let uri = Url::parse("file:///ws/main.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+
+        let changes = import_rename_edits(
+            [(&uri, &line_index, &index)],
+            Path::new("/ws/logistics.dsea"),
+            Path::new("/ws/shared/freight.dsea"),
+            &["sea".to_string(), "dsea".to_string()],
+        );
+
+        // The bare `"logistics"` names a namespace, not the file
+        let edits = changes.get(&uri).expect("importing document is edited");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "\"./shared/freight\"");
+        assert_eq!(edits[0].range.start.line, 0);
+    }
+
+    #[test]
+    fn moving_a_file_rewrites_its_own_relative_imports() {
+        let source = "import * as common from \"common.sea\"\nimport * as ns from \"logistics\"\n";
+        let old_uri = Url::parse("file:///ws/main.sea").unwrap();
+        let line_index = LineIndex::new(source);
+        let index = SemanticIndex::build(source);
+        let extensions = [DEFAULT_FILE_EXTENSION.to_string()];
+
+        let changes = import_rename_edits(
+            [(&old_uri, &line_index, &index)],
+            Path::new("/ws/main.sea"),
+            Path::new("/ws/models/main.sea"),
+            &extensions,
+        );
+        let new_uri = Url::parse("file:///ws/models/main.sea").unwrap();
+        let edits = changes.get(&new_uri).expect("moved document is edited");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "\"../common.sea\"");
+
+        // Renaming within the same directory leaves its imports alone
+        let changes = import_rename_edits(
+            [(&old_uri, &line_index, &index)],
+            Path::new("/ws/main.sea"),
+            Path::new("/ws/app.sea"),
+            &extensions,
+        );
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn renaming_a_namespace_updates_declaration_and_references_across_files() {
+        let declaring = "@namespace \"logistics\"\n\nEntity \"Depot\" in logistics\n";
+        let importing = "import * as logistics from \"logistics\"\n\nRole \"Clerk\" in logistics\n";
+        let declaring_uri = Url::parse("file:///ws/logistics.sea").unwrap();
+        let importing_uri = Url::parse("file:///ws/main.sea").unwrap();
+        let (declaring_lines, declaring_index) =
+            (LineIndex::new(declaring), SemanticIndex::build(declaring));
+        let (importing_lines, importing_index) =
+            (LineIndex::new(importing), SemanticIndex::build(importing));
+
+        let changes = namespace_rename_edits(
+            [
+                (&declaring_uri, &declaring_lines, &declaring_index),
+                (&importing_uri, &importing_lines, &importing_index),
+            ],
+            "logistics",
+            "freight",
+        )
+        .unwrap();
+
+        let apply = |text: &str, line_index: &LineIndex, edits: &[TextEdit]| {
+            let mut out = text.to_string();
+            for edit in edits.iter().rev() {
+                let start = line_index.offset_of(edit.range.start).unwrap();
+                let end = line_index.offset_of(edit.range.end).unwrap();
+                out.replace_range(start..end, &edit.new_text);
+            }
+            out
+        };
+        assert_eq!(
+            apply(declaring, &declaring_lines, &changes[&declaring_uri]),
+            "@namespace \"freight\"\n\nEntity \"Depot\" in freight\n"
+        );
+        assert_eq!(
+            apply(importing, &importing_lines, &changes[&importing_uri]),
+            "import * as freight from \"freight\"\n\nRole \"Clerk\" in freight\n"
+        );
+    }
+
+    #[test]
+    fn renaming_a_namespace_rewrites_qualified_references_and_alias_uses() {
+        let declaring =
+            "@namespace \"logistics\"\n\nEntity \"Depot\"\nInstance d of \"logistics::Depot\"\n";
+        let importing = "import * as logistics from \"logistics\"\n\nEntity \"Market\"\nResource \"Crates\" units\nFlow \"Crates\" from \"logistics::Depot\" to \"Market\"\n";
+        let declaring_uri = Url::parse("file:///ws/logistics.sea").unwrap();
+        let importing_uri = Url::parse("file:///ws/main.sea").unwrap();
+        let (declaring_lines, declaring_index) =
+            (LineIndex::new(declaring), SemanticIndex::build(declaring));
+        let (importing_lines, importing_index) =
+            (LineIndex::new(importing), SemanticIndex::build(importing));
+
+        let changes = namespace_rename_edits(
+            [
+                (&declaring_uri, &declaring_lines, &declaring_index),
+                (&importing_uri, &importing_lines, &importing_index),
+            ],
+            "logistics",
+            "freight",
+        )
+        .unwrap();
+
+        let apply = |text: &str, line_index: &LineIndex, edits: &[TextEdit]| {
+            let mut out = text.to_string();
+            for edit in edits.iter().rev() {
+                let start = line_index.offset_of(edit.range.start).unwrap();
+                let end = line_index.offset_of(edit.range.end).unwrap();
+                out.replace_range(start..end, &edit.new_text);
+            }
+            out
+        };
+        assert_eq!(
+            apply(declaring, &declaring_lines, &changes[&declaring_uri]),
+            "@namespace \"freight\"\n\nEntity \"Depot\"\nInstance d of \"freight::Depot\"\n"
+        );
+        assert_eq!(
+            apply(importing, &importing_lines, &changes[&importing_uri]),
+            "import * as freight from \"freight\"\n\nEntity \"Market\"\nResource \"Crates\" units\nFlow \"Crates\" from \"freight::Depot\" to \"Market\"\n"
+        );
+    }
+
+    #[test]
+    fn renaming_a_namespace_refuses_names_in_clauses_cannot_hold() {
+        let source = "@namespace \"logistics\"\n\nEntity \"Depot\" in logistics\n";
+        let uri = Url::parse("file:///ws/logistics.sea").unwrap();
+        let (line_index, index) = (LineIndex::new(source), SemanticIndex::build(source));
+
+        for invalid in ["a.b", "a::b", "1st", ""] {
+            let changes =
+                namespace_rename_edits([(&uri, &line_index, &index)], "logistics", invalid);
+            assert!(changes.is_none(), "{invalid:?} should be refused");
+        }
+    }
+
+    #[test]
+    fn workspace_symbols_match_query_across_files() {
+        let first = "Entity \"Warehouse\"\nResource \"Cameras\" units\n";
+        let second = "Entity \"WarRoom\"\nEntity \"Factory\"\n";
+        let first_uri = Url::parse("file:///ws/a.sea").unwrap();
+        let second_uri = Url::parse("file:///ws/b.sea").unwrap();
+        let (first_lines, first_index) = (LineIndex::new(first), SemanticIndex::build(first));
+        let (second_lines, second_index) = (LineIndex::new(second), SemanticIndex::build(second));
+
+        let symbols = workspace_symbols(
+            [
+                (&first_uri, &first_lines, &first_index),
+                (&second_uri, &second_lines, &second_index),
+            ],
+            "war",
+        );
+
+        let found: Vec<(&str, &str)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.location.uri.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("WarRoom", "file:///ws/b.sea"),
+                ("Warehouse", "file:///ws/a.sea")
+            ]
+        );
+        assert_eq!(symbols[0].kind, tower_lsp::lsp_types::SymbolKind::CLASS);
+    }
+
+    #[test]
+    fn workspace_references_span_every_file() {
+        let first = "Entity \"Depot\"\nResource \"Parcel\" units\n";
+        let second = "Entity \"Hub\"\nFlow \"Parcel\" from \"Depot\" to \"Hub\"\n";
+        let first_uri = Url::parse("file:///ws/a.sea").unwrap();
+        let second_uri = Url::parse("file:///ws/b.sea").unwrap();
+        let (first_lines, first_index) = (LineIndex::new(first), SemanticIndex::build(first));
+        let (second_lines, second_index) = (LineIndex::new(second), SemanticIndex::build(second));
+        let documents = [
+            (&first_uri, &first_lines, &first_index),
+            (&second_uri, &second_lines, &second_index),
+        ];
+
+        let references = workspace_references(documents, SymbolKind::Entity, "Depot", false);
+        let found: Vec<(&str, u32)> = references
+            .iter()
+            .map(|l| (l.uri.as_str(), l.range.start.line))
+            .collect();
+        assert_eq!(found, vec![("file:///ws/b.sea", 1)]);
+
+        let with_declaration = workspace_references(documents, SymbolKind::Entity, "Depot", true);
+        let found: Vec<(&str, u32)> = with_declaration
+            .iter()
+            .map(|l| (l.uri.as_str(), l.range.start.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("file:///ws/a.sea", 0), ("file:///ws/b.sea", 1)]
+        );
+    }
+
+    #[test]
+    fn new_file_template_parses_and_declares_the_namespace() {
+        let template = new_file_template("acme.logistics").expect("valid namespace");
+        assert!(template.starts_with("@namespace \"acme.logistics\"\n"));
+        sea_core::parse_to_graph(&template).expect("template parses");
+
+        for invalid in ["", "acme..logistics", "1st", "acme logistics", "\"quoted\""] {
+            assert_eq!(new_file_template(invalid), None, "accepted {invalid:?}");
+        }
+    }
+
+    #[test]
+    fn imports_resolve_against_configured_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("main.sea"),
+            "import * as shared from \"shared\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("shared.df"), "Entity \"Depot\"\n").unwrap();
+        let importer = Url::from_file_path(dir.path().join("main.sea")).unwrap();
+        let expected = Url::from_file_path(dir.path().join("shared.df")).unwrap();
+
+        let default = [DEFAULT_FILE_EXTENSION.to_string()];
+        assert_eq!(resolve_import(&importer, "shared", &default), None);
+        assert_eq!(resolve_import(&importer, "shared.df", &default), None);
+
+        let configured = ["sea".to_string(), ".df".to_string()];
+        assert_eq!(
+            resolve_import(&importer, "shared", &configured),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            resolve_import(&importer, "./shared.df", &configured),
+            Some(expected)
+        );
+        assert_eq!(discover_sea_files(dir.path(), &configured).len(), 2);
+    }
+}